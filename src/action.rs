@@ -0,0 +1,360 @@
+mod binding;
+mod format;
+mod map;
+
+pub use binding::{Binding, AxisDirection, AxisSide, InputDevice};
+pub use map::ActionMap;
+
+use crate::error::GameResult;
+use crate::event::KeyState;
+use crate::math::Vector;
+use crate::keyboard::Keyboard;
+use crate::mouse::Mouse;
+use crate::gamepad::{Gamepad, GamepadId};
+use std::collections::HashMap;
+
+struct ActionState {
+    state: KeyState,
+    value: f32,
+}
+
+struct Player {
+    map: ActionMap,
+    devices: Vec<InputDevice>,
+    action_states: HashMap<String, ActionState>,
+    axis_values: HashMap<String, Vector>,
+}
+
+impl Player {
+    fn new(map: ActionMap, devices: Vec<InputDevice>) -> Self {
+        Self {
+            map,
+            devices,
+            action_states: HashMap::new(),
+            axis_values: HashMap::new(),
+        }
+    }
+
+    fn uses_keyboard_mouse(&self) -> bool {
+        self.devices.contains(&InputDevice::KeyboardMouse)
+    }
+}
+
+pub struct Actions {
+    players: Vec<Player>,
+    dead_zone: f32,
+    press_threshold: f32,
+}
+
+impl Actions {
+    pub(crate) fn new(actions_config: ActionsConfig) -> GameResult<Self> {
+        Ok(Self {
+            players: vec![Player::new(actions_config.action_map, vec![InputDevice::KeyboardMouse, InputDevice::AnyGamepad])],
+            dead_zone: actions_config.dead_zone,
+            press_threshold: actions_config.press_threshold,
+        })
+    }
+
+    pub(crate) fn reset_states(&mut self) {
+        for player in self.players.iter_mut() {
+            player.action_states.clear();
+            player.axis_values.clear();
+        }
+    }
+
+    pub(crate) fn update(&mut self, keyboard: &Keyboard, mouse: &Mouse, gamepad: &Gamepad) {
+        self.update_with(keyboard, Some(mouse), gamepad);
+    }
+
+    fn update_with(&mut self, keyboard: &Keyboard, mouse: Option<&Mouse>, gamepad: &Gamepad) {
+        let assigned_ids = self.players.iter()
+            .flat_map(|player| player.devices.iter())
+            .filter_map(|device| match device {
+                InputDevice::Gamepad(id) => Some(*id),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        for player in self.players.iter_mut() {
+            let mut gamepad_ids = Vec::new();
+            for device in player.devices.iter() {
+                match device {
+                    InputDevice::KeyboardMouse => (),
+                    InputDevice::Gamepad(id) => {
+                        if gamepad.is_connected(*id) {
+                            gamepad_ids.push(*id);
+                        }
+                    }
+                    InputDevice::AnyGamepad => {
+                        gamepad_ids.extend(gamepad.connected_ids().filter(|id| !assigned_ids.contains(id)));
+                    }
+                }
+            }
+            let sources = Sources {
+                keyboard_mouse: player.uses_keyboard_mouse(),
+                gamepad_ids: &gamepad_ids,
+                keyboard,
+                mouse,
+                gamepad,
+            };
+
+            let mut action_states = std::mem::take(&mut player.action_states);
+            for action in player.map.actions() {
+                let bindings = match player.map.action(action) {
+                    Some(bindings) => bindings,
+                    None => continue,
+                };
+                let dead_zone = bindings.dead_zone.unwrap_or(self.dead_zone);
+                let value = bindings.bindings.iter()
+                    .map(|binding| {
+                        let value = sources.value(binding);
+                        if binding.is_analog() {
+                            apply_dead_zone(value, dead_zone)
+                        } else {
+                            value
+                        }
+                    })
+                    .fold(0.0, f32::max);
+                let pressed = value > 0.0 && value >= self.press_threshold;
+                let was_pressed = action_states.get(action)
+                    .map(|action| matches!(action.state, KeyState::Down | KeyState::Hold))
+                    .unwrap_or(false);
+                let state = match (was_pressed, pressed) {
+                    (false, true) => KeyState::Down,
+                    (true, true) => KeyState::Hold,
+                    (true, false) => KeyState::Up,
+                    (false, false) => KeyState::Idle,
+                };
+                action_states.insert(action.to_owned(), ActionState { state, value });
+            }
+            action_states.retain(|_, action| action.state != KeyState::Idle || action.value > 0.0);
+            player.action_states = action_states;
+
+            player.axis_values.clear();
+            for axis in player.map.axes() {
+                let bindings = match player.map.axis(axis) {
+                    Some(bindings) => bindings,
+                    None => continue,
+                };
+                let side = |index: usize| bindings.sides[index].iter()
+                    .map(|binding| sources.value(binding))
+                    .fold(0.0, f32::max);
+                let vector = Vector::new(side(1) - side(0), side(3) - side(2));
+                let dead_zone = bindings.dead_zone.unwrap_or(self.dead_zone);
+                let length = (vector.x * vector.x + vector.y * vector.y).sqrt();
+                let value = if length <= dead_zone {
+                    Vector::zero()
+                } else {
+                    let scale = apply_dead_zone(length.min(1.0), dead_zone) / length;
+                    Vector::new(vector.x * scale, vector.y * scale)
+                };
+                player.axis_values.insert(axis.to_owned(), value);
+            }
+        }
+    }
+
+    fn player(&self, player: usize) -> &Player {
+        self.players.get(player).expect("can not find action player")
+    }
+
+    fn player_mut(&mut self, player: usize) -> &mut Player {
+        self.players.get_mut(player).expect("can not find action player")
+    }
+
+    pub fn player_count(&self) -> usize {
+        self.players.len()
+    }
+
+    pub fn add_player(&mut self, map: ActionMap, devices: Vec<InputDevice>) -> usize {
+        self.players.push(Player::new(map, devices));
+        self.players.len() - 1
+    }
+
+    pub fn player_map(&self, player: usize) -> &ActionMap {
+        &self.player(player).map
+    }
+
+    pub fn player_map_mut(&mut self, player: usize) -> &mut ActionMap {
+        &mut self.player_mut(player).map
+    }
+
+    pub fn set_player_map(&mut self, player: usize, map: ActionMap) {
+        let player = self.player_mut(player);
+        player.map = map;
+        player.action_states.clear();
+        player.axis_values.clear();
+    }
+
+    pub fn player_devices(&self, player: usize) -> &[InputDevice] {
+        &self.player(player).devices
+    }
+
+    pub fn set_player_devices(&mut self, player: usize, devices: Vec<InputDevice>) {
+        self.player_mut(player).devices = devices;
+    }
+
+    pub fn dead_zone(&self) -> f32 {
+        self.dead_zone
+    }
+
+    pub fn set_dead_zone(&mut self, dead_zone: f32) {
+        self.dead_zone = dead_zone.clamp(0.0, 0.99);
+    }
+
+    pub fn press_threshold(&self) -> f32 {
+        self.press_threshold
+    }
+
+    pub fn set_press_threshold(&mut self, press_threshold: f32) {
+        self.press_threshold = press_threshold.clamp(0.0, 1.0);
+    }
+
+    fn action_state(&self, player: usize, action: &str) -> Option<&ActionState> {
+        self.players.get(player).and_then(|player| player.action_states.get(action))
+    }
+
+    pub fn is_action_down(&self, player: usize, action: &str) -> bool {
+        matches!(self.action_state(player, action).map(|action| action.state), Some(KeyState::Down))
+    }
+
+    pub fn is_action_hold(&self, player: usize, action: &str) -> bool {
+        matches!(self.action_state(player, action).map(|action| action.state), Some(KeyState::Down | KeyState::Hold))
+    }
+
+    pub fn is_action_up(&self, player: usize, action: &str) -> bool {
+        matches!(self.action_state(player, action).map(|action| action.state), Some(KeyState::Up))
+    }
+
+    pub fn action_value(&self, player: usize, action: &str) -> f32 {
+        self.action_state(player, action)
+            .map(|action| action.value)
+            .unwrap_or(0.0)
+    }
+
+    pub fn axis_value(&self, player: usize, axis: &str) -> Vector {
+        self.players.get(player)
+            .and_then(|player| player.axis_values.get(axis))
+            .copied()
+            .unwrap_or_else(Vector::zero)
+    }
+}
+
+struct Sources<'a> {
+    keyboard_mouse: bool,
+    gamepad_ids: &'a [GamepadId],
+    keyboard: &'a Keyboard,
+    mouse: Option<&'a Mouse>,
+    gamepad: &'a Gamepad,
+}
+
+impl Sources<'_> {
+    fn value(&self, binding: &Binding) -> f32 {
+        match binding {
+            Binding::Key(key) => {
+                if self.keyboard_mouse && self.keyboard.is_key_hold(*key) { 1.0 } else { 0.0 }
+            }
+            Binding::MouseButton(button) => {
+                let pressed = self.mouse.map(|mouse| mouse.is_button_hold(*button)).unwrap_or(false);
+                if self.keyboard_mouse && pressed { 1.0 } else { 0.0 }
+            }
+            Binding::GamepadButton(button) => self.gamepad_ids.iter()
+                .map(|id| {
+                    let pressed = if self.gamepad.is_button_hold(*id, *button) { 1.0 } else { 0.0 };
+                    self.gamepad.button_value(*id, *button).max(pressed)
+                })
+                .fold(0.0, f32::max),
+            Binding::GamepadAxis(axis, direction) => self.gamepad_ids.iter()
+                .map(|id| (self.gamepad.axis_value(*id, *axis) * direction.sign()).max(0.0))
+                .fold(0.0, f32::max),
+        }
+    }
+}
+
+fn apply_dead_zone(value: f32, dead_zone: f32) -> f32 {
+    if value <= dead_zone {
+        0.0
+    } else {
+        ((value - dead_zone) / (1.0 - dead_zone)).min(1.0)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ActionsConfig {
+    action_map: ActionMap,
+    dead_zone: f32,
+    press_threshold: f32,
+}
+
+impl ActionsConfig {
+    pub fn new() -> Self {
+        Self {
+            action_map: ActionMap::new(),
+            dead_zone: 0.2,
+            press_threshold: 0.5,
+        }
+    }
+
+    pub fn action_map(mut self, action_map: ActionMap) -> Self {
+        self.action_map = action_map;
+        self
+    }
+
+    pub fn dead_zone(mut self, dead_zone: f32) -> Self {
+        self.dead_zone = dead_zone.clamp(0.0, 0.99);
+        self
+    }
+
+    pub fn press_threshold(mut self, press_threshold: f32) -> Self {
+        self.press_threshold = press_threshold.clamp(0.0, 1.0);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Actions, ActionsConfig, ActionMap, InputDevice};
+    use crate::event::KeyAction;
+    use crate::keyboard::{Keyboard, KeyboardConfig, KeyCode};
+    use crate::gamepad::{Gamepad, GamepadConfig, GamepadAxis, GamepadId};
+    use crate::filesystem::{Filesystem, FilesystemConfig};
+
+    #[test]
+    fn test_actions() {
+        let mut gamepad = Gamepad::new(GamepadConfig::new(), &Filesystem::new(FilesystemConfig::new().roots(Vec::new())).unwrap()).unwrap();
+        let mut keyboard = Keyboard::new(KeyboardConfig::new()).unwrap();
+        let mut map = ActionMap::new();
+        map.bind_action("jump", KeyCode::Space);
+        map.bind_axis_keys("move", KeyCode::A, KeyCode::D, KeyCode::S, KeyCode::W);
+        map.bind_axis_stick("move", GamepadAxis::LeftStickX, GamepadAxis::LeftStickY);
+        let mut actions = Actions::new(ActionsConfig::new().action_map(map.clone())).unwrap();
+        let pad = GamepadId::from(7);
+        let second = actions.add_player(map, vec![InputDevice::Gamepad(pad)]);
+
+        keyboard.handle_input_event(KeyCode::Space, KeyAction::Down);
+        keyboard.handle_input_event(KeyCode::D, KeyAction::Down);
+        keyboard.handle_input_event(KeyCode::W, KeyAction::Down);
+        gamepad.handle_connect_event(pad);
+        gamepad.handle_axis_change_event(pad, GamepadAxis::LeftStickX, -0.1);
+        actions.update_with(&keyboard, None, &gamepad);
+        assert!(actions.is_action_down(0, "jump"));
+        assert_eq!(actions.action_value(0, "jump"), 1.0);
+        let movement = actions.axis_value(0, "move");
+        assert!(((movement.x * movement.x + movement.y * movement.y).sqrt() - 1.0).abs() < 1e-4);
+        assert!(movement.x > 0.0 && movement.y > 0.0);
+        assert!(!actions.is_action_hold(second, "jump"));
+        assert_eq!(actions.axis_value(second, "move").x, 0.0);
+
+        keyboard.clear_states();
+        gamepad.handle_axis_change_event(pad, GamepadAxis::LeftStickX, -1.0);
+        actions.update_with(&keyboard, None, &gamepad);
+        assert!(actions.is_action_hold(0, "jump") && !actions.is_action_down(0, "jump"));
+        assert!((actions.axis_value(second, "move").x + 1.0).abs() < 1e-4);
+        assert_eq!(actions.axis_value(0, "move"), movement);
+
+        keyboard.handle_input_event(KeyCode::Space, KeyAction::Up);
+        actions.update_with(&keyboard, None, &gamepad);
+        assert!(actions.is_action_up(0, "jump"));
+        keyboard.clear_states();
+        actions.update_with(&keyboard, None, &gamepad);
+        assert!(!actions.is_action_up(0, "jump") && !actions.is_action_hold(0, "jump"));
+    }
+}
@@ -0,0 +1,76 @@
+use crate::keyboard::KeyCode;
+use crate::mouse::MouseButton;
+use crate::gamepad::{GamepadButton, GamepadAxis, GamepadId};
+
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum AxisDirection {
+    Positive,
+    Negative,
+}
+
+impl AxisDirection {
+    pub(crate) fn sign(&self) -> f32 {
+        match self {
+            Self::Positive => 1.0,
+            Self::Negative => -1.0,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum AxisSide {
+    NegativeX,
+    PositiveX,
+    NegativeY,
+    PositiveY,
+}
+
+impl AxisSide {
+    pub(crate) fn index(&self) -> usize {
+        match self {
+            Self::NegativeX => 0,
+            Self::PositiveX => 1,
+            Self::NegativeY => 2,
+            Self::PositiveY => 3,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum Binding {
+    Key(KeyCode),
+    MouseButton(MouseButton),
+    GamepadButton(GamepadButton),
+    GamepadAxis(GamepadAxis, AxisDirection),
+}
+
+impl Binding {
+    pub fn is_analog(&self) -> bool {
+        matches!(self, Self::GamepadButton(_) | Self::GamepadAxis(_, _))
+    }
+}
+
+impl From<KeyCode> for Binding {
+    fn from(key: KeyCode) -> Self {
+        Self::Key(key)
+    }
+}
+
+impl From<MouseButton> for Binding {
+    fn from(button: MouseButton) -> Self {
+        Self::MouseButton(button)
+    }
+}
+
+impl From<GamepadButton> for Binding {
+    fn from(button: GamepadButton) -> Self {
+        Self::GamepadButton(button)
+    }
+}
+
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum InputDevice {
+    KeyboardMouse,
+    Gamepad(GamepadId),
+    AnyGamepad,
+}
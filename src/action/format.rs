@@ -0,0 +1,102 @@
+use super::binding::{Binding, AxisDirection};
+use crate::keyboard::KeyCode;
+use crate::mouse::MouseButton;
+use crate::gamepad::{GamepadButton, GamepadAxis};
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct ActionMapFile {
+    #[serde(default)]
+    pub actions: BTreeMap<String, ActionFile>,
+    #[serde(default)]
+    pub axes: BTreeMap<String, AxisFile>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct ActionFile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dead_zone: Option<f32>,
+    #[serde(default)]
+    pub bindings: Vec<BindingFile>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct AxisFile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dead_zone: Option<f32>,
+    #[serde(default)]
+    pub negative_x: Vec<BindingFile>,
+    #[serde(default)]
+    pub positive_x: Vec<BindingFile>,
+    #[serde(default)]
+    pub negative_y: Vec<BindingFile>,
+    #[serde(default)]
+    pub positive_y: Vec<BindingFile>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) enum BindingFile {
+    Key(KeyCode),
+    MouseButton(MouseButton),
+    GamepadButton(GamepadButton),
+    GamepadAxis(GamepadAxis, DirectionFile),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) enum DirectionFile {
+    Positive,
+    Negative,
+}
+
+impl From<Binding> for BindingFile {
+    fn from(binding: Binding) -> Self {
+        match binding {
+            Binding::Key(key) => Self::Key(key),
+            Binding::MouseButton(button) => Self::MouseButton(button),
+            Binding::GamepadButton(button) => Self::GamepadButton(button),
+            Binding::GamepadAxis(axis, direction) => Self::GamepadAxis(axis, match direction {
+                AxisDirection::Positive => DirectionFile::Positive,
+                AxisDirection::Negative => DirectionFile::Negative,
+            }),
+        }
+    }
+}
+
+impl From<BindingFile> for Binding {
+    fn from(binding: BindingFile) -> Self {
+        match binding {
+            BindingFile::Key(key) => Self::Key(key),
+            BindingFile::MouseButton(button) => Self::MouseButton(button),
+            BindingFile::GamepadButton(button) => Self::GamepadButton(button),
+            BindingFile::GamepadAxis(axis, direction) => Self::GamepadAxis(axis, match direction {
+                DirectionFile::Positive => AxisDirection::Positive,
+                DirectionFile::Negative => AxisDirection::Negative,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BindingFile, DirectionFile};
+    use crate::keyboard::KeyCode;
+    use crate::gamepad::GamepadAxis;
+
+    #[test]
+    fn test_variants() {
+        let bindings = vec![
+            BindingFile::Key(KeyCode::Space),
+            BindingFile::Key(KeyCode::Other(99)),
+            BindingFile::GamepadAxis(GamepadAxis::LeftZ, DirectionFile::Negative),
+        ];
+        let json = serde_json::to_string(&bindings).unwrap();
+        assert_eq!(json, "[{\"Key\":\"Space\"},{\"Key\":{\"Other\":99}},{\"GamepadAxis\":[\"LeftZ\",\"Negative\"]}]");
+        let ron = ron::to_string(&bindings).unwrap();
+        assert_eq!(ron, "[Key(Space),Key(Other(99)),GamepadAxis(LeftZ,Negative)]");
+        assert_eq!(format!("{:?}", serde_json::from_str::<Vec<BindingFile>>(&json).unwrap()), format!("{:?}", bindings));
+        assert_eq!(format!("{:?}", ron::from_str::<Vec<BindingFile>>(&ron).unwrap()), format!("{:?}", bindings));
+        assert!(serde_json::from_str::<Vec<BindingFile>>("[{\"Key\":\"Nope\"}]").is_err());
+        assert!(ron::from_str::<Vec<BindingFile>>("[Key(Other)]").is_err());
+    }
+}
@@ -0,0 +1,307 @@
+use super::binding::{Binding, AxisDirection, AxisSide};
+use super::format::{ActionMapFile, ActionFile, AxisFile, BindingFile};
+use crate::error::{GameError, GameResult};
+use crate::keyboard::KeyCode;
+use crate::gamepad::GamepadAxis;
+use crate::filesystem::Filesystem;
+use ron::ser::PrettyConfig;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct ActionBindings {
+    pub bindings: Vec<Binding>,
+    pub dead_zone: Option<f32>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct AxisBindings {
+    pub sides: [Vec<Binding>; 4],
+    pub dead_zone: Option<f32>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ActionMap {
+    actions: BTreeMap<String, ActionBindings>,
+    axes: BTreeMap<String, AxisBindings>,
+}
+
+impl ActionMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn bind_action(&mut self, action: impl Into<String>, binding: impl Into<Binding>) {
+        let bindings = &mut self.actions.entry(action.into()).or_default().bindings;
+        let binding = binding.into();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+    }
+
+    pub fn unbind_action(&mut self, action: &str, binding: impl Into<Binding>) -> bool {
+        let binding = binding.into();
+        match self.actions.get_mut(action) {
+            Some(action) => {
+                let len = action.bindings.len();
+                action.bindings.retain(|bound| *bound != binding);
+                action.bindings.len() != len
+            }
+            None => false,
+        }
+    }
+
+    pub fn rebind_action(&mut self, action: &str, from: impl Into<Binding>, to: impl Into<Binding>) -> bool {
+        let (from, to) = (from.into(), to.into());
+        match self.actions.get_mut(action).and_then(|action| action.bindings.iter_mut().find(|bound| **bound == from)) {
+            Some(bound) => {
+                *bound = to;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn clear_action(&mut self, action: &str) {
+        if let Some(action) = self.actions.get_mut(action) {
+            action.bindings.clear();
+        }
+    }
+
+    pub fn remove_action(&mut self, action: &str) -> bool {
+        self.actions.remove(action).is_some()
+    }
+
+    pub fn action_bindings(&self, action: &str) -> &[Binding] {
+        self.actions.get(action)
+            .map(|action| action.bindings.as_slice())
+            .unwrap_or(&[])
+    }
+
+    pub fn action_dead_zone(&self, action: &str) -> Option<f32> {
+        self.actions.get(action).and_then(|action| action.dead_zone)
+    }
+
+    pub fn set_action_dead_zone(&mut self, action: impl Into<String>, dead_zone: Option<f32>) {
+        self.actions.entry(action.into()).or_default().dead_zone = dead_zone.map(|dead_zone| dead_zone.clamp(0.0, 0.99));
+    }
+
+    pub fn actions(&self) -> impl Iterator<Item = &str> {
+        self.actions.keys().map(String::as_str)
+    }
+
+    pub fn bind_axis(&mut self, axis: impl Into<String>, side: AxisSide, binding: impl Into<Binding>) {
+        let bindings = &mut self.axes.entry(axis.into()).or_default().sides[side.index()];
+        let binding = binding.into();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+    }
+
+    pub fn bind_axis_stick(&mut self, axis: impl Into<String>, x: GamepadAxis, y: GamepadAxis) {
+        let axis = axis.into();
+        self.bind_axis(axis.clone(), AxisSide::NegativeX, Binding::GamepadAxis(x, AxisDirection::Negative));
+        self.bind_axis(axis.clone(), AxisSide::PositiveX, Binding::GamepadAxis(x, AxisDirection::Positive));
+        self.bind_axis(axis.clone(), AxisSide::NegativeY, Binding::GamepadAxis(y, AxisDirection::Negative));
+        self.bind_axis(axis, AxisSide::PositiveY, Binding::GamepadAxis(y, AxisDirection::Positive));
+    }
+
+    pub fn bind_axis_keys(&mut self, axis: impl Into<String>, left: KeyCode, right: KeyCode, down: KeyCode, up: KeyCode) {
+        let axis = axis.into();
+        self.bind_axis(axis.clone(), AxisSide::NegativeX, left);
+        self.bind_axis(axis.clone(), AxisSide::PositiveX, right);
+        self.bind_axis(axis.clone(), AxisSide::NegativeY, down);
+        self.bind_axis(axis, AxisSide::PositiveY, up);
+    }
+
+    pub fn unbind_axis(&mut self, axis: &str, side: AxisSide, binding: impl Into<Binding>) -> bool {
+        let binding = binding.into();
+        match self.axes.get_mut(axis) {
+            Some(axis) => {
+                let bindings = &mut axis.sides[side.index()];
+                let len = bindings.len();
+                bindings.retain(|bound| *bound != binding);
+                bindings.len() != len
+            }
+            None => false,
+        }
+    }
+
+    pub fn rebind_axis(&mut self, axis: &str, side: AxisSide, from: impl Into<Binding>, to: impl Into<Binding>) -> bool {
+        let (from, to) = (from.into(), to.into());
+        match self.axes.get_mut(axis).and_then(|axis| axis.sides[side.index()].iter_mut().find(|bound| **bound == from)) {
+            Some(bound) => {
+                *bound = to;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn remove_axis(&mut self, axis: &str) -> bool {
+        self.axes.remove(axis).is_some()
+    }
+
+    pub fn axis_bindings(&self, axis: &str, side: AxisSide) -> &[Binding] {
+        self.axes.get(axis)
+            .map(|axis| axis.sides[side.index()].as_slice())
+            .unwrap_or(&[])
+    }
+
+    pub fn axis_dead_zone(&self, axis: &str) -> Option<f32> {
+        self.axes.get(axis).and_then(|axis| axis.dead_zone)
+    }
+
+    pub fn set_axis_dead_zone(&mut self, axis: impl Into<String>, dead_zone: Option<f32>) {
+        self.axes.entry(axis.into()).or_default().dead_zone = dead_zone.map(|dead_zone| dead_zone.clamp(0.0, 0.99));
+    }
+
+    pub fn axes(&self) -> impl Iterator<Item = &str> {
+        self.axes.keys().map(String::as_str)
+    }
+
+    pub(crate) fn action(&self, action: &str) -> Option<&ActionBindings> {
+        self.actions.get(action)
+    }
+
+    pub(crate) fn axis(&self, axis: &str) -> Option<&AxisBindings> {
+        self.axes.get(axis)
+    }
+
+    pub fn to_json(&self) -> GameResult<String> {
+        let mut output = serde_json::to_string_pretty(&self.to_file())
+            .map_err(|error| GameError::RuntimeError(format!("can not write action map json: {}", error).into()))?;
+        output.push('\n');
+        Ok(output)
+    }
+
+    pub fn from_json(source: &str) -> GameResult<Self> {
+        let file = serde_json::from_str(source)
+            .map_err(|error| GameError::RuntimeError(format!("invalid action map json: {}", error).into()))?;
+        Ok(Self::from_file(file))
+    }
+
+    pub fn to_ron(&self) -> GameResult<String> {
+        let mut output = ron::ser::to_string_pretty(&self.to_file(), PrettyConfig::new())
+            .map_err(|error| GameError::RuntimeError(format!("can not write action map ron: {}", error).into()))?;
+        output.push('\n');
+        Ok(output)
+    }
+
+    pub fn from_ron(source: &str) -> GameResult<Self> {
+        let file = ron::from_str(source)
+            .map_err(|error| GameError::RuntimeError(format!("invalid action map ron: {}", error).into()))?;
+        Ok(Self::from_file(file))
+    }
+
+    pub fn save(&self, filesystem: &Filesystem, path: impl AsRef<Path>) -> GameResult {
+        let path = path.as_ref();
+        let source = match file_format(path)? {
+            FileFormat::Json => self.to_json()?,
+            FileFormat::Ron => self.to_ron()?,
+        };
+        filesystem.write_atomic(path, source)
+    }
+
+    pub fn load(filesystem: &Filesystem, path: impl AsRef<Path>) -> GameResult<Self> {
+        let path = path.as_ref();
+        let format = file_format(path)?;
+        let source = filesystem.read_to_string(path)?;
+        match format {
+            FileFormat::Json => Self::from_json(&source),
+            FileFormat::Ron => Self::from_ron(&source),
+        }
+    }
+
+    fn to_file(&self) -> ActionMapFile {
+        let to_files = |bindings: &[Binding]| bindings.iter().copied().map(BindingFile::from).collect();
+        let actions = self.actions.iter()
+            .map(|(name, action)| (name.clone(), ActionFile {
+                dead_zone: action.dead_zone,
+                bindings: to_files(&action.bindings),
+            }))
+            .collect();
+        let axes = self.axes.iter()
+            .map(|(name, axis)| (name.clone(), AxisFile {
+                dead_zone: axis.dead_zone,
+                negative_x: to_files(&axis.sides[AxisSide::NegativeX.index()]),
+                positive_x: to_files(&axis.sides[AxisSide::PositiveX.index()]),
+                negative_y: to_files(&axis.sides[AxisSide::NegativeY.index()]),
+                positive_y: to_files(&axis.sides[AxisSide::PositiveY.index()]),
+            }))
+            .collect();
+        ActionMapFile { actions, axes }
+    }
+
+    fn from_file(file: ActionMapFile) -> Self {
+        let from_files = |bindings: Vec<BindingFile>| bindings.into_iter().map(Binding::from).collect();
+        let mut map = Self::new();
+        for (name, action) in file.actions {
+            map.actions.entry(name.clone()).or_default().bindings = from_files(action.bindings);
+            map.set_action_dead_zone(name, action.dead_zone);
+        }
+        for (name, axis) in file.axes {
+            map.axes.entry(name.clone()).or_default().sides = [
+                from_files(axis.negative_x),
+                from_files(axis.positive_x),
+                from_files(axis.negative_y),
+                from_files(axis.positive_y),
+            ];
+            map.set_axis_dead_zone(name, axis.dead_zone);
+        }
+        map
+    }
+}
+
+enum FileFormat {
+    Json,
+    Ron,
+}
+
+fn file_format(path: &Path) -> GameResult<FileFormat> {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some(extension) if extension.eq_ignore_ascii_case("json") => Ok(FileFormat::Json),
+        Some(extension) if extension.eq_ignore_ascii_case("ron") => Ok(FileFormat::Ron),
+        _ => Err(GameError::NotSupportedError(format!("action map format of `{}` is not supported", path.display()).into())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ActionMap;
+    use crate::action::{Binding, AxisDirection, AxisSide};
+    use crate::keyboard::KeyCode;
+    use crate::mouse::MouseButton;
+    use crate::gamepad::{GamepadButton, GamepadAxis};
+
+    #[test]
+    fn test_formats() {
+        let mut map = ActionMap::new();
+        map.bind_action("jump", KeyCode::Space);
+        map.bind_action("jump", GamepadButton::South);
+        map.bind_action("fire", MouseButton::Left);
+        map.bind_action("fire", KeyCode::Other(99));
+        map.bind_action("brake", Binding::GamepadAxis(GamepadAxis::LeftZ, AxisDirection::Positive));
+        map.set_action_dead_zone("brake", Some(0.1));
+        map.bind_axis_keys("move", KeyCode::A, KeyCode::D, KeyCode::S, KeyCode::W);
+        map.bind_axis_stick("move", GamepadAxis::LeftStickX, GamepadAxis::LeftStickY);
+        assert_eq!(map.action_bindings("jump"), &[Binding::Key(KeyCode::Space), Binding::GamepadButton(GamepadButton::South)]);
+        assert_eq!(map.axis_bindings("move", AxisSide::PositiveY).len(), 2);
+        assert_eq!(ActionMap::from_json(&map.to_json().unwrap()).unwrap(), map);
+        assert_eq!(ActionMap::from_ron(&map.to_ron().unwrap()).unwrap(), map);
+        assert!(map.rebind_action("jump", KeyCode::Space, KeyCode::Enter));
+        assert!(!map.rebind_action("jump", KeyCode::Space, KeyCode::Enter));
+        assert!(map.unbind_action("fire", MouseButton::Left));
+        assert_eq!(map.action_bindings("fire"), &[Binding::Key(KeyCode::Other(99))]);
+        let map = ActionMap::from_ron("(actions: {\"jump\": (bindings: [Key(Space)])})").unwrap();
+        assert_eq!(map.action_bindings("jump"), &[Binding::Key(KeyCode::Space)]);
+        let map = ActionMap::from_json("{\"actions\": {\"jump\": {\"bindings\": [{\"GamepadAxis\": [\"LeftStickY\", \"Negative\"]}]}}}").unwrap();
+        assert_eq!(map.action_bindings("jump"), &[Binding::GamepadAxis(GamepadAxis::LeftStickY, AxisDirection::Negative)]);
+        assert!(ActionMap::from_json("{\"actions\": {\"jump\": {\"bindings\": [{\"Key\": \"Nope\"}]}}}").is_err());
+        let map = ActionMap::from_json("{\"actions\": {\"jump\": {\"dead_zone\": 1.5}}, \"axes\": {\"move\": {\"dead_zone\": -1}}}").unwrap();
+        assert_eq!(map.action_dead_zone("jump"), Some(0.99));
+        assert_eq!(map.axis_dead_zone("move"), Some(0.0));
+        let map = ActionMap::from_ron("(axes: {\"move\": (dead_zone: Some(2.0))})").unwrap();
+        assert_eq!(map.axis_dead_zone("move"), Some(0.99));
+    }
+}
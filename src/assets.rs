@@ -0,0 +1,271 @@
+use crate::error::GameResult;
+use crate::engine::Engine;
+use crate::filesystem::normalize_path;
+use crate::graphics::{Texture, Font, Program};
+use crate::audio::{Sound, Music};
+use std::any::{Any, TypeId};
+use std::rc::Rc;
+use std::path::{Path, PathBuf, Component};
+use std::collections::HashMap;
+
+fn asset_path(path: &Path) -> GameResult<String> {
+    if path.is_absolute() {
+        let mut normalized = PathBuf::new();
+        for component in path.components() {
+            match component {
+                Component::CurDir => (),
+                Component::ParentDir => {
+                    normalized.pop();
+                }
+                component => normalized.push(component),
+            }
+        }
+        Ok(normalized.to_string_lossy().into_owned())
+    } else {
+        normalize_path(path)
+    }
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+struct AssetKey {
+    type_id: TypeId,
+    paths: Vec<String>,
+}
+
+struct AssetEntry {
+    asset: Rc<dyn Any>,
+    hit_count: u64,
+    last_used_scene: u64,
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct AssetUsage {
+    pub hit_count: u64,
+    pub reference_count: usize,
+    pub used_in_current_scene: bool,
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct AssetStats {
+    pub asset_count: usize,
+    pub hit_count: u64,
+    pub miss_count: u64,
+    pub unload_count: u64,
+}
+
+pub struct Assets {
+    entries: HashMap<AssetKey, AssetEntry>,
+    scene: u64,
+    stats: AssetStats,
+}
+
+impl Assets {
+    pub(crate) fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+            scene: 0,
+            stats: AssetStats::default(),
+        }
+    }
+
+    fn key<T: 'static>(paths: &[&Path]) -> GameResult<AssetKey> {
+        let paths = paths.iter()
+            .map(|path| asset_path(path))
+            .collect::<GameResult<Vec<_>>>()?;
+        Ok(AssetKey { type_id: TypeId::of::<T>(), paths })
+    }
+
+    fn get<T: 'static>(&mut self, key: &AssetKey) -> Option<Rc<T>> {
+        let scene = self.scene;
+        let entry = self.entries.get_mut(key)?;
+        let asset = entry.asset.clone().downcast::<T>().ok()?;
+        entry.hit_count += 1;
+        entry.last_used_scene = scene;
+        self.stats.hit_count += 1;
+        Some(asset)
+    }
+
+    fn insert<T: 'static>(&mut self, key: AssetKey, asset: T) -> Rc<T> {
+        let asset = Rc::new(asset);
+        self.entries.insert(key, AssetEntry {
+            asset: asset.clone(),
+            hit_count: 0,
+            last_used_scene: self.scene,
+        });
+        self.stats.miss_count += 1;
+        asset
+    }
+
+    fn load_paths<T, F>(engine: &mut Engine, paths: &[&Path], load: F) -> GameResult<Rc<T>>
+        where
+            T: 'static,
+            F: FnOnce(&mut Engine) -> GameResult<T>,
+    {
+        let key = Self::key::<T>(paths)?;
+        if let Some(asset) = engine.assets().get(&key) {
+            return Ok(asset);
+        }
+        let asset = load(engine)?;
+        Ok(engine.assets().insert(key, asset))
+    }
+
+    pub fn load_with<T, F>(engine: &mut Engine, path: impl AsRef<Path>, load: F) -> GameResult<Rc<T>>
+        where
+            T: 'static,
+            F: FnOnce(&mut Engine, &Path) -> GameResult<T>,
+    {
+        let path = path.as_ref();
+        Self::load_paths(engine, &[path], |engine| load(engine, path))
+    }
+
+    pub fn texture(engine: &mut Engine, path: impl AsRef<Path>) -> GameResult<Rc<Texture>> {
+        Self::load_with(engine, path, |engine, path| Texture::load(engine, path))
+    }
+
+    pub fn font(engine: &mut Engine, path: impl AsRef<Path>) -> GameResult<Rc<Font>> {
+        Self::load_with(engine, path, |engine, path| Font::load(engine, path))
+    }
+
+    pub fn program(engine: &mut Engine, vertex_shader_path: impl AsRef<Path>, fragment_shader_path: impl AsRef<Path>) -> GameResult<Rc<Program>> {
+        let vertex_shader_path = vertex_shader_path.as_ref();
+        let fragment_shader_path = fragment_shader_path.as_ref();
+        Self::load_paths(engine, &[vertex_shader_path, fragment_shader_path], |engine| {
+            Program::load(engine, vertex_shader_path, fragment_shader_path)
+        })
+    }
+
+    pub fn sound(engine: &mut Engine, path: impl AsRef<Path>) -> GameResult<Rc<Sound>> {
+        Self::load_with(engine, path, |engine, path| Sound::load(engine, path))
+    }
+
+    pub fn music(engine: &mut Engine, path: impl AsRef<Path>) -> GameResult<Rc<Music>> {
+        Self::load_with(engine, path, |engine, path| Music::load(engine, path))
+    }
+
+    pub fn contains(&self, path: impl AsRef<Path>) -> bool {
+        match asset_path(path.as_ref()) {
+            Ok(path) => self.entries.keys().any(|key| key.paths.contains(&path)),
+            Err(_) => false,
+        }
+    }
+
+    pub fn usage(&self, path: impl AsRef<Path>) -> Option<AssetUsage> {
+        let path = asset_path(path.as_ref()).ok()?;
+        let mut usage = None;
+        for (key, entry) in self.entries.iter() {
+            if key.paths.contains(&path) {
+                let usage = usage.get_or_insert_with(AssetUsage::default);
+                usage.hit_count += entry.hit_count;
+                usage.reference_count += Rc::strong_count(&entry.asset) - 1;
+                usage.used_in_current_scene |= entry.last_used_scene == self.scene;
+            }
+        }
+        usage
+    }
+
+    pub fn unload(&mut self, path: impl AsRef<Path>) -> bool {
+        let path = match asset_path(path.as_ref()) {
+            Ok(path) => path,
+            Err(_) => return false,
+        };
+        let count = self.entries.len();
+        self.entries.retain(|key, _| !key.paths.contains(&path));
+        let unload_count = count - self.entries.len();
+        self.stats.unload_count += unload_count as u64;
+        unload_count > 0
+    }
+
+    pub fn unload_all(&mut self) {
+        self.stats.unload_count += self.entries.len() as u64;
+        self.entries.clear();
+    }
+
+    pub fn mark_scene_change(&mut self) {
+        self.scene += 1;
+    }
+
+    pub fn purge_unused(&mut self) -> usize {
+        let scene = self.scene;
+        let count = self.entries.len();
+        self.entries.retain(|_, entry| entry.last_used_scene == scene || Rc::strong_count(&entry.asset) > 1);
+        let purge_count = count - self.entries.len();
+        self.stats.unload_count += purge_count as u64;
+        purge_count
+    }
+
+    pub fn stats(&self) -> AssetStats {
+        AssetStats {
+            asset_count: self.entries.len(),
+            ..self.stats
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Assets, asset_path};
+    use crate::engine::EngineBuilder;
+    use crate::graphics::{Texture, Filter, FilterMode, Wrap, WrapMode};
+    use std::path::Path;
+
+    #[test]
+    fn test_cache() {
+        let mut assets = Assets::new();
+        let key = Assets::key::<String>(&[Path::new("./images/../images/a.png")]).unwrap();
+        assert!(assets.get::<String>(&key).is_none());
+        let asset = assets.insert(key.clone(), String::from("a"));
+        let shared = assets.get::<String>(&key).unwrap();
+        assert!(std::rc::Rc::ptr_eq(&asset, &shared));
+        assert!(assets.get::<u32>(&Assets::key::<u32>(&[Path::new("images/a.png")]).unwrap()).is_none());
+        assert!(assets.contains("images/a.png"));
+        let usage = assets.usage("images/a.png").unwrap();
+        assert_eq!(usage.hit_count, 1);
+        assert_eq!(usage.reference_count, 2);
+        let stats = assets.stats();
+        assert_eq!((stats.asset_count, stats.hit_count, stats.miss_count), (1, 1, 1));
+        assert!(assets.unload("images/a.png"));
+        assert!(!assets.contains("images/a.png"));
+        assert_eq!(*asset, "a");
+    }
+
+    #[test]
+    fn test_purge_unused() {
+        let mut assets = Assets::new();
+        let held_key = Assets::key::<u32>(&[Path::new("held")]).unwrap();
+        let used_key = Assets::key::<u32>(&[Path::new("used")]).unwrap();
+        let stale_key = Assets::key::<u32>(&[Path::new("stale")]).unwrap();
+        let held = assets.insert(held_key, 1u32);
+        drop(assets.insert(used_key.clone(), 2u32));
+        drop(assets.insert(stale_key, 3u32));
+        assets.mark_scene_change();
+        assert!(assets.get::<u32>(&used_key).is_some());
+        assert_eq!(assets.purge_unused(), 1);
+        assert!(assets.contains("held"));
+        assert!(assets.contains("used"));
+        assert!(!assets.contains("stale"));
+        assert_eq!(assets.stats().unload_count, 1);
+        assert_eq!(*held, 1);
+    }
+
+    #[test]
+    fn test_absolute_path() {
+        assert_eq!(asset_path(Path::new("/assets/./images/../a.png")).unwrap(), "/assets/a.png");
+        assert_eq!(asset_path(Path::new("/../a.png")).unwrap(), "/a.png");
+        let key = Assets::key::<u32>(&[Path::new("/assets/a.png")]).unwrap();
+        assert_eq!(key, Assets::key::<u32>(&[Path::new("/assets/images/../a.png")]).unwrap());
+    }
+
+    #[test]
+    fn test_shared_texture_state() {
+        let mut engine = EngineBuilder::new().headless(true).build().unwrap();
+        let texture = Assets::load_with(&mut engine, "a.png", |engine, _| Texture::new(engine.graphics(), (2, 2), None)).unwrap();
+        let filter = Filter::new(FilterMode::Nearest, FilterMode::Nearest, Some(FilterMode::Linear));
+        let wrap = Wrap::uv(WrapMode::MirroredRepeat, WrapMode::ClampToEdge);
+        texture.set_filter(filter);
+        texture.set_wrap(wrap);
+        let shared = Assets::load_with(&mut engine, "a.png", |_, _| -> crate::error::GameResult<Texture> { unreachable!() }).unwrap();
+        assert!(std::rc::Rc::ptr_eq(&texture, &shared));
+        assert_eq!(shared.filter(), filter);
+        assert_eq!(shared.wrap(), wrap);
+    }
+}
@@ -1,22 +1,381 @@
-use crate::error::GameResult;
+mod decoder;
+mod sound;
+mod music;
+mod params;
+mod bus;
+mod spatial;
+mod stream;
+mod voice;
+mod mixer;
+mod output;
 
-pub struct Audio {}
+use decoder::Decoder;
+use sound::SoundData;
+use bus::{BusState, duration_to_frames};
+use spatial::{Listener, Spatialized};
+use stream::MusicStream;
+use voice::{VoiceSource, VoiceState};
+use mixer::{Mixer, lock_mixer};
+use output::Output;
+
+pub use sound::Sound;
+pub use music::Music;
+pub use params::PlayParams;
+pub use bus::{AudioBus, Ducking};
+pub use spatial::{Attenuation, SpatialSettings};
+pub use voice::{VoiceId, Voice};
+pub use output::AudioBackend;
+
+use crate::error::{GameError, GameResult};
+use crate::math::{Vector, Position};
+use std::sync::{Arc, Mutex};
+
+pub struct Audio {
+    output: Output,
+    mixer: Arc<Mutex<Mixer>>,
+}
 
 impl Audio {
-    pub(crate) fn new(_: AudioConfig) -> GameResult<Self> {
-        Ok(Self {})
+    pub(crate) fn new(audio_config: AudioConfig, headless: bool) -> GameResult<Self> {
+        let backend = if headless { AudioBackend::Null } else { audio_config.backend };
+        let new_output = |backend| Output::new(backend, audio_config.sample_rate, audio_config.max_voices, audio_config.spatial_settings);
+        let (output, mixer) = match new_output(backend) {
+            Ok(output) => output,
+            Err(_) if backend == AudioBackend::Device => new_output(AudioBackend::Null)?,
+            Err(error) => return Err(error),
+        };
+        Ok(Self {
+            output,
+            mixer,
+        })
+    }
+
+    pub(crate) fn suspend(&mut self) {
+        self.output.pause();
+    }
+
+    pub(crate) fn resume(&mut self) {
+        self.output.play();
+    }
+
+    fn play(&mut self, source: VoiceSource, default_bus: AudioBus, params: PlayParams) -> GameResult<Voice> {
+        let bus = params.bus.unwrap_or(default_bus);
+        let id = lock_mixer(&self.mixer).add_voice(source, bus, params)
+            .ok_or_else(|| GameError::RuntimeError("audio voices are exhausted".into()))?;
+        Ok(Voice::new(self.mixer.clone(), id))
+    }
+
+    pub fn play_sound(&mut self, sound: &Sound, params: PlayParams) -> GameResult<Voice> {
+        let source = VoiceSource::Sound {
+            data: sound.data().clone(),
+            frame: 0,
+        };
+        self.play(source, AudioBus::Sfx, params)
+    }
+
+    pub fn play_music(&mut self, music: &Music, params: PlayParams) -> GameResult<Voice> {
+        let source = VoiceSource::Music(MusicStream::new(music)?);
+        self.play(source, AudioBus::Music, params)
+    }
+
+    pub fn stop_all(&mut self) {
+        lock_mixer(&self.mixer).clear_voices();
+    }
+
+    pub fn bus_volume(&self, bus: AudioBus) -> f32 {
+        lock_mixer(&self.mixer).bus(bus).map(|bus| bus.volume).unwrap_or(1.0)
+    }
+
+    pub fn set_bus_volume(&mut self, bus: AudioBus, volume: f32) {
+        lock_mixer(&self.mixer).bus_mut(bus).volume = volume.max(0.0);
+    }
+
+    pub fn is_bus_muted(&self, bus: AudioBus) -> bool {
+        lock_mixer(&self.mixer).bus(bus).map(|bus| bus.muted).unwrap_or(false)
+    }
+
+    pub fn set_bus_muted(&mut self, bus: AudioBus, muted: bool) {
+        lock_mixer(&self.mixer).bus_mut(bus).muted = muted;
     }
 
-    pub(crate) fn suspend(&mut self) {}
+    pub fn bus_ducking(&self, bus: AudioBus) -> Option<Ducking> {
+        lock_mixer(&self.mixer).bus(bus).and_then(|bus| bus.ducking)
+    }
+
+    pub fn set_bus_ducking(&mut self, bus: AudioBus, ducking: Option<Ducking>) {
+        lock_mixer(&self.mixer).bus_mut(bus).ducking = ducking;
+    }
 
-    pub(crate) fn resume(&mut self) {}
+    pub fn listener_position(&self) -> Position {
+        lock_mixer(&self.mixer).listener().position
+    }
+
+    pub fn set_listener_position(&mut self, position: impl Into<Position>) {
+        lock_mixer(&self.mixer).listener_mut().position = position.into();
+    }
+
+    pub fn listener_velocity(&self) -> Vector {
+        lock_mixer(&self.mixer).listener().velocity
+    }
+
+    pub fn set_listener_velocity(&mut self, velocity: impl Into<Vector>) {
+        lock_mixer(&self.mixer).listener_mut().velocity = velocity.into();
+    }
+
+    pub fn spatial_settings(&self) -> SpatialSettings {
+        lock_mixer(&self.mixer).spatial_settings()
+    }
+
+    pub fn set_spatial_settings(&mut self, spatial_settings: SpatialSettings) {
+        lock_mixer(&self.mixer).set_spatial_settings(spatial_settings);
+    }
+
+    pub fn backend(&self) -> AudioBackend {
+        self.output.backend()
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        lock_mixer(&self.mixer).sample_rate()
+    }
+
+    pub fn max_voices(&self) -> usize {
+        lock_mixer(&self.mixer).max_voices()
+    }
+
+    pub fn voice_count(&self) -> usize {
+        lock_mixer(&self.mixer).voice_count()
+    }
+
+    pub fn render(&mut self, buffer: &mut [f32]) -> GameResult {
+        match self.output.backend() {
+            AudioBackend::Null => {
+                lock_mixer(&self.mixer).render(buffer);
+                Ok(())
+            }
+            backend => Err(GameError::StateError(format!("audio can not be rendered manually on backend `{:?}`", backend).into())),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
-pub struct AudioConfig {}
+pub struct AudioConfig {
+    backend: AudioBackend,
+    sample_rate: u32,
+    max_voices: usize,
+    spatial_settings: SpatialSettings,
+}
 
 impl AudioConfig {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            backend: AudioBackend::default(),
+            sample_rate: 44100,
+            max_voices: 64,
+            spatial_settings: SpatialSettings::new(),
+        }
+    }
+
+    pub fn backend(mut self, backend: AudioBackend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    pub fn sample_rate(mut self, sample_rate: u32) -> Self {
+        self.sample_rate = sample_rate;
+        self
+    }
+
+    pub fn max_voices(mut self, max_voices: usize) -> Self {
+        self.max_voices = max_voices;
+        self
+    }
+
+    pub fn spatial_settings(mut self, spatial_settings: SpatialSettings) -> Self {
+        self.spatial_settings = spatial_settings;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Audio, AudioConfig, AudioBackend, AudioBus, Ducking, Attenuation, SpatialSettings, Sound, Music, PlayParams};
+    use std::time::Duration;
+
+    fn null_audio(max_voices: usize) -> Audio {
+        Audio::new(AudioConfig::new()
+            .backend(AudioBackend::Null)
+            .sample_rate(100)
+            .max_voices(max_voices), false).unwrap()
+    }
+
+    pub(super) fn wav_bytes(sample_rate: u32, samples: &[i16]) -> Vec<u8> {
+        let data_size = (samples.len() * 2) as u32;
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(36 + data_size).to_le_bytes());
+        bytes.extend_from_slice(b"WAVEfmt ");
+        bytes.extend_from_slice(&16_u32.to_le_bytes());
+        bytes.extend_from_slice(&1_u16.to_le_bytes());
+        bytes.extend_from_slice(&1_u16.to_le_bytes());
+        bytes.extend_from_slice(&sample_rate.to_le_bytes());
+        bytes.extend_from_slice(&(sample_rate * 2).to_le_bytes());
+        bytes.extend_from_slice(&2_u16.to_le_bytes());
+        bytes.extend_from_slice(&16_u16.to_le_bytes());
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&data_size.to_le_bytes());
+        for sample in samples {
+            bytes.extend_from_slice(&sample.to_le_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_decode_wav() {
+        let bytes = wav_bytes(100, &[16384, -16384, 0, 32767]);
+        let sound = Sound::from_bytes(&bytes).unwrap();
+        assert_eq!(sound.channels(), 1);
+        assert_eq!(sound.sample_rate(), 100);
+        assert_eq!(sound.samples(), &[0.5, -0.5, 0.0, 32767.0 / 32768.0]);
+        let music = Music::from_bytes(&bytes).unwrap();
+        assert_eq!(music.channels(), 1);
+        assert!(Sound::from_bytes(b"not audio").is_err());
+    }
+
+    #[test]
+    fn test_backend_fallback() {
+        assert_eq!(Audio::new(AudioConfig::new(), true).unwrap().backend(), AudioBackend::Null);
+        assert!(Audio::new(AudioConfig::new(), false).is_ok());
+    }
+
+    #[test]
+    fn test_play_sound() {
+        let mut audio = null_audio(8);
+        let sound = Sound::new(1, 100, vec![0.5, 0.25, 0.125]).unwrap();
+        let voice = audio.play_sound(&sound, PlayParams::default().pan(1.0)).unwrap();
+        assert!(voice.is_playing());
+        let mut buffer = [0.0; 8];
+        audio.render(&mut buffer).unwrap();
+        assert_eq!(buffer, [0.0, 0.5, 0.0, 0.25, 0.0, 0.125, 0.0, 0.0]);
+        assert!(voice.is_stopped());
+        assert_eq!(audio.voice_count(), 0);
+    }
+
+    #[test]
+    fn test_voice_controls() {
+        let mut audio = null_audio(1);
+        let sound = Sound::new(2, 50, vec![1.0, 1.0, 0.0, 0.0]).unwrap();
+        let voice = audio.play_sound(&sound, PlayParams::default().volume(0.5).looping(true)).unwrap();
+        assert!(audio.play_sound(&sound, PlayParams::default().priority(-1)).is_err());
+        let mut buffer = [0.0; 8];
+        audio.render(&mut buffer).unwrap();
+        assert_eq!(buffer, [0.5, 0.5, 0.25, 0.25, 0.0, 0.0, 0.25, 0.25]);
+        voice.pause();
+        audio.render(&mut buffer).unwrap();
+        assert_eq!(buffer, [0.0; 8]);
+        assert!(voice.is_paused());
+        voice.stop();
+        assert!(voice.is_stopped());
+        assert!(audio.play_sound(&sound, PlayParams::default()).is_ok());
+    }
+
+    #[test]
+    fn test_buses() {
+        let mut audio = null_audio(8);
+        let sound = Sound::new(1, 100, vec![1.0; 4]).unwrap();
+        audio.set_bus_volume(AudioBus::Sfx, 0.5);
+        audio.set_bus_volume(AudioBus::Master, 0.5);
+        audio.play_sound(&sound, PlayParams::default()).unwrap();
+        audio.play_sound(&sound, PlayParams::default().bus(AudioBus::Ui)).unwrap();
+        let mut buffer = [0.0; 2];
+        audio.render(&mut buffer).unwrap();
+        assert_eq!(buffer, [0.75, 0.75]);
+        audio.set_bus_muted(AudioBus::Ui, true);
+        audio.render(&mut buffer).unwrap();
+        assert_eq!(buffer, [0.25, 0.25]);
+        assert!(audio.is_bus_muted(AudioBus::Ui));
+    }
+
+    #[test]
+    fn test_ducking() {
+        let mut audio = null_audio(8);
+        let music = Sound::new(1, 100, vec![1.0; 16]).unwrap();
+        let effect = Sound::new(1, 100, vec![0.0; 2]).unwrap();
+        audio.set_bus_ducking(AudioBus::Music, Some(Ducking::new(AudioBus::Sfx, 0.5)
+            .attack(Duration::from_millis(20))
+            .release(Duration::from_millis(20))));
+        audio.play_sound(&music, PlayParams::default().bus(AudioBus::Music)).unwrap();
+        audio.play_sound(&effect, PlayParams::default()).unwrap();
+        let mut buffer = [0.0; 6];
+        audio.render(&mut buffer).unwrap();
+        assert_eq!(buffer, [0.75, 0.75, 0.5, 0.5, 0.5, 0.5]);
+        audio.render(&mut buffer).unwrap();
+        assert_eq!(buffer, [0.75, 0.75, 1.0, 1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_priority_stealing() {
+        let mut audio = null_audio(2);
+        let sound = Sound::new(1, 100, vec![1.0; 4]).unwrap();
+        let first = audio.play_sound(&sound, PlayParams::default().priority(1)).unwrap();
+        let second = audio.play_sound(&sound, PlayParams::default()).unwrap();
+        let third = audio.play_sound(&sound, PlayParams::default()).unwrap();
+        assert!(!first.is_stopped());
+        assert!(second.is_stopped());
+        assert!(!third.is_stopped());
+        assert!(audio.play_sound(&sound, PlayParams::default().priority(-1)).is_err());
+        assert_eq!(audio.voice_count(), 2);
+    }
+
+    #[test]
+    fn test_fade() {
+        let mut audio = null_audio(8);
+        let sound = Sound::new(1, 100, vec![1.0; 16]).unwrap();
+        let voice = audio.play_sound(&sound, PlayParams::default().fade_in(Duration::from_millis(40))).unwrap();
+        let mut buffer = [0.0; 12];
+        audio.render(&mut buffer).unwrap();
+        assert_eq!(buffer, [0.0, 0.0, 0.25, 0.25, 0.5, 0.5, 0.75, 0.75, 1.0, 1.0, 1.0, 1.0]);
+        voice.fade_out(Duration::from_millis(20));
+        audio.render(&mut buffer).unwrap();
+        assert_eq!(buffer, [1.0, 1.0, 0.5, 0.5, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+        assert!(voice.is_stopped());
+    }
+
+    #[test]
+    fn test_spatial() {
+        let mut audio = null_audio(8);
+        audio.set_spatial_settings(SpatialSettings::new()
+            .attenuation(Attenuation::Linear)
+            .reference_distance(100.0)
+            .max_distance(300.0)
+            .pan_width(200.0));
+        audio.set_listener_position((100.0, 100.0));
+        let sound = Sound::new(1, 100, vec![1.0; 16]).unwrap();
+        let voice = audio.play_sound(&sound, PlayParams::default().position((300.0, 100.0))).unwrap();
+        let mut buffer = [0.0; 2];
+        audio.render(&mut buffer).unwrap();
+        assert_eq!(buffer, [0.0, 0.5]);
+        voice.set_position((0.0, 100.0));
+        audio.render(&mut buffer).unwrap();
+        assert_eq!(buffer, [1.0, 0.5]);
+        audio.set_listener_position((0.0, 500.0));
+        audio.render(&mut buffer).unwrap();
+        assert_eq!(buffer, [0.0, 0.0]);
+        voice.clear_position();
+        audio.render(&mut buffer).unwrap();
+        assert_eq!(buffer, [1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_doppler() {
+        let mut audio = null_audio(8);
+        audio.set_spatial_settings(SpatialSettings::new()
+            .attenuation(Attenuation::None)
+            .doppler_factor(1.0)
+            .speed_of_sound(300.0));
+        let sound = Sound::new(1, 100, vec![0.0, 1.0, 0.0, 1.0, 0.0, 1.0]).unwrap();
+        audio.play_sound(&sound, PlayParams::default().position((0.0, 100.0)).velocity((0.0, -150.0))).unwrap();
+        let mut buffer = [0.0; 4];
+        audio.render(&mut buffer).unwrap();
+        assert_eq!(buffer, [0.0, 0.0, 0.0, 0.0]);
     }
 }
@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum AudioBus {
+    Master,
+    Music,
+    Sfx,
+    Ui,
+    Custom(u32),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Ducking {
+    pub trigger: AudioBus,
+    pub volume: f32,
+    pub attack: Duration,
+    pub release: Duration,
+}
+
+impl Ducking {
+    pub fn new(trigger: AudioBus, volume: f32) -> Self {
+        Self {
+            trigger,
+            volume,
+            attack: Duration::from_millis(50),
+            release: Duration::from_millis(300),
+        }
+    }
+
+    pub fn attack(mut self, attack: Duration) -> Self {
+        self.attack = attack;
+        self
+    }
+
+    pub fn release(mut self, release: Duration) -> Self {
+        self.release = release;
+        self
+    }
+}
+
+pub(crate) fn duration_to_frames(duration: Duration, sample_rate: u32) -> u64 {
+    (duration.as_secs_f64() * sample_rate as f64).round() as u64
+}
+
+pub(crate) struct BusState {
+    pub volume: f32,
+    pub muted: bool,
+    pub ducking: Option<Ducking>,
+    duck_gain: f32,
+    pub buffer: Vec<f32>,
+}
+
+impl BusState {
+    pub fn new() -> Self {
+        Self {
+            volume: 1.0,
+            muted: false,
+            ducking: None,
+            duck_gain: 1.0,
+            buffer: Vec::new(),
+        }
+    }
+
+    pub fn prepare(&mut self, len: usize) {
+        self.buffer.clear();
+        self.buffer.resize(len, 0.0);
+    }
+
+    pub fn apply_gain(&mut self, buffer: &mut [f32], ducked: bool, sample_rate: u32) {
+        let (duck_target, duck_step) = match self.ducking {
+            Some(ducking) => {
+                let volume = ducking.volume.clamp(0.0, 1.0);
+                let (target, duration) = if ducked {
+                    (volume, ducking.attack)
+                } else {
+                    (1.0, ducking.release)
+                };
+                let frames = duration_to_frames(duration, sample_rate);
+                let step = if frames == 0 { 1.0 } else { (1.0 - volume) / frames as f32 };
+                (target, step)
+            }
+            None => (1.0, 1.0),
+        };
+        for frame in buffer.chunks_exact_mut(2) {
+            if self.duck_gain < duck_target {
+                self.duck_gain = (self.duck_gain + duck_step).min(duck_target);
+            } else if self.duck_gain > duck_target {
+                self.duck_gain = (self.duck_gain - duck_step).max(duck_target);
+            }
+            let gain = if self.muted { 0.0 } else { self.volume * self.duck_gain };
+            frame[0] *= gain;
+            frame[1] *= gain;
+        }
+    }
+}
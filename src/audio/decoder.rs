@@ -0,0 +1,155 @@
+use crate::error::{GameError, GameResult};
+use hound::{WavReader, WavIntoSamples, SampleFormat};
+use lewton::inside_ogg::OggStreamReader;
+use claxon::FlacReader;
+use std::io::Cursor;
+use std::sync::Arc;
+
+type Bytes = Cursor<Arc<[u8]>>;
+
+enum DecoderKind {
+    WavInt {
+        samples: WavIntoSamples<Bytes, i32>,
+        scale: f32,
+    },
+    WavFloat {
+        samples: WavIntoSamples<Bytes, f32>,
+    },
+    Ogg {
+        reader: Box<OggStreamReader<Bytes>>,
+    },
+    Flac {
+        reader: Box<FlacReader<Bytes>>,
+        buffer: Vec<i32>,
+        scale: f32,
+    },
+}
+
+pub(crate) struct Decoder {
+    kind: DecoderKind,
+    channels: u16,
+    sample_rate: u32,
+    pending: Vec<f32>,
+    offset: usize,
+    finished: bool,
+}
+
+impl Decoder {
+    pub(crate) fn new(bytes: Arc<[u8]>) -> GameResult<Self> {
+        let (kind, channels, sample_rate) = if bytes.starts_with(b"RIFF") {
+            let reader = WavReader::new(Cursor::new(bytes))
+                .map_err(|error| GameError::InitError(error.into()))?;
+            let spec = reader.spec();
+            let kind = match spec.sample_format {
+                SampleFormat::Int => DecoderKind::WavInt {
+                    samples: reader.into_samples(),
+                    scale: 1.0 / (1_i64 << (spec.bits_per_sample - 1)) as f32,
+                },
+                SampleFormat::Float => DecoderKind::WavFloat {
+                    samples: reader.into_samples(),
+                },
+            };
+            (kind, spec.channels, spec.sample_rate)
+        } else if bytes.starts_with(b"OggS") {
+            let reader = OggStreamReader::new(Cursor::new(bytes))
+                .map_err(|error| GameError::InitError(error.into()))?;
+            let channels = reader.ident_hdr.audio_channels as u16;
+            let sample_rate = reader.ident_hdr.audio_sample_rate;
+            (DecoderKind::Ogg { reader: Box::new(reader) }, channels, sample_rate)
+        } else if bytes.starts_with(b"fLaC") {
+            let reader = FlacReader::new(Cursor::new(bytes))
+                .map_err(|error| GameError::InitError(error.into()))?;
+            let info = reader.streaminfo();
+            let kind = DecoderKind::Flac {
+                reader: Box::new(reader),
+                buffer: Vec::new(),
+                scale: 1.0 / (1_i64 << (info.bits_per_sample - 1)) as f32,
+            };
+            (kind, info.channels as u16, info.sample_rate)
+        } else {
+            return Err(GameError::NotSupportedError("unknown audio format, only WAV, OGG and FLAC are supported".into()));
+        };
+        if channels == 0 || sample_rate == 0 {
+            return Err(GameError::InitError("illegal audio channels or sample rate".into()));
+        }
+        Ok(Self {
+            kind,
+            channels,
+            sample_rate,
+            pending: Vec::new(),
+            offset: 0,
+            finished: false,
+        })
+    }
+
+    pub(crate) fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    pub(crate) fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn decode_next_chunk(&mut self) -> bool {
+        self.pending.clear();
+        self.offset = 0;
+        match &mut self.kind {
+            DecoderKind::WavInt { samples, scale } => {
+                for sample in samples.take(4096) {
+                    match sample {
+                        Ok(sample) => self.pending.push(sample as f32 * *scale),
+                        Err(_) => break,
+                    }
+                }
+            }
+            DecoderKind::WavFloat { samples } => {
+                for sample in samples.take(4096) {
+                    match sample {
+                        Ok(sample) => self.pending.push(sample),
+                        Err(_) => break,
+                    }
+                }
+            }
+            DecoderKind::Ogg { reader } => {
+                while self.pending.is_empty() {
+                    match reader.read_dec_packet_itl() {
+                        Ok(Some(packet)) => {
+                            self.pending.extend(packet.into_iter().map(|sample| sample as f32 / 32768.0));
+                        }
+                        _ => break,
+                    }
+                }
+            }
+            DecoderKind::Flac { reader, buffer, scale } => {
+                let block_buffer = std::mem::take(buffer);
+                if let Ok(Some(block)) = reader.blocks().read_next_or_eof(block_buffer) {
+                    for index in 0..block.duration() {
+                        for channel in 0..block.channels() {
+                            self.pending.push(block.sample(channel, index) as f32 * *scale);
+                        }
+                    }
+                    *buffer = block.into_buffer();
+                }
+            }
+        }
+        !self.pending.is_empty()
+    }
+
+    pub(crate) fn next_sample(&mut self) -> Option<f32> {
+        if self.offset >= self.pending.len() && (self.finished || !self.decode_next_chunk()) {
+            self.finished = true;
+            return None;
+        }
+        let sample = self.pending[self.offset];
+        self.offset += 1;
+        Some(sample)
+    }
+
+    pub(crate) fn decode_all(mut self) -> Vec<f32> {
+        let mut samples = Vec::new();
+        while let Some(sample) = self.next_sample() {
+            samples.push(sample);
+        }
+        samples
+    }
+}
@@ -0,0 +1,149 @@
+use super::{AudioBus, BusState, SpatialSettings, Listener, Spatialized, VoiceId, VoiceSource, VoiceState, PlayParams};
+use crate::math::{Vector, Position};
+use std::sync::{Mutex, MutexGuard};
+use std::collections::{HashMap, HashSet};
+
+pub(crate) fn lock_mixer(mixer: &Mutex<Mixer>) -> MutexGuard<'_, Mixer> {
+    mixer.lock().unwrap_or_else(|error| error.into_inner())
+}
+
+pub(crate) struct Mixer {
+    sample_rate: u32,
+    max_voices: usize,
+    voices: Vec<VoiceState>,
+    next_voice_id: u64,
+    buses: HashMap<AudioBus, BusState>,
+    spatial_settings: SpatialSettings,
+    listener: Listener,
+}
+
+impl Mixer {
+    pub fn new(sample_rate: u32, max_voices: usize, spatial_settings: SpatialSettings) -> Self {
+        let mut buses = HashMap::new();
+        for bus in [AudioBus::Master, AudioBus::Music, AudioBus::Sfx, AudioBus::Ui] {
+            buses.insert(bus, BusState::new());
+        }
+        Self {
+            sample_rate,
+            max_voices,
+            voices: Vec::with_capacity(max_voices),
+            next_voice_id: 0,
+            buses,
+            spatial_settings,
+            listener: Listener {
+                position: Position::zero(),
+                velocity: Vector::zero(),
+            },
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn max_voices(&self) -> usize {
+        self.max_voices
+    }
+
+    pub fn voice_count(&self) -> usize {
+        self.voices.len()
+    }
+
+    pub fn add_voice(&mut self, source: VoiceSource, bus: AudioBus, params: PlayParams) -> Option<VoiceId> {
+        let priority = params.priority.unwrap_or(0);
+        if self.voices.len() >= self.max_voices {
+            let victim = self.voices.iter()
+                .enumerate()
+                .min_by_key(|(_, voice)| (voice.priority, voice.id.0))
+                .filter(|(_, voice)| voice.priority <= priority)
+                .map(|(index, _)| index)?;
+            self.voices.remove(victim);
+        }
+        let id = VoiceId(self.next_voice_id);
+        self.next_voice_id += 1;
+        self.buses.entry(bus).or_insert_with(BusState::new);
+        self.voices.push(VoiceState::new(id, source, bus, params, self.sample_rate));
+        Some(id)
+    }
+
+    pub fn voice_mut(&mut self, id: VoiceId) -> Option<&mut VoiceState> {
+        self.voices.iter_mut().find(|voice| voice.id == id)
+    }
+
+    pub fn remove_voice(&mut self, id: VoiceId) {
+        self.voices.retain(|voice| voice.id != id);
+    }
+
+    pub fn clear_voices(&mut self) {
+        self.voices.clear();
+    }
+
+    pub fn bus(&self, bus: AudioBus) -> Option<&BusState> {
+        self.buses.get(&bus)
+    }
+
+    pub fn bus_mut(&mut self, bus: AudioBus) -> &mut BusState {
+        self.buses.entry(bus).or_insert_with(BusState::new)
+    }
+
+    pub fn spatial_settings(&self) -> SpatialSettings {
+        self.spatial_settings
+    }
+
+    pub fn set_spatial_settings(&mut self, spatial_settings: SpatialSettings) {
+        self.spatial_settings = spatial_settings;
+    }
+
+    pub fn listener_mut(&mut self) -> &mut Listener {
+        &mut self.listener
+    }
+
+    pub fn listener(&self) -> &Listener {
+        &self.listener
+    }
+
+    pub fn render(&mut self, buffer: &mut [f32]) {
+        for sample in buffer.iter_mut() {
+            *sample = 0.0;
+        }
+        for bus in self.buses.values_mut() {
+            bus.prepare(buffer.len());
+        }
+        let active_buses = self.voices.iter()
+            .filter(|voice| !voice.paused)
+            .map(|voice| voice.bus)
+            .collect::<HashSet<_>>();
+        let sample_rate = self.sample_rate;
+        for voice in self.voices.iter_mut() {
+            if let Some(position) = voice.position {
+                voice.spatialized = Spatialized::compute(&self.spatial_settings, &self.listener, position, voice.velocity);
+            }
+            if let Some(bus) = self.buses.get_mut(&voice.bus) {
+                voice.mix(&mut bus.buffer, sample_rate);
+            }
+        }
+        self.voices.retain(|voice| !voice.is_finished());
+        for (id, bus) in self.buses.iter_mut() {
+            if *id == AudioBus::Master {
+                continue;
+            }
+            let ducked = bus.ducking.map(|ducking| active_buses.contains(&ducking.trigger)).unwrap_or(false);
+            let mut bus_buffer = std::mem::take(&mut bus.buffer);
+            bus.apply_gain(&mut bus_buffer, ducked, sample_rate);
+            for (sample, bus_sample) in buffer.iter_mut().zip(bus_buffer.iter()) {
+                *sample += *bus_sample;
+            }
+            bus.buffer = bus_buffer;
+        }
+        if let Some(master) = self.buses.get_mut(&AudioBus::Master) {
+            for (sample, master_sample) in buffer.iter_mut().zip(master.buffer.iter()) {
+                *sample += *master_sample;
+            }
+            let ducked = master.ducking.map(|ducking| active_buses.contains(&ducking.trigger)).unwrap_or(false);
+            master.apply_gain(buffer, ducked, sample_rate);
+        }
+        for sample in buffer.iter_mut() {
+            *sample = sample.clamp(-1.0, 1.0);
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use super::Decoder;
+use crate::error::GameResult;
+use crate::engine::Engine;
+use crate::loader::LoadHandle;
+use std::sync::Arc;
+use std::path::Path;
+
+#[derive(Clone)]
+pub struct Music {
+    bytes: Arc<[u8]>,
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl Music {
+    pub fn from_bytes(bytes: &[u8]) -> GameResult<Self> {
+        Self::from_shared_bytes(Arc::from(bytes))
+    }
+
+    pub fn load(engine: &mut Engine, path: impl AsRef<Path>) -> GameResult<Self> {
+        let bytes = engine.filesystem().read(path)?;
+        Self::from_shared_bytes(Arc::from(bytes))
+    }
+
+    pub fn load_async(engine: &mut Engine, path: impl AsRef<Path>) -> LoadHandle<Self> {
+        let path = path.as_ref().to_path_buf();
+        let filesystem = engine.filesystem().clone();
+        engine.loader().spawn(move || {
+            let bytes = filesystem.read(&path)?;
+            Self::from_shared_bytes(Arc::from(bytes))
+        }, |_, music| Ok(music))
+    }
+
+    fn from_shared_bytes(bytes: Arc<[u8]>) -> GameResult<Self> {
+        let decoder = Decoder::new(bytes.clone())?;
+        Ok(Self {
+            bytes,
+            channels: decoder.channels(),
+            sample_rate: decoder.sample_rate(),
+        })
+    }
+
+    pub(crate) fn decoder(&self) -> GameResult<Decoder> {
+        Decoder::new(self.bytes.clone())
+    }
+
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+}
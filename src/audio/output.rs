@@ -0,0 +1,111 @@
+use super::{SpatialSettings, Mixer, lock_mixer};
+use crate::error::{GameError, GameResult};
+use cpal::{Device, Stream, StreamConfig, SampleFormat, SampleRate, BufferSize, SizedSample, FromSample};
+use cpal::traits::{HostTrait, DeviceTrait, StreamTrait};
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Default, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum AudioBackend {
+    #[default]
+    Device,
+    Null,
+}
+
+pub(crate) struct Output {
+    backend: AudioBackend,
+    stream: Option<Stream>,
+}
+
+impl Output {
+    pub(crate) fn new(backend: AudioBackend, sample_rate: u32, max_voices: usize, spatial_settings: SpatialSettings) -> GameResult<(Self, Arc<Mutex<Mixer>>)> {
+        match backend {
+            AudioBackend::Device => {
+                let device = cpal::default_host().default_output_device()
+                    .ok_or_else(|| GameError::InitError("no audio output device available".into()))?;
+                let (stream_config, sample_format) = select_stream_config(&device, sample_rate)?;
+                let mixer = Arc::new(Mutex::new(Mixer::new(stream_config.sample_rate.0, max_voices, spatial_settings)));
+                let stream = match sample_format {
+                    SampleFormat::F32 => build_stream::<f32>(&device, &stream_config, mixer.clone())?,
+                    SampleFormat::I16 => build_stream::<i16>(&device, &stream_config, mixer.clone())?,
+                    SampleFormat::U16 => build_stream::<u16>(&device, &stream_config, mixer.clone())?,
+                    _ => return Err(GameError::NotSupportedError(format!("audio sample format `{}` is not supported", sample_format).into())),
+                };
+                stream.play()
+                    .map_err(|error| GameError::InitError(error.into()))?;
+                Ok((Self { backend, stream: Some(stream) }, mixer))
+            }
+            AudioBackend::Null => {
+                let mixer = Arc::new(Mutex::new(Mixer::new(sample_rate, max_voices, spatial_settings)));
+                Ok((Self { backend, stream: None }, mixer))
+            }
+        }
+    }
+
+    pub(crate) fn backend(&self) -> AudioBackend {
+        self.backend
+    }
+
+    pub(crate) fn pause(&self) {
+        if let Some(stream) = &self.stream {
+            let _ = stream.pause();
+        }
+    }
+
+    pub(crate) fn play(&self) {
+        if let Some(stream) = &self.stream {
+            let _ = stream.play();
+        }
+    }
+}
+
+fn select_stream_config(device: &Device, sample_rate: u32) -> GameResult<(StreamConfig, SampleFormat)> {
+    let default_config = device.default_output_config()
+        .map_err(|error| GameError::InitError(error.into()))?;
+    let channels = default_config.channels();
+    let sample_format = default_config.sample_format();
+    let mut selected_sample_rate = default_config.sample_rate();
+    if let Ok(supported_configs) = device.supported_output_configs() {
+        for supported_config in supported_configs {
+            if supported_config.channels() == channels
+                && supported_config.sample_format() == sample_format
+                && supported_config.min_sample_rate().0 <= sample_rate
+                && sample_rate <= supported_config.max_sample_rate().0 {
+                selected_sample_rate = SampleRate(sample_rate);
+                break;
+            }
+        }
+    }
+    let stream_config = StreamConfig {
+        channels,
+        sample_rate: selected_sample_rate,
+        buffer_size: BufferSize::Default,
+    };
+    Ok((stream_config, sample_format))
+}
+
+fn build_stream<T>(device: &Device, stream_config: &StreamConfig, mixer: Arc<Mutex<Mixer>>) -> GameResult<Stream>
+    where
+        T: SizedSample + FromSample<f32>,
+{
+    let channels = stream_config.channels as usize;
+    let mut buffer = Vec::new();
+    device.build_output_stream(
+        stream_config,
+        move |data: &mut [T], _| {
+            let frame_count = data.len() / channels;
+            buffer.resize(frame_count * 2, 0.0);
+            lock_mixer(&mixer).render(&mut buffer);
+            for (frame, stereo) in data.chunks_exact_mut(channels).zip(buffer.chunks_exact(2)) {
+                if channels == 1 {
+                    frame[0] = T::from_sample((stereo[0] + stereo[1]) * 0.5);
+                } else {
+                    for (channel, sample) in frame.iter_mut().enumerate() {
+                        *sample = T::from_sample(stereo.get(channel).copied().unwrap_or(0.0));
+                    }
+                }
+            }
+        },
+        |_| {},
+        None,
+    ).map_err(|error| GameError::InitError(error.into()))
+}
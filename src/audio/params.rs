@@ -0,0 +1,63 @@
+use super::AudioBus;
+use crate::math::{Vector, Position};
+use std::time::Duration;
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PlayParams {
+    pub bus: Option<AudioBus>,
+    pub priority: Option<i32>,
+    pub fade_in: Option<Duration>,
+    pub volume: Option<f32>,
+    pub pitch: Option<f32>,
+    pub pan: Option<f32>,
+    pub looping: Option<bool>,
+    pub position: Option<Position>,
+    pub velocity: Option<Vector>,
+}
+
+impl PlayParams {
+    pub fn bus(mut self, bus: AudioBus) -> Self {
+        self.bus = Some(bus);
+        self
+    }
+
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    pub fn fade_in(mut self, duration: Duration) -> Self {
+        self.fade_in = Some(duration);
+        self
+    }
+
+    pub fn volume(mut self, volume: f32) -> Self {
+        self.volume = Some(volume);
+        self
+    }
+
+    pub fn pitch(mut self, pitch: f32) -> Self {
+        self.pitch = Some(pitch);
+        self
+    }
+
+    pub fn pan(mut self, pan: f32) -> Self {
+        self.pan = Some(pan);
+        self
+    }
+
+    pub fn looping(mut self, looping: bool) -> Self {
+        self.looping = Some(looping);
+        self
+    }
+
+    pub fn position(mut self, position: impl Into<Position>) -> Self {
+        self.position = Some(position.into());
+        self
+    }
+
+    pub fn velocity(mut self, velocity: impl Into<Vector>) -> Self {
+        self.velocity = Some(velocity.into());
+        self
+    }
+}
@@ -0,0 +1,83 @@
+use super::Decoder;
+use crate::error::{GameError, GameResult};
+use crate::engine::Engine;
+use crate::loader::LoadHandle;
+use std::sync::Arc;
+use std::path::Path;
+use std::time::Duration;
+
+pub(crate) struct SoundData {
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub samples: Vec<f32>,
+}
+
+#[derive(Clone)]
+pub struct Sound {
+    data: Arc<SoundData>,
+}
+
+impl Sound {
+    pub fn new(channels: u16, sample_rate: u32, samples: Vec<f32>) -> GameResult<Self> {
+        if channels == 0 || sample_rate == 0 {
+            return Err(GameError::InitError("illegal audio channels or sample rate".into()));
+        }
+        if !samples.len().is_multiple_of(channels as usize) {
+            return Err(GameError::InitError("illegal samples length".into()));
+        }
+        Ok(Self {
+            data: Arc::new(SoundData {
+                channels,
+                sample_rate,
+                samples,
+            }),
+        })
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> GameResult<Self> {
+        let decoder = Decoder::new(Arc::from(bytes))?;
+        let channels = decoder.channels();
+        let sample_rate = decoder.sample_rate();
+        let mut samples = decoder.decode_all();
+        samples.truncate(samples.len() - samples.len() % channels as usize);
+        Self::new(channels, sample_rate, samples)
+    }
+
+    pub fn load(engine: &mut Engine, path: impl AsRef<Path>) -> GameResult<Self> {
+        let bytes = engine.filesystem().read(path)?;
+        Self::from_bytes(&bytes)
+    }
+
+    pub fn load_async(engine: &mut Engine, path: impl AsRef<Path>) -> LoadHandle<Self> {
+        let path = path.as_ref().to_path_buf();
+        let filesystem = engine.filesystem().clone();
+        engine.loader().spawn(move || {
+            let bytes = filesystem.read(&path)?;
+            Self::from_bytes(&bytes)
+        }, |_, sound| Ok(sound))
+    }
+
+    pub(crate) fn data(&self) -> &Arc<SoundData> {
+        &self.data
+    }
+
+    pub fn channels(&self) -> u16 {
+        self.data.channels
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.data.sample_rate
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.data.samples.len() / self.data.channels as usize
+    }
+
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f64(self.frame_count() as f64 / self.data.sample_rate as f64)
+    }
+
+    pub fn samples(&self) -> &[f32] {
+        &self.data.samples
+    }
+}
@@ -0,0 +1,155 @@
+use crate::math::{Vector, Position};
+
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum Attenuation {
+    None,
+    Linear,
+    Inverse,
+    Exponential,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SpatialSettings {
+    pub attenuation: Attenuation,
+    pub reference_distance: f32,
+    pub max_distance: f32,
+    pub rolloff_factor: f32,
+    pub pan_width: f32,
+    pub doppler_factor: f32,
+    pub speed_of_sound: f32,
+}
+
+impl SpatialSettings {
+    pub fn new() -> Self {
+        Self {
+            attenuation: Attenuation::Inverse,
+            reference_distance: 100.0,
+            max_distance: 2000.0,
+            rolloff_factor: 1.0,
+            pan_width: 500.0,
+            doppler_factor: 0.0,
+            speed_of_sound: 3000.0,
+        }
+    }
+
+    pub fn attenuation(mut self, attenuation: Attenuation) -> Self {
+        self.attenuation = attenuation;
+        self
+    }
+
+    pub fn reference_distance(mut self, distance: f32) -> Self {
+        self.reference_distance = distance;
+        self
+    }
+
+    pub fn max_distance(mut self, distance: f32) -> Self {
+        self.max_distance = distance;
+        self
+    }
+
+    pub fn rolloff_factor(mut self, factor: f32) -> Self {
+        self.rolloff_factor = factor;
+        self
+    }
+
+    pub fn pan_width(mut self, width: f32) -> Self {
+        self.pan_width = width;
+        self
+    }
+
+    pub fn doppler_factor(mut self, factor: f32) -> Self {
+        self.doppler_factor = factor;
+        self
+    }
+
+    pub fn speed_of_sound(mut self, speed: f32) -> Self {
+        self.speed_of_sound = speed;
+        self
+    }
+
+    fn gain(&self, distance: f32) -> f32 {
+        let reference_distance = self.reference_distance.max(f32::EPSILON);
+        let max_distance = self.max_distance.max(reference_distance);
+        let distance = distance.clamp(reference_distance, max_distance);
+        let gain = match self.attenuation {
+            Attenuation::None => 1.0,
+            Attenuation::Linear => {
+                if max_distance > reference_distance {
+                    1.0 - self.rolloff_factor * (distance - reference_distance) / (max_distance - reference_distance)
+                } else {
+                    1.0
+                }
+            }
+            Attenuation::Inverse => {
+                reference_distance / (reference_distance + self.rolloff_factor * (distance - reference_distance))
+            }
+            Attenuation::Exponential => (distance / reference_distance).powf(-self.rolloff_factor),
+        };
+        gain.clamp(0.0, 1.0)
+    }
+
+    fn doppler_pitch(&self, direction: Vector, listener_velocity: Vector, emitter_velocity: Vector) -> f32 {
+        if self.doppler_factor <= 0.0 || self.speed_of_sound <= 0.0 {
+            return 1.0;
+        }
+        let max_speed = self.speed_of_sound / self.doppler_factor;
+        let listener_speed = (direction.x * listener_velocity.x + direction.y * listener_velocity.y).min(max_speed);
+        let emitter_speed = (direction.x * emitter_velocity.x + direction.y * emitter_velocity.y).min(max_speed);
+        let numerator = self.speed_of_sound - self.doppler_factor * listener_speed;
+        let denominator = self.speed_of_sound - self.doppler_factor * emitter_speed;
+        if denominator <= 0.0 {
+            1.0
+        } else {
+            (numerator / denominator).max(0.0)
+        }
+    }
+}
+
+impl Default for SpatialSettings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct Listener {
+    pub position: Position,
+    pub velocity: Vector,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct Spatialized {
+    pub gain: f32,
+    pub pan: f32,
+    pub pitch: f32,
+}
+
+impl Spatialized {
+    pub fn none() -> Self {
+        Self {
+            gain: 1.0,
+            pan: 0.0,
+            pitch: 1.0,
+        }
+    }
+
+    pub fn compute(settings: &SpatialSettings, listener: &Listener, position: Position, velocity: Vector) -> Self {
+        let offset = position - listener.position;
+        let distance = (offset.x * offset.x + offset.y * offset.y).sqrt();
+        let direction = if distance > 0.0 {
+            Vector::new(-offset.x / distance, -offset.y / distance)
+        } else {
+            Vector::zero()
+        };
+        let pan = if settings.pan_width > 0.0 {
+            (offset.x / settings.pan_width).clamp(-1.0, 1.0)
+        } else {
+            0.0
+        };
+        Self {
+            gain: settings.gain(distance),
+            pan,
+            pitch: settings.doppler_pitch(direction, listener.velocity, velocity),
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use super::{Decoder, Music};
+use crate::error::{GameError, GameResult};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+use std::thread::{self, Thread};
+use std::time::Duration;
+
+const BUFFER_SECONDS: f32 = 0.5;
+const IDLE_TIMEOUT: Duration = Duration::from_millis(10);
+
+struct Shared {
+    samples: Box<[AtomicU32]>,
+    read: AtomicUsize,
+    write: AtomicUsize,
+    looping: AtomicBool,
+    rewind: AtomicBool,
+    finished: AtomicBool,
+    stopped: AtomicBool,
+}
+
+impl Shared {
+    fn capacity(&self) -> usize {
+        self.samples.len()
+    }
+
+    fn fill(&self, music: &Music, decoder: &mut Option<Decoder>, channels: usize) -> bool {
+        let mut written = false;
+        let mut write = self.write.load(Ordering::Relaxed);
+        let mut frame = vec![0.0; channels];
+        while !self.finished.load(Ordering::Relaxed) && self.capacity() - (write - self.read.load(Ordering::Acquire)) >= channels {
+            let mut restarted = false;
+            let complete = loop {
+                let complete = match decoder.as_mut() {
+                    Some(decoder) => frame.iter_mut().all(|sample| match decoder.next_sample() {
+                        Some(value) => {
+                            *sample = value;
+                            true
+                        }
+                        None => false,
+                    }),
+                    None => false,
+                };
+                if complete || restarted || !self.looping.load(Ordering::Relaxed) {
+                    break complete;
+                }
+                *decoder = music.decoder().ok();
+                restarted = true;
+            };
+            if !complete {
+                self.finished.store(true, Ordering::Release);
+                break;
+            }
+            for (offset, sample) in frame.iter().enumerate() {
+                self.samples[(write + offset) % self.capacity()].store(sample.to_bits(), Ordering::Relaxed);
+            }
+            write += channels;
+            self.write.store(write, Ordering::Release);
+            written = true;
+        }
+        written
+    }
+}
+
+pub(crate) struct MusicStream {
+    shared: Arc<Shared>,
+    feeder: Thread,
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl MusicStream {
+    pub(crate) fn new(music: &Music) -> GameResult<Self> {
+        let channels = music.channels().max(1) as usize;
+        let frames = ((music.sample_rate() as f32 * BUFFER_SECONDS) as usize).max(1);
+        let shared = Arc::new(Shared {
+            samples: (0..frames * channels).map(|_| AtomicU32::new(0)).collect(),
+            read: AtomicUsize::new(0),
+            write: AtomicUsize::new(0),
+            looping: AtomicBool::new(false),
+            rewind: AtomicBool::new(false),
+            finished: AtomicBool::new(false),
+            stopped: AtomicBool::new(false),
+        });
+        let mut decoder = Some(music.decoder()?);
+        shared.fill(music, &mut decoder, channels);
+        let feeder = {
+            let shared = shared.clone();
+            let music = music.clone();
+            thread::Builder::new()
+                .name("tge-music".to_owned())
+                .spawn(move || {
+                    while !shared.stopped.load(Ordering::Acquire) {
+                        if shared.rewind.swap(false, Ordering::AcqRel) {
+                            decoder = music.decoder().ok();
+                        }
+                        if !shared.fill(&music, &mut decoder, channels) {
+                            thread::park_timeout(IDLE_TIMEOUT);
+                        }
+                    }
+                })
+                .map_err(|error| GameError::InitError(error.into()))?
+                .thread()
+                .clone()
+        };
+        Ok(Self {
+            shared,
+            feeder,
+            channels: music.channels(),
+            sample_rate: music.sample_rate(),
+        })
+    }
+
+    pub(crate) fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    pub(crate) fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub(crate) fn set_looping(&self, looping: bool) {
+        self.shared.looping.store(looping, Ordering::Relaxed);
+    }
+
+    pub(crate) fn rewind(&self) {
+        self.shared.rewind.store(true, Ordering::Release);
+        self.shared.finished.store(false, Ordering::Release);
+        self.feeder.unpark();
+    }
+
+    fn pop_frame(&self) -> Option<[f32; 2]> {
+        let channels = self.channels.max(1) as usize;
+        let read = self.shared.read.load(Ordering::Relaxed);
+        if self.shared.write.load(Ordering::Acquire) - read < channels {
+            return None;
+        }
+        let sample = |offset: usize| f32::from_bits(self.shared.samples[(read + offset) % self.shared.capacity()].load(Ordering::Relaxed));
+        let left = sample(0);
+        let right = if channels > 1 { sample(1) } else { left };
+        self.shared.read.store(read + channels, Ordering::Release);
+        self.feeder.unpark();
+        Some([left, right])
+    }
+
+    pub(crate) fn next_frame(&mut self) -> Option<[f32; 2]> {
+        let finished = self.shared.finished.load(Ordering::Acquire) && !self.shared.rewind.load(Ordering::Acquire);
+        match self.pop_frame() {
+            Some(frame) => Some(frame),
+            None if finished => None,
+            None => Some([0.0, 0.0]),
+        }
+    }
+}
+
+impl Drop for MusicStream {
+    fn drop(&mut self) {
+        self.shared.stopped.store(true, Ordering::Release);
+        self.feeder.unpark();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MusicStream;
+    use crate::audio::Music;
+    use crate::audio::tests::wav_bytes;
+    use std::time::{Duration, Instant};
+
+    fn next_frames(stream: &MusicStream, count: usize) -> Vec<f32> {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut frames = Vec::new();
+        while frames.len() < count {
+            match stream.pop_frame() {
+                Some(frame) => frames.push(frame[0]),
+                None => {
+                    assert!(Instant::now() < deadline, "music stream stalled");
+                    std::thread::yield_now();
+                }
+            }
+        }
+        frames
+    }
+
+    #[test]
+    fn test_music_stream() {
+        let samples: Vec<i16> = (0..24).map(|index| index * 1000).collect();
+        let music = Music::from_bytes(&wav_bytes(8, &samples)).unwrap();
+        let expected = music.decoder().unwrap().decode_all();
+
+        let mut stream = MusicStream::new(&music).unwrap();
+        assert_eq!(next_frames(&stream, 24), expected);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while stream.next_frame().is_some() {
+            assert!(Instant::now() < deadline, "music stream never finished");
+            std::thread::yield_now();
+        }
+        stream.rewind();
+        assert_eq!(next_frames(&stream, 24), expected);
+
+        let stream = MusicStream::new(&music).unwrap();
+        stream.set_looping(true);
+        let looped = next_frames(&stream, 96);
+        for chunk in looped.chunks(24) {
+            assert_eq!(chunk, &expected[..]);
+        }
+    }
+}
@@ -0,0 +1,340 @@
+use super::{AudioBus, MusicStream, SoundData, PlayParams, Spatialized, Mixer, lock_mixer, duration_to_frames};
+use crate::math::{Vector, Position};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const DEFAULT_VOLUME: f32 = 1.0;
+const DEFAULT_PITCH: f32 = 1.0;
+const DEFAULT_PAN: f32 = 0.0;
+
+pub(crate) enum VoiceSource {
+    Sound {
+        data: Arc<SoundData>,
+        frame: usize,
+    },
+    Music(MusicStream),
+}
+
+impl VoiceSource {
+    fn channels(&self) -> u16 {
+        match self {
+            Self::Sound { data, .. } => data.channels,
+            Self::Music(stream) => stream.channels(),
+        }
+    }
+
+    fn sample_rate(&self) -> u32 {
+        match self {
+            Self::Sound { data, .. } => data.sample_rate,
+            Self::Music(stream) => stream.sample_rate(),
+        }
+    }
+
+    fn next_frame(&mut self) -> Option<[f32; 2]> {
+        let channels = self.channels() as usize;
+        match self {
+            Self::Sound { data, frame } => {
+                let offset = *frame * channels;
+                if offset + channels > data.samples.len() {
+                    return None;
+                }
+                *frame += 1;
+                let left = data.samples[offset];
+                let right = if channels > 1 { data.samples[offset + 1] } else { left };
+                Some([left, right])
+            }
+            Self::Music(stream) => stream.next_frame(),
+        }
+    }
+
+    fn set_looping(&self, looping: bool) {
+        if let Self::Music(stream) = self {
+            stream.set_looping(looping);
+        }
+    }
+
+    fn rewind(&mut self) {
+        match self {
+            Self::Sound { frame, .. } => *frame = 0,
+            Self::Music(stream) => stream.rewind(),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Fade {
+    target: f32,
+    step: f32,
+    stop: bool,
+}
+
+pub(crate) struct VoiceState {
+    pub id: VoiceId,
+    source: VoiceSource,
+    pub bus: AudioBus,
+    pub priority: i32,
+    volume: f32,
+    fade: Option<Fade>,
+    pub pitch: f32,
+    pub pan: f32,
+    pub looping: bool,
+    pub paused: bool,
+    pub position: Option<Position>,
+    pub velocity: Vector,
+    pub spatialized: Spatialized,
+    current: Option<[f32; 2]>,
+    next: Option<[f32; 2]>,
+    fraction: f64,
+}
+
+impl VoiceState {
+    pub fn new(id: VoiceId, source: VoiceSource, bus: AudioBus, params: PlayParams, sample_rate: u32) -> Self {
+        let volume = params.volume.unwrap_or(DEFAULT_VOLUME).max(0.0);
+        let mut state = Self {
+            id,
+            source,
+            bus,
+            priority: params.priority.unwrap_or(0),
+            volume,
+            fade: None,
+            pitch: params.pitch.unwrap_or(DEFAULT_PITCH).max(0.0),
+            pan: params.pan.unwrap_or(DEFAULT_PAN).clamp(-1.0, 1.0),
+            looping: params.looping.unwrap_or(false),
+            paused: false,
+            position: params.position,
+            velocity: params.velocity.unwrap_or_else(Vector::zero),
+            spatialized: Spatialized::none(),
+            current: None,
+            next: None,
+            fraction: 0.0,
+        };
+        if let Some(fade_in) = params.fade_in {
+            state.volume = 0.0;
+            state.fade_to(volume, fade_in, false, sample_rate);
+        }
+        state.current = state.read_frame();
+        state.next = state.read_frame();
+        state
+    }
+
+    pub fn volume(&self) -> f32 {
+        self.fade.map(|fade| fade.target).unwrap_or(self.volume)
+    }
+
+    pub fn set_volume(&mut self, volume: f32) {
+        self.volume = volume.max(0.0);
+        self.fade = None;
+    }
+
+    pub fn fade_to(&mut self, volume: f32, duration: Duration, stop: bool, sample_rate: u32) {
+        let target = volume.max(0.0);
+        let frames = duration_to_frames(duration, sample_rate);
+        if frames == 0 {
+            self.volume = target;
+            self.fade = None;
+            if stop {
+                self.current = None;
+                self.next = None;
+            }
+        } else {
+            let step = (target - self.volume).abs() / frames as f32;
+            self.fade = Some(Fade { target, step, stop });
+        }
+    }
+
+    fn read_frame(&mut self) -> Option<[f32; 2]> {
+        self.source.set_looping(self.looping);
+        match self.source.next_frame() {
+            Some(frame) => Some(frame),
+            None if self.looping => {
+                self.source.rewind();
+                self.source.next_frame()
+            }
+            None => None,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.current.is_none()
+    }
+
+    pub fn mix(&mut self, buffer: &mut [f32], output_sample_rate: u32) {
+        if self.paused {
+            return;
+        }
+        let pitch = self.pitch * self.spatialized.pitch;
+        let step = pitch as f64 * self.source.sample_rate() as f64 / output_sample_rate as f64;
+        let pan = (self.pan + self.spatialized.pan).clamp(-1.0, 1.0);
+        let left_pan = (1.0 - pan).min(1.0) * self.spatialized.gain;
+        let right_pan = (1.0 + pan).min(1.0) * self.spatialized.gain;
+        for frame in buffer.chunks_exact_mut(2) {
+            let current = match self.current {
+                Some(current) => current,
+                None => break,
+            };
+            let next = self.next.unwrap_or(current);
+            let t = self.fraction as f32;
+            frame[0] += (current[0] + (next[0] - current[0]) * t) * self.volume * left_pan;
+            frame[1] += (current[1] + (next[1] - current[1]) * t) * self.volume * right_pan;
+            if let Some(fade) = self.fade {
+                if self.volume < fade.target {
+                    self.volume = (self.volume + fade.step).min(fade.target);
+                } else {
+                    self.volume = (self.volume - fade.step).max(fade.target);
+                }
+                if self.volume == fade.target {
+                    self.fade = None;
+                    if fade.stop {
+                        self.current = None;
+                        self.next = None;
+                        break;
+                    }
+                }
+            }
+            self.fraction += step;
+            while self.fraction >= 1.0 && self.current.is_some() {
+                self.fraction -= 1.0;
+                self.current = self.next;
+                self.next = if self.current.is_some() { self.read_frame() } else { None };
+            }
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub struct VoiceId(pub(crate) u64);
+
+#[derive(Clone)]
+pub struct Voice {
+    mixer: Arc<Mutex<Mixer>>,
+    id: VoiceId,
+}
+
+impl Voice {
+    pub(crate) fn new(mixer: Arc<Mutex<Mixer>>, id: VoiceId) -> Self {
+        Self { mixer, id }
+    }
+
+    fn with_state<R>(&self, default: R, f: impl FnOnce(&mut VoiceState) -> R) -> R {
+        lock_mixer(&self.mixer).voice_mut(self.id).map(f).unwrap_or(default)
+    }
+
+    pub fn id(&self) -> VoiceId {
+        self.id
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.with_state(false, |state| !state.paused)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.with_state(false, |state| state.paused)
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.with_state(true, |_| false)
+    }
+
+    pub fn pause(&self) {
+        self.with_state((), |state| state.paused = true)
+    }
+
+    pub fn resume(&self) {
+        self.with_state((), |state| state.paused = false)
+    }
+
+    pub fn stop(&self) {
+        lock_mixer(&self.mixer).remove_voice(self.id);
+    }
+
+    pub fn bus(&self) -> Option<AudioBus> {
+        self.with_state(None, |state| Some(state.bus))
+    }
+
+    pub fn priority(&self) -> i32 {
+        self.with_state(0, |state| state.priority)
+    }
+
+    pub fn set_priority(&self, priority: i32) {
+        self.with_state((), |state| state.priority = priority)
+    }
+
+    pub fn volume(&self) -> f32 {
+        self.with_state(DEFAULT_VOLUME, |state| state.volume())
+    }
+
+    pub fn set_volume(&self, volume: f32) {
+        self.with_state((), |state| state.set_volume(volume))
+    }
+
+    pub fn fade_to(&self, volume: f32, duration: Duration) {
+        let mut mixer = lock_mixer(&self.mixer);
+        let sample_rate = mixer.sample_rate();
+        if let Some(state) = mixer.voice_mut(self.id) {
+            state.fade_to(volume, duration, false, sample_rate);
+        }
+    }
+
+    pub fn fade_out(&self, duration: Duration) {
+        let mut mixer = lock_mixer(&self.mixer);
+        let sample_rate = mixer.sample_rate();
+        if let Some(state) = mixer.voice_mut(self.id) {
+            state.fade_to(0.0, duration, true, sample_rate);
+        }
+    }
+
+    pub fn pitch(&self) -> f32 {
+        self.with_state(DEFAULT_PITCH, |state| state.pitch)
+    }
+
+    pub fn set_pitch(&self, pitch: f32) {
+        self.with_state((), |state| state.pitch = pitch.max(0.0))
+    }
+
+    pub fn pan(&self) -> f32 {
+        self.with_state(DEFAULT_PAN, |state| state.pan)
+    }
+
+    pub fn set_pan(&self, pan: f32) {
+        self.with_state((), |state| state.pan = pan.clamp(-1.0, 1.0))
+    }
+
+    pub fn position(&self) -> Option<Position> {
+        self.with_state(None, |state| state.position)
+    }
+
+    pub fn set_position(&self, position: impl Into<Position>) {
+        let position = position.into();
+        self.with_state((), |state| state.position = Some(position))
+    }
+
+    pub fn clear_position(&self) {
+        self.with_state((), |state| {
+            state.position = None;
+            state.spatialized = Spatialized::none();
+        })
+    }
+
+    pub fn velocity(&self) -> Vector {
+        self.with_state(Vector::zero(), |state| state.velocity)
+    }
+
+    pub fn set_velocity(&self, velocity: impl Into<Vector>) {
+        let velocity = velocity.into();
+        self.with_state((), |state| state.velocity = velocity)
+    }
+
+    pub fn is_looping(&self) -> bool {
+        self.with_state(false, |state| state.looping)
+    }
+
+    pub fn set_looping(&self, looping: bool) {
+        self.with_state((), |state| state.looping = looping)
+    }
+}
+
+impl PartialEq for Voice {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
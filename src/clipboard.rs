@@ -0,0 +1,123 @@
+#[cfg(any(target_os = "linux", target_os = "dragonfly", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd"))]
+mod x11;
+#[cfg(any(target_os = "linux", target_os = "dragonfly", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd"))]
+mod wayland;
+
+use crate::error::GameResult;
+
+#[derive(Debug, Default, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum ClipboardBackend {
+    #[default]
+    System,
+    Memory,
+}
+
+enum Inner {
+    #[cfg(any(target_os = "linux", target_os = "dragonfly", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd"))]
+    X11(x11::X11Clipboard),
+    #[cfg(any(target_os = "linux", target_os = "dragonfly", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd"))]
+    Wayland(wayland::WaylandClipboard),
+    Memory(Option<String>),
+}
+
+pub struct Clipboard {
+    inner: Inner,
+}
+
+impl Clipboard {
+    pub(crate) fn new(clipboard_config: ClipboardConfig, window: Option<&winit::window::Window>) -> GameResult<Self> {
+        let inner = match (clipboard_config.backend, window) {
+            (ClipboardBackend::System, Some(window)) => system_clipboard(window).unwrap_or(Inner::Memory(None)),
+            _ => Inner::Memory(None),
+        };
+        Ok(Self { inner })
+    }
+
+    pub fn backend(&self) -> ClipboardBackend {
+        match self.inner {
+            Inner::Memory(_) => ClipboardBackend::Memory,
+            #[allow(unreachable_patterns)]
+            _ => ClipboardBackend::System,
+        }
+    }
+
+    pub fn get_text(&mut self) -> GameResult<Option<String>> {
+        match &mut self.inner {
+            #[cfg(any(target_os = "linux", target_os = "dragonfly", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd"))]
+            Inner::X11(clipboard) => clipboard.get_text(),
+            #[cfg(any(target_os = "linux", target_os = "dragonfly", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd"))]
+            Inner::Wayland(clipboard) => clipboard.get_text(),
+            Inner::Memory(text) => Ok(text.clone()),
+        }
+    }
+
+    pub fn set_text(&mut self, text: impl Into<String>) -> GameResult {
+        let text = text.into();
+        match &mut self.inner {
+            #[cfg(any(target_os = "linux", target_os = "dragonfly", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd"))]
+            Inner::X11(clipboard) => clipboard.set_text(text),
+            #[cfg(any(target_os = "linux", target_os = "dragonfly", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd"))]
+            Inner::Wayland(clipboard) => clipboard.set_text(text),
+            Inner::Memory(memory) => {
+                *memory = Some(text);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn has_text(&mut self) -> bool {
+        matches!(self.get_text(), Ok(Some(text)) if !text.is_empty())
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "dragonfly", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd"))]
+fn system_clipboard(window: &winit::window::Window) -> Option<Inner> {
+    use winit::platform::unix::WindowExtUnix;
+    if let Some(display) = window.wayland_display() {
+        return Some(Inner::Wayland(unsafe { wayland::WaylandClipboard::new(display) }));
+    }
+    x11::X11Clipboard::new().ok().map(Inner::X11)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "dragonfly", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd")))]
+fn system_clipboard(_window: &winit::window::Window) -> Option<Inner> {
+    None
+}
+
+#[derive(Debug, Clone)]
+pub struct ClipboardConfig {
+    backend: ClipboardBackend,
+}
+
+impl ClipboardConfig {
+    pub fn new() -> Self {
+        Self {
+            backend: ClipboardBackend::default(),
+        }
+    }
+
+    pub fn backend(mut self, backend: ClipboardBackend) -> Self {
+        self.backend = backend;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Clipboard, ClipboardConfig, ClipboardBackend};
+
+    #[test]
+    fn test_memory() {
+        let mut clipboard = Clipboard::new(ClipboardConfig::new(), None).unwrap();
+        assert_eq!(clipboard.backend(), ClipboardBackend::Memory);
+        assert_eq!(clipboard.get_text().unwrap(), None);
+        assert!(!clipboard.has_text());
+        clipboard.set_text("copy 📋").unwrap();
+        assert_eq!(clipboard.get_text().unwrap().as_deref(), Some("copy 📋"));
+        assert!(clipboard.has_text());
+        clipboard.set_text("").unwrap();
+        assert!(!clipboard.has_text());
+        let clipboard = Clipboard::new(ClipboardConfig::new().backend(ClipboardBackend::Memory), None).unwrap();
+        assert_eq!(clipboard.backend(), ClipboardBackend::Memory);
+    }
+}
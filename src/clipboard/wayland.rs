@@ -0,0 +1,23 @@
+use crate::error::GameResult;
+use std::os::raw::c_void;
+
+pub(crate) struct WaylandClipboard {
+    clipboard: smithay_clipboard::Clipboard,
+}
+
+impl WaylandClipboard {
+    pub unsafe fn new(display: *mut c_void) -> Self {
+        Self {
+            clipboard: smithay_clipboard::Clipboard::new(display),
+        }
+    }
+
+    pub fn get_text(&self) -> GameResult<Option<String>> {
+        Ok(self.clipboard.load().ok())
+    }
+
+    pub fn set_text(&self, text: String) -> GameResult {
+        self.clipboard.store(text);
+        Ok(())
+    }
+}
@@ -0,0 +1,417 @@
+use crate::error::{GameError, GameResult};
+use x11_dl::xlib;
+use std::ffi::CString;
+use std::os::raw::{c_int, c_long, c_uchar, c_ulong};
+use std::sync::mpsc::{self, Sender, Receiver, RecvTimeoutError};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+const GET_TIMEOUT: Duration = Duration::from_millis(1000);
+const MAX_CHUNK_SIZE: usize = 256 * 1024;
+
+enum Request {
+    Get(Sender<Option<String>>),
+    Set(String),
+    Quit,
+}
+
+pub(crate) struct X11Clipboard {
+    sender: Sender<Request>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl X11Clipboard {
+    pub fn new() -> GameResult<Self> {
+        let (sender, receiver) = mpsc::channel();
+        let (init_sender, init_receiver) = mpsc::channel();
+        let worker = std::thread::Builder::new()
+            .name("tge-clipboard".to_owned())
+            .spawn(move || {
+                match Connection::open() {
+                    Ok(mut connection) => {
+                        let _ = init_sender.send(Ok(()));
+                        connection.run(receiver);
+                    }
+                    Err(error) => {
+                        let _ = init_sender.send(Err(error));
+                    }
+                }
+            })
+            .map_err(|error| GameError::InitError(error.into()))?;
+        match init_receiver.recv() {
+            Ok(Ok(())) => Ok(Self { sender, worker: Some(worker) }),
+            Ok(Err(error)) => {
+                let _ = worker.join();
+                Err(GameError::InitError(error.into()))
+            }
+            Err(error) => Err(GameError::InitError(error.into())),
+        }
+    }
+
+    pub fn get_text(&self) -> GameResult<Option<String>> {
+        let (sender, receiver) = mpsc::channel();
+        self.sender.send(Request::Get(sender))
+            .map_err(|error| GameError::RuntimeError(error.to_string().into()))?;
+        receiver.recv()
+            .map_err(|error| GameError::RuntimeError(error.into()))
+    }
+
+    pub fn set_text(&self, text: String) -> GameResult {
+        self.sender.send(Request::Set(text))
+            .map_err(|error| GameError::RuntimeError(error.to_string().into()))
+    }
+}
+
+impl Drop for X11Clipboard {
+    fn drop(&mut self) {
+        let _ = self.sender.send(Request::Quit);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+struct Atoms {
+    clipboard: xlib::Atom,
+    targets: xlib::Atom,
+    utf8_string: xlib::Atom,
+    text: xlib::Atom,
+    incr: xlib::Atom,
+    property: xlib::Atom,
+}
+
+struct IncrTransfer {
+    requestor: xlib::Window,
+    property: xlib::Atom,
+    property_type: xlib::Atom,
+    data: Vec<u8>,
+    offset: usize,
+}
+
+struct Connection {
+    xlib: xlib::Xlib,
+    display: *mut xlib::Display,
+    window: xlib::Window,
+    atoms: Atoms,
+    chunk_size: usize,
+    owned_text: Option<String>,
+    transfers: Vec<IncrTransfer>,
+}
+
+impl Connection {
+    fn open() -> Result<Self, String> {
+        let xlib = xlib::Xlib::open().map_err(|error| error.to_string())?;
+        unsafe {
+            let display = (xlib.XOpenDisplay)(std::ptr::null());
+            if display.is_null() {
+                return Err("can not open X11 display".to_owned());
+            }
+            let root = (xlib.XDefaultRootWindow)(display);
+            let window = (xlib.XCreateSimpleWindow)(display, root, 0, 0, 1, 1, 0, 0, 0);
+            (xlib.XSelectInput)(display, window, xlib::PropertyChangeMask);
+            let max_request_size = match (xlib.XExtendedMaxRequestSize)(display) {
+                0 => (xlib.XMaxRequestSize)(display),
+                size => size,
+            };
+            let chunk_size = (max_request_size.max(0) as usize * 4).saturating_sub(1024).clamp(4096, MAX_CHUNK_SIZE);
+            let intern = |name: &str| {
+                let name = CString::new(name).expect("atom name contains nul");
+                (xlib.XInternAtom)(display, name.as_ptr(), xlib::False)
+            };
+            let atoms = Atoms {
+                clipboard: intern("CLIPBOARD"),
+                targets: intern("TARGETS"),
+                utf8_string: intern("UTF8_STRING"),
+                text: intern("TEXT"),
+                incr: intern("INCR"),
+                property: intern("TGE_CLIPBOARD"),
+            };
+            Ok(Self {
+                xlib,
+                display,
+                window,
+                atoms,
+                chunk_size,
+                owned_text: None,
+                transfers: Vec::new(),
+            })
+        }
+    }
+
+    fn run(&mut self, receiver: Receiver<Request>) {
+        loop {
+            while let Some(event) = self.next_event() {
+                self.handle_event(event);
+            }
+            let request = if self.owned_text.is_none() && self.transfers.is_empty() {
+                receiver.recv().map_err(|_| RecvTimeoutError::Disconnected)
+            } else {
+                receiver.recv_timeout(POLL_INTERVAL)
+            };
+            match request {
+                Ok(Request::Get(sender)) => {
+                    let _ = sender.send(self.get_text());
+                }
+                Ok(Request::Set(text)) => self.set_text(text),
+                Ok(Request::Quit) | Err(RecvTimeoutError::Disconnected) => break,
+                Err(RecvTimeoutError::Timeout) => (),
+            }
+        }
+    }
+
+    fn next_event(&mut self) -> Option<xlib::XEvent> {
+        unsafe {
+            if (self.xlib.XPending)(self.display) > 0 {
+                let mut event = std::mem::zeroed();
+                (self.xlib.XNextEvent)(self.display, &mut event);
+                Some(event)
+            } else {
+                None
+            }
+        }
+    }
+
+    fn handle_event(&mut self, event: xlib::XEvent) {
+        match event.get_type() {
+            xlib::SelectionRequest => {
+                let request = unsafe { event.selection_request };
+                self.handle_selection_request(request);
+            }
+            xlib::SelectionClear => {
+                self.owned_text = None;
+            }
+            xlib::PropertyNotify => {
+                let notify = unsafe { event.property };
+                if notify.state == xlib::PropertyDelete {
+                    self.continue_transfer(notify.window, notify.atom);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn handle_selection_request(&mut self, request: xlib::XSelectionRequestEvent) {
+        let property = if request.property == 0 { request.target } else { request.property };
+        let mut notify = xlib::XSelectionEvent {
+            type_: xlib::SelectionNotify,
+            serial: 0,
+            send_event: xlib::True,
+            display: self.display,
+            requestor: request.requestor,
+            selection: request.selection,
+            target: request.target,
+            property: 0,
+            time: request.time,
+        };
+        if let Some(text) = &self.owned_text {
+            unsafe {
+                if request.target == self.atoms.targets {
+                    let targets = [self.atoms.targets, self.atoms.utf8_string, self.atoms.text, xlib::XA_STRING];
+                    (self.xlib.XChangeProperty)(
+                        self.display, request.requestor, property, xlib::XA_ATOM, 32, xlib::PropModeReplace,
+                        targets.as_ptr() as *const c_uchar, targets.len() as c_int,
+                    );
+                    notify.property = property;
+                } else if request.target == self.atoms.utf8_string || request.target == self.atoms.text || request.target == xlib::XA_STRING {
+                    let (property_type, data) = if request.target == xlib::XA_STRING {
+                        (xlib::XA_STRING, encode_latin1(text))
+                    } else {
+                        (self.atoms.utf8_string, text.as_bytes().to_vec())
+                    };
+                    if data.len() > self.chunk_size {
+                        (self.xlib.XSelectInput)(self.display, request.requestor, xlib::PropertyChangeMask);
+                        let size = data.len() as c_long;
+                        (self.xlib.XChangeProperty)(
+                            self.display, request.requestor, property, self.atoms.incr, 32, xlib::PropModeReplace,
+                            &size as *const c_long as *const c_uchar, 1,
+                        );
+                        self.transfers.retain(|transfer| transfer.requestor != request.requestor || transfer.property != property);
+                        self.transfers.push(IncrTransfer {
+                            requestor: request.requestor,
+                            property,
+                            property_type,
+                            data,
+                            offset: 0,
+                        });
+                    } else {
+                        (self.xlib.XChangeProperty)(
+                            self.display, request.requestor, property, property_type, 8, xlib::PropModeReplace,
+                            data.as_ptr(), data.len() as c_int,
+                        );
+                    }
+                    notify.property = property;
+                }
+            }
+        }
+        unsafe {
+            let mut event = xlib::XEvent { selection: notify };
+            (self.xlib.XSendEvent)(self.display, request.requestor, xlib::False, 0, &mut event);
+            (self.xlib.XFlush)(self.display);
+        }
+    }
+
+    fn continue_transfer(&mut self, requestor: xlib::Window, property: xlib::Atom) {
+        let index = match self.transfers.iter().position(|transfer| transfer.requestor == requestor && transfer.property == property) {
+            Some(index) => index,
+            None => return,
+        };
+        let transfer = &mut self.transfers[index];
+        let end = (transfer.offset + self.chunk_size).min(transfer.data.len());
+        let chunk = &transfer.data[transfer.offset..end];
+        unsafe {
+            (self.xlib.XChangeProperty)(
+                self.display, requestor, property, transfer.property_type, 8, xlib::PropModeReplace,
+                chunk.as_ptr(), chunk.len() as c_int,
+            );
+        }
+        if chunk.is_empty() {
+            self.transfers.remove(index);
+            if !self.transfers.iter().any(|transfer| transfer.requestor == requestor) {
+                unsafe { (self.xlib.XSelectInput)(self.display, requestor, xlib::NoEventMask) };
+            }
+        } else {
+            transfer.offset = end;
+        }
+        unsafe { (self.xlib.XFlush)(self.display) };
+    }
+
+    fn set_text(&mut self, text: String) {
+        self.owned_text = Some(text);
+        unsafe {
+            (self.xlib.XSetSelectionOwner)(self.display, self.atoms.clipboard, self.window, xlib::CurrentTime);
+            (self.xlib.XFlush)(self.display);
+        }
+    }
+
+    fn get_text(&mut self) -> Option<String> {
+        let owner = unsafe { (self.xlib.XGetSelectionOwner)(self.display, self.atoms.clipboard) };
+        if owner == self.window {
+            return self.owned_text.clone();
+        }
+        if owner == 0 {
+            return None;
+        }
+        match self.convert_selection(self.atoms.utf8_string) {
+            Some(text) => text,
+            None => self.convert_selection(xlib::XA_STRING).flatten(),
+        }
+    }
+
+    fn convert_selection(&mut self, target: xlib::Atom) -> Option<Option<String>> {
+        unsafe {
+            (self.xlib.XConvertSelection)(self.display, self.atoms.clipboard, target, self.atoms.property, self.window, xlib::CurrentTime);
+            (self.xlib.XFlush)(self.display);
+        }
+        let deadline = Instant::now() + GET_TIMEOUT;
+        while Instant::now() < deadline {
+            match self.next_event() {
+                Some(event) if event.get_type() == xlib::SelectionNotify => {
+                    let notify = unsafe { event.selection };
+                    if notify.property == 0 {
+                        return None;
+                    }
+                    return Some(self.read_selection());
+                }
+                Some(event) => self.handle_event(event),
+                None => std::thread::sleep(POLL_INTERVAL),
+            }
+        }
+        Some(None)
+    }
+
+    fn read_selection(&mut self) -> Option<String> {
+        let (property_type, data) = self.read_property()?;
+        let (property_type, data) = if property_type == self.atoms.incr {
+            self.read_incr()?
+        } else {
+            (property_type, data)
+        };
+        Some(if property_type == xlib::XA_STRING {
+            decode_latin1(&data)
+        } else {
+            String::from_utf8_lossy(&data).into_owned()
+        })
+    }
+
+    fn read_incr(&mut self) -> Option<(xlib::Atom, Vec<u8>)> {
+        let mut property_type = 0;
+        let mut data = Vec::new();
+        let mut deadline = Instant::now() + GET_TIMEOUT;
+        while Instant::now() < deadline {
+            match self.next_event() {
+                Some(event) if event.get_type() == xlib::PropertyNotify => {
+                    let notify = unsafe { event.property };
+                    if notify.window != self.window || notify.atom != self.atoms.property || notify.state != xlib::PropertyNewValue {
+                        self.handle_event(event);
+                        continue;
+                    }
+                    let (chunk_type, chunk) = self.read_property()?;
+                    if chunk.is_empty() {
+                        return Some((property_type, data));
+                    }
+                    property_type = chunk_type;
+                    data.extend_from_slice(&chunk);
+                    deadline = Instant::now() + GET_TIMEOUT;
+                }
+                Some(event) => self.handle_event(event),
+                None => std::thread::sleep(POLL_INTERVAL),
+            }
+        }
+        None
+    }
+
+    fn read_property(&mut self) -> Option<(xlib::Atom, Vec<u8>)> {
+        unsafe {
+            let mut actual_type = 0;
+            let mut actual_format = 0;
+            let mut item_count: c_ulong = 0;
+            let mut bytes_after: c_ulong = 0;
+            let mut data = std::ptr::null_mut();
+            let status = (self.xlib.XGetWindowProperty)(
+                self.display, self.window, self.atoms.property, 0, c_long::MAX / 4, xlib::True, xlib::AnyPropertyType as c_ulong,
+                &mut actual_type, &mut actual_format, &mut item_count, &mut bytes_after, &mut data,
+            );
+            if status != xlib::Success as c_int || data.is_null() {
+                return None;
+            }
+            let bytes = if actual_format == 8 {
+                std::slice::from_raw_parts(data, item_count as usize).to_vec()
+            } else {
+                Vec::new()
+            };
+            (self.xlib.XFree)(data as *mut _);
+            Some((actual_type, bytes))
+        }
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        unsafe {
+            (self.xlib.XDestroyWindow)(self.display, self.window);
+            (self.xlib.XCloseDisplay)(self.display);
+        }
+    }
+}
+
+fn encode_latin1(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|char| if (char as u32) < 0x100 { char as u8 } else { b'?' })
+        .collect()
+}
+
+fn decode_latin1(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| *byte as char).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{encode_latin1, decode_latin1};
+
+    #[test]
+    fn test_latin1() {
+        assert_eq!(encode_latin1("café 📋"), b"caf\xe9 ?");
+        assert_eq!(decode_latin1(b"caf\xe9"), "café");
+    }
+}
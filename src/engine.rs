@@ -3,18 +3,26 @@ use crate::math::Vector;
 use crate::event::{Event, KeyAction};
 use crate::filesystem::{Filesystem, FilesystemConfig};
 use crate::window::{Window, WindowConfig, LogicalPosition, LogicalSize};
-use crate::graphics::{Graphics, GraphicsConfig};
+use crate::graphics::{Graphics, GraphicsConfig, WatchedAsset, watch_paths};
+use crate::loader::{Loader, LoaderConfig};
+use crate::assets::Assets;
 use crate::timer::{Timer, TimerConfig};
 use crate::keyboard::{Keyboard, KeyboardConfig};
 use crate::mouse::{Mouse, MouseConfig};
 use crate::touch::{Touch, TouchConfig};
 use crate::touchpad::{Touchpad, TouchpadConfig};
 use crate::gamepad::{Gamepad, GamepadConfig};
+use crate::action::{Actions, ActionsConfig};
+use crate::clipboard::{Clipboard, ClipboardConfig};
 use crate::audio::{Audio, AudioConfig};
+use crate::replay::{InputRecording, Replay};
 use crate::game::Game;
 use winit::event_loop::{EventLoop, ControlFlow};
 use winit::event::{StartCause, WindowEvent, MouseScrollDelta};
 use winit::platform::run_return::EventLoopExtRunReturn;
+use std::path::Path;
+use std::time::Duration;
+use std::collections::VecDeque;
 
 #[derive(Debug)]
 enum State {
@@ -25,8 +33,10 @@ enum State {
 }
 
 pub struct Engine {
+    clipboard: Clipboard,
     event_loop: Option<EventLoop<()>>,
     filesystem: Filesystem,
+    assets: Assets,
     window: Window,
     graphics: Graphics,
     timer: Timer,
@@ -35,7 +45,12 @@ pub struct Engine {
     touch: Touch,
     touchpad: Touchpad,
     gamepad: Gamepad,
+    actions: Actions,
     audio: Audio,
+    loader: Loader,
+    injected_events: VecDeque<Event>,
+    replay: Replay,
+    replay_fixed_update_duration: Option<Option<Duration>>,
     state: State,
 }
 
@@ -76,10 +91,26 @@ impl Engine {
         &mut self.gamepad
     }
 
+    pub fn actions(&mut self) -> &mut Actions {
+        &mut self.actions
+    }
+
+    pub fn clipboard(&mut self) -> &mut Clipboard {
+        &mut self.clipboard
+    }
+
     pub fn audio(&mut self) -> &mut Audio {
         &mut self.audio
     }
 
+    pub fn loader(&mut self) -> &mut Loader {
+        &mut self.loader
+    }
+
+    pub fn assets(&mut self) -> &mut Assets {
+        &mut self.assets
+    }
+
     pub fn quit(&mut self) {
         match &self.state {
             State::Finished | State::Broken(_) => (),
@@ -94,6 +125,11 @@ impl Engine {
         }
     }
 
+    pub(crate) fn watch_asset(&mut self, asset: WatchedAsset, paths: &[&Path]) {
+        let paths = watch_paths(&self.filesystem, paths);
+        self.graphics.watch_asset(asset, paths);
+    }
+
     fn handle_event(&mut self, event: winit::event::Event<()>, control_flow: &mut ControlFlow, game: &mut impl Game) -> GameResult {
         match event {
             winit::event::Event::NewEvents(start_cause) => {
@@ -103,16 +139,15 @@ impl Engine {
                 }
             }
             winit::event::Event::WindowEvent { window_id, event } => {
-                if window_id == self.window.window().id() {
+                if self.window.id() == Some(window_id) {
                     match event {
                         WindowEvent::CloseRequested => {
-                            if !game.event(self, Event::WindowClose)? {
+                            if !self.dispatch_event(game, Event::WindowClose)? {
                                 *control_flow = ControlFlow::Exit;
-                                self.quit();
                             }
                         }
                         WindowEvent::Resized(physical_size) => {
-                            let scale_factor = self.window.window().scale_factor();
+                            let scale_factor = self.window.scale_factor() as f64;
                             let logical_size = physical_size.to_logical(scale_factor);
                             self.graphics.resize(physical_size, scale_factor);
                             game.event(self, Event::WindowResize(LogicalSize::new(logical_size.width, logical_size.height)))?;
@@ -123,77 +158,74 @@ impl Engine {
                             game.event(self, Event::WindowResize(LogicalSize::new(logical_size.width, logical_size.height)))?;
                         }
                         WindowEvent::Moved(physical_position) => {
-                            let scale_factor = self.window.window().scale_factor();
+                            let scale_factor = self.window.scale_factor() as f64;
                             let logical_position = physical_position.to_logical(scale_factor);
                             game.event(self, Event::WindowMove(LogicalPosition::new(logical_position.x, logical_position.y)))?;
                         }
                         WindowEvent::Focused(focused) => {
-                            self.window.handle_focus_change_event(focused);
-                            game.event(self, Event::WindowFocusChange(focused))?;
+                            self.dispatch_event(game, Event::WindowFocusChange(focused))?;
                         }
                         WindowEvent::ReceivedCharacter(char) => {
-                            game.event(self, Event::ReceiveChar(char))?;
+                            self.dispatch_event(game, Event::ReceiveChar(char))?;
+                        }
+                        WindowEvent::Ime(ime) => {
+                            let event = match ime {
+                                winit::event::Ime::Enabled => Event::ImeEnable,
+                                winit::event::Ime::Preedit(text, cursor) => Event::ImePreedit { text, cursor },
+                                winit::event::Ime::Commit(text) => Event::ImeCommit(text),
+                                winit::event::Ime::Disabled => Event::ImeDisable,
+                            };
+                            self.dispatch_event(game, event)?;
                         }
                         WindowEvent::KeyboardInput { input, .. } => {
                             let key = (input.virtual_keycode, input.scancode).into();
                             let action = input.state.into();
-                            let repeated = self.keyboard.handle_input_event(key, action);
-                            game.event(self, Event::KeyboardInput { key, action, repeated })?;
+                            self.dispatch_event(game, Event::KeyboardInput { key, action, repeated: false })?;
                         }
                         WindowEvent::ModifiersChanged(state) => {
-                            let state = state.into();
-                            self.keyboard.handle_modifiers_state_change(state);
-                            game.event(self, Event::ModifiersChange(state))?;
+                            self.dispatch_event(game, Event::ModifiersChange(state.into()))?;
                         }
                         WindowEvent::CursorMoved { position, .. } => {
-                            let scale_factor = self.window.window().scale_factor();
+                            let scale_factor = self.window.scale_factor() as f64;
                             let logical_position = position.to_logical(scale_factor);
                             let position = LogicalPosition::new(logical_position.x, logical_position.y);
-                            self.mouse.handle_move_event(position);
-                            game.event(self, Event::MouseMove(position))?;
+                            self.dispatch_event(game, Event::MouseMove(position))?;
                         }
                         WindowEvent::CursorEntered { .. } => {
-                            self.mouse.handle_enter_window_event();
-                            game.event(self, Event::MouseEnterWindow)?;
+                            self.dispatch_event(game, Event::MouseEnterWindow)?;
                         }
                         WindowEvent::CursorLeft { .. } => {
-                            self.mouse.handle_leave_window_event();
-                            game.event(self, Event::MouseLeaveWindow)?;
+                            self.dispatch_event(game, Event::MouseLeaveWindow)?;
                         }
                         WindowEvent::MouseWheel { delta, phase, .. } => {
                             match delta {
                                 MouseScrollDelta::LineDelta(delta_x, delta_y) => {
                                     let delta = Vector::new(delta_x, delta_y);
-                                    self.mouse.handle_wheel_scroll_event(delta);
-                                    game.event(self, Event::MouseWheelScroll(delta))?;
+                                    self.dispatch_event(game, Event::MouseWheelScroll(delta))?;
                                 }
                                 MouseScrollDelta::PixelDelta(logical_position) => {
                                     let delta = Vector::new(logical_position.x as f32, logical_position.y as f32);
-                                    self.touchpad.handle_scroll_event(delta);
-                                    game.event(self, Event::TouchpadScroll { delta, phase: phase.into() })?;
+                                    self.dispatch_event(game, Event::TouchpadScroll { delta, phase: phase.into() })?;
                                 }
                             }
                         }
                         WindowEvent::MouseInput { state, button, .. } => {
                             let button = button.into();
                             let action = state.into();
-                            self.mouse.handle_input_event(button, action);
-                            game.event(self, Event::MouseInput { button, action })?;
+                            self.dispatch_event(game, Event::MouseInput { button, action })?;
                         }
                         WindowEvent::Touch(touch) => {
                             let id = touch.id;
                             let phase = touch.phase.into();
                             let position = {
-                                let scale_factor = self.window.window().scale_factor();
+                                let scale_factor = self.window.scale_factor() as f64;
                                 let logical_position = touch.location.to_logical(scale_factor);
                                 LogicalPosition::new(logical_position.x, logical_position.y)
                             };
-                            self.touch.handle_event(id, phase, position);
-                            game.event(self, Event::Touch { id, phase, position })?;
+                            self.dispatch_event(game, Event::Touch { id, phase, position })?;
                         }
                         WindowEvent::TouchpadPressure { pressure, stage, .. } => {
-                            self.touchpad.handle_press_event(pressure, stage);
-                            game.event(self, Event::TouchpadPress { pressure, click_stage: stage })?;
+                            self.dispatch_event(game, Event::TouchpadPress { pressure, click_stage: stage })?;
                         }
                         WindowEvent::Destroyed => self.quit(),
                         _ => (),
@@ -209,57 +241,15 @@ impl Engine {
                 game.event(self, Event::AppResume)?;
             }
             winit::event::Event::MainEventsCleared => {
-                let events = self.gamepad.pump_events();
-                for event in events {
-                    let id = event.id;
-                    let event = event.event;
-                    match event {
-                        gilrs::EventType::Connected => {
-                            self.gamepad.handle_connect_event(id);
-                            game.event(self, Event::GamepadConnect(id))?;
-                        }
-                        gilrs::EventType::Disconnected => {
-                            self.gamepad.handle_disconnect_event(id);
-                            game.event(self, Event::GamepadDisconnect(id))?;
-                        }
-                        gilrs::EventType::ButtonPressed(button, _) => {
-                            let button = button.into();
-                            let action = KeyAction::Down;
-                            self.gamepad.handle_button_input_event(id, button, action);
-                            game.event(self, Event::GamepadButtonInput { id, button, action })?;
-                        }
-                        gilrs::EventType::ButtonReleased(button, _) => {
-                            let button = button.into();
-                            let action = KeyAction::Up;
-                            self.gamepad.handle_button_input_event(id, button, action);
-                            game.event(self, Event::GamepadButtonInput { id, button, action })?;
-                        }
-                        gilrs::EventType::ButtonChanged(button, value, _) => {
-                            let button = button.into();
-                            self.gamepad.handle_button_change_event(id, button, value);
-                            game.event(self, Event::GamepadButtonChange { id, button, value })?;
-                        }
-                        gilrs::EventType::AxisChanged(axis, value, _) => {
-                            let axis = axis.into();
-                            self.gamepad.handle_axis_change_event(id, axis, value);
-                            game.event(self, Event::GamepadAxisChange { id, axis, value })?;
-                        }
-                        _ => (),
-                    }
-                }
-                self.window.window().request_redraw();
+                self.handle_main_events_cleared(game)?;
+                self.window.request_redraw();
             }
             winit::event::Event::RedrawRequested(window_id) => {
-                if window_id == self.window.window().id() {
-                    if self.timer.tick_and_check() {
-                        game.update(self)?;
-                        game.render(self)?;
-                        self.graphics.present()?;
-                        self.keyboard.clear_states();
-                        self.mouse.clear_states();
-                        self.touch.clear_states();
-                        self.touchpad.clear_states();
-                        self.gamepad.clear_states();
+                if self.window.id() == Some(window_id) {
+                    if let Some(delta_time) = self.timer.check() {
+                        let delta_time = self.replay.frame_delta().unwrap_or(delta_time);
+                        self.timer.tick_with(delta_time);
+                        self.handle_frame(game)?;
                     }
                 }
             }
@@ -272,6 +262,228 @@ impl Engine {
         Ok(())
     }
 
+    fn dispatch_event(&mut self, game: &mut impl Game, event: Event) -> GameResult<bool> {
+        if event.is_input() {
+            if self.replay.is_replaying() {
+                return Ok(false);
+            }
+            self.replay.record_event(&event);
+        }
+        self.apply_event(game, event)
+    }
+
+    fn apply_event(&mut self, game: &mut impl Game, mut event: Event) -> GameResult<bool> {
+        match &mut event {
+            Event::WindowFocusChange(focused) => self.window.handle_focus_change_event(*focused),
+            Event::KeyboardInput { key, action, repeated } => *repeated = self.keyboard.handle_input_event(*key, *action),
+            Event::ModifiersChange(state) => self.keyboard.handle_modifiers_state_change(*state),
+            Event::MouseMove(position) => self.mouse.handle_move_event(*position),
+            Event::MouseEnterWindow => self.mouse.handle_enter_window_event(),
+            Event::MouseLeaveWindow => self.mouse.handle_leave_window_event(),
+            Event::MouseWheelScroll(delta) => self.mouse.handle_wheel_scroll_event(*delta),
+            Event::MouseInput { button, action } => self.mouse.handle_input_event(*button, *action),
+            Event::Touch { id, phase, position } => self.touch.handle_event(*id, *phase, *position, self.timer.elapsed()),
+            Event::TouchpadScroll { delta, phase } => self.touchpad.handle_scroll_event(*delta, *phase, self.timer.elapsed(), self.touch.is_gestures_enabled()),
+            Event::TouchpadPress { pressure, click_stage } => self.touchpad.handle_press_event(*pressure, *click_stage),
+            Event::GamepadConnect(id) => self.gamepad.handle_connect_event(*id),
+            Event::GamepadDisconnect(id) => self.gamepad.handle_disconnect_event(*id),
+            Event::GamepadButtonInput { id, button, action } => self.gamepad.handle_button_input_event(*id, *button, *action),
+            Event::GamepadButtonChange { id, button, value } => self.gamepad.handle_button_change_event(*id, *button, *value),
+            Event::GamepadAxisChange { id, axis, value } => self.gamepad.handle_axis_change_event(*id, *axis, *value),
+            _ => (),
+        }
+        let close = matches!(event, Event::WindowClose);
+        let gesture = matches!(event, Event::Touch { .. } | Event::TouchpadScroll { .. });
+        let emulated_events = self.touch.emulate_event(&event);
+        let prevented = game.event(self, event)?;
+        if close && !prevented {
+            self.quit();
+        }
+        for emulated_event in emulated_events {
+            self.apply_event(game, emulated_event)?;
+        }
+        if gesture {
+            self.dispatch_gestures(game)?;
+        }
+        Ok(prevented)
+    }
+
+    fn dispatch_gestures(&mut self, game: &mut impl Game) -> GameResult {
+        let mut gestures = self.touch.take_gestures();
+        gestures.extend(self.touchpad.take_gestures());
+        for gesture in gestures {
+            game.event(self, Event::Gesture(gesture))?;
+        }
+        Ok(())
+    }
+
+    pub fn inject_event(&mut self, event: Event) {
+        self.injected_events.push_back(event);
+    }
+
+    pub fn injected_event_count(&self) -> usize {
+        self.injected_events.len()
+    }
+
+    pub fn start_recording(&mut self, seed: u64) -> GameResult {
+        if self.replay.is_replaying() {
+            return Err(GameError::StateError("can not start recording while replaying".into()));
+        }
+        let mouse_position = self.mouse.last_position();
+        let mouse_inside_window = self.mouse.is_inside_window();
+        self.reset_input_states();
+        self.replay = Replay::Recording(InputRecording::new(seed, self.timer.fixed_update_duration()));
+        let mut initial_events = vec![Event::MouseMove(mouse_position)];
+        if mouse_inside_window {
+            initial_events.push(Event::MouseEnterWindow);
+        }
+        for event in initial_events {
+            self.replay.record_event(&event);
+            match event {
+                Event::MouseMove(position) => self.mouse.handle_move_event(position),
+                _ => self.mouse.handle_enter_window_event(),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn stop_recording(&mut self) -> Option<InputRecording> {
+        if self.replay.is_recording() {
+            self.replay.take()
+        } else {
+            None
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.replay.is_recording()
+    }
+
+    pub fn start_replay(&mut self, recording: InputRecording) -> GameResult {
+        if self.replay.is_recording() {
+            return Err(GameError::StateError("can not start replay while recording".into()));
+        }
+        if recording.frame_count() == 0 {
+            self.stop_replay();
+            return Ok(());
+        }
+        if self.replay_fixed_update_duration.is_none() {
+            self.replay_fixed_update_duration = Some(self.timer.fixed_update_duration());
+        }
+        self.timer.set_fixed_update_duration(recording.fixed_update_duration());
+        self.reset_input_states();
+        self.replay = Replay::Replaying { recording, frame: 0, event_index: 0 };
+        Ok(())
+    }
+
+    pub fn stop_replay(&mut self) -> Option<InputRecording> {
+        if let Some(fixed_update_duration) = self.replay_fixed_update_duration.take() {
+            self.timer.set_fixed_update_duration(fixed_update_duration);
+        }
+        if self.replay.is_replaying() {
+            self.replay.take()
+        } else {
+            None
+        }
+    }
+
+    fn reset_input_states(&mut self) {
+        self.keyboard.reset_states();
+        self.mouse.reset_states();
+        self.touch.reset_states();
+        self.touchpad.reset_states();
+        self.gamepad.reset_states();
+        self.actions.reset_states();
+        self.timer.reset_tick();
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        self.replay.is_replaying()
+    }
+
+    pub fn replay_seed(&self) -> Option<u64> {
+        self.replay.seed()
+    }
+
+    fn handle_main_events_cleared(&mut self, game: &mut impl Game) -> GameResult {
+        if let Some(physical_size) = self.window.take_headless_resize() {
+            let scale_factor = self.window.scale_factor() as f64;
+            let logical_size = physical_size.to_logical(scale_factor);
+            self.graphics.resize(physical_size, scale_factor);
+            game.event(self, Event::WindowResize(LogicalSize::new(logical_size.width, logical_size.height)))?;
+        }
+        while let Some(event) = self.injected_events.pop_front() {
+            self.dispatch_event(game, event)?;
+        }
+        for event in self.replay.take_frame_events() {
+            self.apply_event(game, event)?;
+        }
+        let events = self.gamepad.pump_events();
+        for (id, event) in events {
+            let event = match event {
+                gilrs::EventType::Connected => Event::GamepadConnect(id),
+                gilrs::EventType::Disconnected => Event::GamepadDisconnect(id),
+                gilrs::EventType::ButtonPressed(button, _) => Event::GamepadButtonInput { id, button: button.into(), action: KeyAction::Down },
+                gilrs::EventType::ButtonReleased(button, _) => Event::GamepadButtonInput { id, button: button.into(), action: KeyAction::Up },
+                gilrs::EventType::ButtonChanged(button, value, _) => Event::GamepadButtonChange { id, button: button.into(), value },
+                gilrs::EventType::AxisChanged(axis, value, _) => Event::GamepadAxisChange { id, axis: axis.into(), value },
+                _ => continue,
+            };
+            self.dispatch_event(game, event)?;
+        }
+        self.loader.pump(&mut self.graphics);
+        let events = self.graphics.poll_hot_reload(&self.filesystem);
+        for event in events {
+            game.event(self, event)?;
+        }
+        Ok(())
+    }
+
+    fn handle_frame(&mut self, game: &mut impl Game) -> GameResult {
+        self.touch.update_gestures(self.timer.elapsed());
+        self.dispatch_gestures(game)?;
+        for handle in self.timer.take_fired_timers() {
+            game.event(self, Event::TimerFire(handle))?;
+        }
+        let update_steps = self.timer.update_steps();
+        if update_steps > 0 {
+            self.actions.update(&self.keyboard, &self.mouse, &self.gamepad);
+        }
+        for _ in 0..update_steps {
+            game.update(self)?;
+        }
+        game.render(self)?;
+        self.graphics.present()?;
+        if update_steps > 0 {
+            self.keyboard.clear_states();
+            self.mouse.clear_states();
+            self.touch.clear_states();
+            self.touchpad.clear_states();
+            self.gamepad.clear_states();
+        }
+        if self.replay.end_frame(self.timer.unscaled_delta_time()) {
+            self.stop_replay();
+            game.event(self, Event::ReplayFinish)?;
+        }
+        Ok(())
+    }
+
+    pub fn step(&mut self, game: &mut impl Game, delta_time: Duration) -> GameResult {
+        match &self.state {
+            State::Ready => self.state = State::Running,
+            State::Running => (),
+            _ => return Err(GameError::StateError(format!("engine can not be stepped on state `{:?}`", self.state).into())),
+        }
+        self.handle_main_events_cleared(game)?;
+        let delta_time = self.replay.frame_delta().unwrap_or(delta_time);
+        self.timer.tick_with(delta_time);
+        self.handle_frame(game)
+    }
+
+    pub fn is_running(&self) -> bool {
+        matches!(self.state, State::Ready | State::Running)
+    }
+
     pub fn run(&mut self, game: &mut impl Game) -> GameResult {
         match &self.state {
             State::Ready => self.state = State::Running,
@@ -325,7 +537,11 @@ pub struct EngineBuilder {
     touch_config: Option<TouchConfig>,
     touchpad_config: Option<TouchpadConfig>,
     gamepad_config: Option<GamepadConfig>,
+    actions_config: Option<ActionsConfig>,
+    clipboard_config: Option<ClipboardConfig>,
     audio_config: Option<AudioConfig>,
+    loader_config: Option<LoaderConfig>,
+    headless: bool,
 }
 
 impl EngineBuilder {
@@ -340,7 +556,11 @@ impl EngineBuilder {
             touch_config: None,
             touchpad_config: None,
             gamepad_config: None,
+            actions_config: None,
+            clipboard_config: None,
             audio_config: None,
+            loader_config: None,
+            headless: false,
         }
     }
 
@@ -389,11 +609,31 @@ impl EngineBuilder {
         self
     }
 
+    pub fn actions_config(mut self, actions_config: ActionsConfig) -> Self {
+        self.actions_config = Some(actions_config);
+        self
+    }
+
+    pub fn clipboard_config(mut self, clipboard_config: ClipboardConfig) -> Self {
+        self.clipboard_config = Some(clipboard_config);
+        self
+    }
+
     pub fn audio_config(mut self, audio_config: AudioConfig) -> Self {
         self.audio_config = Some(audio_config);
         self
     }
 
+    pub fn loader_config(mut self, loader_config: LoaderConfig) -> Self {
+        self.loader_config = Some(loader_config);
+        self
+    }
+
+    pub fn headless(mut self, headless: bool) -> Self {
+        self.headless = headless;
+        self
+    }
+
     pub fn build(self) -> GameResult<Engine> {
         let filesystem_config = self.filesystem_config.unwrap_or_else(|| FilesystemConfig::new());
         let window_config = self.window_config.unwrap_or_else(|| WindowConfig::new());
@@ -404,23 +644,33 @@ impl EngineBuilder {
         let touch_config = self.touch_config.unwrap_or_else(|| TouchConfig::new());
         let touchpad_config = self.touchpad_config.unwrap_or_else(|| TouchpadConfig::new());
         let gamepad_config = self.gamepad_config.unwrap_or_else(|| GamepadConfig::new());
+        let actions_config = self.actions_config.unwrap_or_else(|| ActionsConfig::new());
+        let clipboard_config = self.clipboard_config.unwrap_or_else(|| ClipboardConfig::new());
         let audio_config = self.audio_config.unwrap_or_else(|| AudioConfig::new());
-
-        let event_loop = EventLoop::new();
+        let loader_config = self.loader_config.unwrap_or_else(|| LoaderConfig::new());
 
         let filesystem = Filesystem::new(filesystem_config)?;
-        let window = Window::new(window_config, &event_loop, &filesystem)?;
-        let graphics = Graphics::new(graphics_config, window.context_wrapper(), window.gl())?;
+        let (event_loop, window) = if self.headless {
+            (None, Window::new_headless(window_config)?)
+        } else {
+            let event_loop = EventLoop::new();
+            let window = Window::new(window_config, &event_loop, &filesystem)?;
+            (Some(event_loop), window)
+        };
+        let graphics = Graphics::new(graphics_config, window.context(), window.gl())?;
         let timer = Timer::new(timer_config)?;
         let keyboard = Keyboard::new(keyboard_config)?;
-        let mouse = Mouse::new(mouse_config, window.context_wrapper())?;
+        let mouse = Mouse::new(mouse_config, window.context())?;
         let touch = Touch::new(touch_config)?;
         let touchpad = Touchpad::new(touchpad_config)?;
-        let gamepad = Gamepad::new(gamepad_config)?;
-        let audio = Audio::new(audio_config)?;
+        let gamepad = Gamepad::new(gamepad_config, &filesystem)?;
+        let actions = Actions::new(actions_config)?;
+        let clipboard = Clipboard::new(clipboard_config, window.window())?;
+        let audio = Audio::new(audio_config, self.headless)?;
+        let loader = Loader::new(loader_config)?;
 
         Ok(Engine {
-            event_loop: Some(event_loop),
+            event_loop,
             filesystem,
             window,
             graphics,
@@ -430,8 +680,130 @@ impl EngineBuilder {
             touch,
             touchpad,
             gamepad,
+            actions,
+            clipboard,
             audio,
+            loader,
+            assets: Assets::new(),
+            injected_events: VecDeque::new(),
+            replay: Replay::Idle,
+            replay_fixed_update_duration: None,
             state: State::Ready,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::EngineBuilder;
+    use crate::error::GameResult;
+    use crate::engine::Engine;
+    use crate::game::Game;
+    use crate::event::{Event, KeyAction};
+    use crate::keyboard::KeyCode;
+    use crate::window::LogicalSize;
+    use crate::math::Size;
+    use crate::replay::InputRecording;
+    use std::time::Duration;
+
+    struct Counter {
+        updates: u32,
+        renders: u32,
+    }
+
+    impl Game for Counter {
+        fn update(&mut self, engine: &mut Engine) -> GameResult {
+            self.updates += 1;
+            if self.updates == 3 {
+                engine.quit();
+            }
+            Ok(())
+        }
+
+        fn render(&mut self, _engine: &mut Engine) -> GameResult {
+            self.renders += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_headless_step() {
+        let mut engine = EngineBuilder::new().headless(true).build().unwrap();
+        assert!(engine.window().is_headless());
+        let mut game = Counter { updates: 0, renders: 0 };
+        for _ in 0..3 {
+            engine.step(&mut game, Duration::from_millis(16)).unwrap();
+        }
+        assert_eq!((game.updates, game.renders), (3, 3));
+        assert_eq!(engine.timer().elapsed(), Duration::from_millis(48));
+        assert!(!engine.is_running());
+        assert!(engine.step(&mut game, Duration::from_millis(16)).is_err());
+    }
+
+    struct Recorder {
+        events: Vec<Event>,
+        key_down_updates: u32,
+    }
+
+    impl Game for Recorder {
+        fn update(&mut self, engine: &mut Engine) -> GameResult {
+            if engine.keyboard().is_key_down(KeyCode::Space) {
+                self.key_down_updates += 1;
+            }
+            Ok(())
+        }
+
+        fn render(&mut self, _engine: &mut Engine) -> GameResult {
+            Ok(())
+        }
+
+        fn event(&mut self, _engine: &mut Engine, event: Event) -> GameResult<bool> {
+            self.events.push(event);
+            Ok(false)
+        }
+    }
+
+    #[test]
+    fn test_inject_event() {
+        let mut engine = EngineBuilder::new().headless(true).build().unwrap();
+        let mut game = Recorder { events: Vec::new(), key_down_updates: 0 };
+        engine.inject_event(Event::KeyboardInput { key: KeyCode::Space, action: KeyAction::Down, repeated: false });
+        engine.inject_event(Event::KeyboardInput { key: KeyCode::Space, action: KeyAction::Down, repeated: false });
+        assert_eq!(engine.injected_event_count(), 2);
+        engine.step(&mut game, Duration::from_millis(16)).unwrap();
+        engine.step(&mut game, Duration::from_millis(16)).unwrap();
+        assert_eq!(engine.injected_event_count(), 0);
+        assert_eq!(game.key_down_updates, 1);
+        assert!(engine.keyboard().is_key_hold(KeyCode::Space));
+        assert_eq!(game.events[1], Event::KeyboardInput { key: KeyCode::Space, action: KeyAction::Down, repeated: true });
+        engine.window().set_inner_size(LogicalSize::new(320.0, 240.0));
+        engine.step(&mut game, Duration::from_millis(16)).unwrap();
+        assert_eq!(engine.window().inner_size(), LogicalSize::new(320.0, 240.0));
+        assert_eq!(engine.graphics().size(), Size::new(320.0, 240.0));
+        assert_eq!(game.events.last(), Some(&Event::WindowResize(LogicalSize::new(320.0, 240.0))));
+        engine.inject_event(Event::WindowClose);
+        engine.step(&mut game, Duration::from_millis(16)).unwrap();
+        assert!(!engine.is_running());
+    }
+
+    #[test]
+    fn test_replay_state() {
+        let mut engine = EngineBuilder::new().headless(true).build().unwrap();
+        let mut game = Recorder { events: Vec::new(), key_down_updates: 0 };
+        engine.timer().set_fixed_update_duration(Some(Duration::from_millis(10)));
+        engine.inject_event(Event::KeyboardInput { key: KeyCode::Space, action: KeyAction::Down, repeated: false });
+        engine.step(&mut game, Duration::from_millis(16)).unwrap();
+        assert!(engine.keyboard().is_key_hold(KeyCode::Space));
+        let mut recording = InputRecording::new(1, None);
+        recording.push_frame(Duration::from_millis(16));
+        recording.push_frame(Duration::from_millis(16));
+        engine.start_replay(recording).unwrap();
+        assert!(!engine.keyboard().is_key_hold(KeyCode::Space));
+        assert_eq!(engine.timer().fixed_update_duration(), None);
+        engine.step(&mut game, Duration::from_millis(16)).unwrap();
+        engine.step(&mut game, Duration::from_millis(16)).unwrap();
+        assert!(!engine.is_replaying());
+        assert_eq!(game.events.last(), Some(&Event::ReplayFinish));
+        assert_eq!(engine.timer().fixed_update_duration(), Some(Duration::from_millis(10)));
+    }
+}
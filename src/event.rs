@@ -3,7 +3,10 @@ use crate::window::{LogicalPosition, LogicalSize};
 use crate::keyboard::{KeyCode, ModifiersState};
 use crate::mouse::MouseButton;
 use crate::gamepad::{GamepadButton, GamepadAxis, GamepadId};
+use crate::timer::TimerHandle;
+use crate::touch::Gesture;
 use winit::event::ElementState;
+use std::path::PathBuf;
 
 #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
 pub(crate) enum KeyState {
@@ -65,6 +68,13 @@ pub enum Event {
     WindowMove(LogicalPosition),
     WindowFocusChange(bool),
     ReceiveChar(char),
+    ImeEnable,
+    ImePreedit {
+        text: String,
+        cursor: Option<(usize, usize)>,
+    },
+    ImeCommit(String),
+    ImeDisable,
     KeyboardInput {
         key: KeyCode,
         action: KeyAction,
@@ -92,6 +102,7 @@ pub enum Event {
         pressure: f32,
         click_stage: i64,
     },
+    Gesture(Gesture),
     GamepadConnect(GamepadId),
     GamepadDisconnect(GamepadId),
     GamepadButtonInput {
@@ -109,4 +120,39 @@ pub enum Event {
         axis: GamepadAxis,
         value: f32,
     },
+    AssetReload(PathBuf),
+    AssetReloadError {
+        path: PathBuf,
+        message: String,
+    },
+    TimerFire(TimerHandle),
+    ReplayFinish,
+}
+
+impl Event {
+    pub fn is_input(&self) -> bool {
+        matches!(
+            self,
+            Self::ReceiveChar(_)
+            | Self::ImeEnable
+            | Self::ImePreedit { .. }
+            | Self::ImeCommit(_)
+            | Self::ImeDisable
+            | Self::KeyboardInput { .. }
+            | Self::ModifiersChange(_)
+            | Self::MouseMove(_)
+            | Self::MouseEnterWindow
+            | Self::MouseLeaveWindow
+            | Self::MouseWheelScroll(_)
+            | Self::MouseInput { .. }
+            | Self::Touch { .. }
+            | Self::TouchpadScroll { .. }
+            | Self::TouchpadPress { .. }
+            | Self::GamepadConnect(_)
+            | Self::GamepadDisconnect(_)
+            | Self::GamepadButtonInput { .. }
+            | Self::GamepadButtonChange { .. }
+            | Self::GamepadAxisChange { .. }
+        )
+    }
 }
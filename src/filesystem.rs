@@ -1,27 +1,424 @@
+mod path;
+mod metadata;
+mod mount;
+mod archive;
+
+use mount::Mount;
+
+pub use path::normalize_path;
+pub use metadata::Metadata;
+pub use mount::FilesystemRoot;
+pub use archive::PackWriter;
+
 use crate::error::{GameError, GameResult};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::fs::{File, OpenOptions};
+use std::io::{ErrorKind, Write};
+
+fn not_found_error(path: &Path) -> GameError {
+    GameError::IoError(Box::new(std::io::Error::new(ErrorKind::NotFound, format!("path `{}` not found", path.display()))))
+}
 
-pub struct Filesystem {}
+fn validate_identity(identity: &str) -> GameResult {
+    if identity.is_empty() || identity == "." || identity == ".." || identity.contains(['/', '\\']) {
+        return Err(GameError::InitError(format!("identity `{}` is not a valid directory name", identity).into()));
+    }
+    Ok(())
+}
+
+#[derive(Clone)]
+pub struct Filesystem {
+    mounts: Vec<Mount>,
+    save_dir: Option<PathBuf>,
+}
 
 impl Filesystem {
-    pub(crate) fn new(_: FilesystemConfig) -> GameResult<Self> {
-        Ok(Self {})
+    pub(crate) fn new(filesystem_config: FilesystemConfig) -> GameResult<Self> {
+        let save_dir = match (filesystem_config.save_dir, filesystem_config.identity) {
+            (Some(save_dir), _) => Some(save_dir),
+            (None, Some(identity)) => {
+                validate_identity(&identity)?;
+                let data_dir = dirs::data_dir()
+                    .ok_or_else(|| GameError::InitError("data directory is not available".into()))?;
+                Some(data_dir.join(identity))
+            }
+            (None, None) => None,
+        };
+        let mut roots = filesystem_config.roots;
+        if save_dir.is_some() && !roots.iter().any(|(root, _)| *root == FilesystemRoot::SaveDir) {
+            roots.insert(0, (FilesystemRoot::SaveDir, String::new()));
+        }
+        let mut mounts = Vec::with_capacity(roots.len());
+        for (root, mount_point) in roots {
+            mounts.push(Mount::new(root, &mount_point, save_dir.as_deref())?);
+        }
+        Ok(Self { mounts, save_dir })
+    }
+
+    pub fn save_dir(&self) -> Option<&Path> {
+        self.save_dir.as_deref()
+    }
+
+    pub fn mount(&mut self, root: FilesystemRoot, mount_point: impl AsRef<Path>, append: bool) -> GameResult {
+        let mount_point = mount_point.as_ref().to_str()
+            .ok_or_else(|| GameError::IoError("mount point is not valid unicode".into()))?;
+        let mount = Mount::new(root, mount_point, self.save_dir.as_deref())?;
+        self.unmount(mount.root());
+        if append {
+            self.mounts.push(mount);
+        } else {
+            self.mounts.insert(0, mount);
+        }
+        Ok(())
+    }
+
+    pub fn unmount(&mut self, root: &FilesystemRoot) -> bool {
+        let count = self.mounts.len();
+        self.mounts.retain(|mount| mount.root() != root);
+        self.mounts.len() != count
+    }
+
+    pub fn mounts(&self) -> Vec<(FilesystemRoot, String)> {
+        self.mounts.iter()
+            .map(|mount| (mount.root().clone(), mount.mount_point().to_owned()))
+            .collect()
+    }
+
+    pub fn real_path(&self, path: impl AsRef<Path>) -> Option<PathBuf> {
+        let path = path.as_ref();
+        if path.is_absolute() {
+            return if path.exists() { Some(path.to_path_buf()) } else { None };
+        }
+        let path = normalize_path(path).ok()?;
+        self.mounts.iter().find_map(|mount| mount.real_path(&path))
     }
 
     pub fn read(&self, path: impl AsRef<Path>) -> GameResult<Vec<u8>> {
-        std::fs::read(path).map_err(|error| GameError::IoError(error.into()))
+        let path = path.as_ref();
+        if path.is_absolute() {
+            return std::fs::read(path).map_err(|error| GameError::IoError(error.into()));
+        }
+        let virtual_path = normalize_path(path)?;
+        self.mounts.iter()
+            .find_map(|mount| mount.read(&virtual_path))
+            .unwrap_or_else(|| Err(not_found_error(path)))
     }
 
     pub fn read_to_string(&self, path: impl AsRef<Path>) -> GameResult<String> {
-        std::fs::read_to_string(path).map_err(|error| GameError::IoError(error.into()))
+        let bytes = self.read(path)?;
+        String::from_utf8(bytes).map_err(|error| GameError::IoError(error.into()))
+    }
+
+    pub fn metadata(&self, path: impl AsRef<Path>) -> GameResult<Metadata> {
+        let path = path.as_ref();
+        if path.is_absolute() {
+            let metadata = std::fs::metadata(path).map_err(|error| GameError::IoError(error.into()))?;
+            let modified = metadata.modified().ok();
+            return if metadata.is_dir() {
+                Ok(Metadata::dir(modified))
+            } else {
+                Ok(Metadata::file(metadata.len(), modified))
+            };
+        }
+        let virtual_path = normalize_path(path)?;
+        if let Some(metadata) = self.mounts.iter().find_map(|mount| mount.metadata(&virtual_path)) {
+            return Ok(metadata);
+        }
+        if self.mounts.iter().any(|mount| is_virtual_parent(&virtual_path, mount.mount_point())) {
+            return Ok(Metadata::dir(None));
+        }
+        Err(not_found_error(path))
+    }
+
+    pub fn exists(&self, path: impl AsRef<Path>) -> bool {
+        self.metadata(path).is_ok()
+    }
+
+    pub fn is_file(&self, path: impl AsRef<Path>) -> bool {
+        self.metadata(path).map(|metadata| metadata.is_file()).unwrap_or(false)
+    }
+
+    pub fn is_dir(&self, path: impl AsRef<Path>) -> bool {
+        self.metadata(path).map(|metadata| metadata.is_dir()).unwrap_or(false)
+    }
+
+    pub fn read_dir(&self, path: impl AsRef<Path>) -> GameResult<Vec<String>> {
+        let path = path.as_ref();
+        if path.is_absolute() {
+            let mut names = Vec::new();
+            for entry in std::fs::read_dir(path).map_err(|error| GameError::IoError(error.into()))?.flatten() {
+                if let Some(name) = entry.file_name().to_str() {
+                    names.push(name.to_owned());
+                }
+            }
+            names.sort();
+            return Ok(names);
+        }
+        let virtual_path = normalize_path(path)?;
+        let mut found = false;
+        let mut names = Vec::new();
+        for mount in &self.mounts {
+            if let Some(mount_names) = mount.read_dir(&virtual_path) {
+                found = true;
+                names.extend(mount_names);
+            }
+            if is_virtual_parent(&virtual_path, mount.mount_point()) {
+                found = true;
+                let rest = if virtual_path.is_empty() {
+                    mount.mount_point()
+                } else {
+                    &mount.mount_point()[virtual_path.len() + 1..]
+                };
+                if let Some(name) = rest.split('/').next() {
+                    names.push(name.to_owned());
+                }
+            }
+        }
+        if !found {
+            return Err(not_found_error(path));
+        }
+        names.sort();
+        names.dedup();
+        Ok(names)
+    }
+
+    fn save_path(&self, path: impl AsRef<Path>) -> GameResult<PathBuf> {
+        let save_dir = self.save_dir.as_ref()
+            .ok_or_else(|| GameError::StateError("save directory is not configured".into()))?;
+        let path = normalize_path(path)?;
+        Ok(if path.is_empty() { save_dir.clone() } else { save_dir.join(path) })
+    }
+
+    fn create_parent_dir(path: &Path) -> GameResult {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|error| GameError::IoError(error.into()))?;
+        }
+        Ok(())
+    }
+
+    pub fn create_dir_all(&self, path: impl AsRef<Path>) -> GameResult {
+        let path = self.save_path(path)?;
+        std::fs::create_dir_all(path).map_err(|error| GameError::IoError(error.into()))
+    }
+
+    pub fn write(&self, path: impl AsRef<Path>, bytes: impl AsRef<[u8]>) -> GameResult {
+        let path = self.save_path(path)?;
+        Self::create_parent_dir(&path)?;
+        std::fs::write(path, bytes).map_err(|error| GameError::IoError(error.into()))
+    }
+
+    pub fn append(&self, path: impl AsRef<Path>, bytes: impl AsRef<[u8]>) -> GameResult {
+        let path = self.save_path(path)?;
+        Self::create_parent_dir(&path)?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|error| GameError::IoError(error.into()))?;
+        file.write_all(bytes.as_ref()).map_err(|error| GameError::IoError(error.into()))
+    }
+
+    pub fn write_atomic(&self, path: impl AsRef<Path>, bytes: impl AsRef<[u8]>) -> GameResult {
+        let path = self.save_path(path)?;
+        Self::create_parent_dir(&path)?;
+        let file_name = path.file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| GameError::IoError("atomic write requires a file name".into()))?;
+        let temp_path = path.with_file_name(format!(".{}.{}.tmp", file_name, std::process::id()));
+        let result = File::create(&temp_path)
+            .and_then(|mut file| {
+                file.write_all(bytes.as_ref())?;
+                file.sync_all()
+            })
+            .and_then(|_| std::fs::rename(&temp_path, &path));
+        if let Err(error) = result {
+            let _ = std::fs::remove_file(&temp_path);
+            return Err(GameError::IoError(error.into()));
+        }
+        Ok(())
+    }
+
+    pub fn remove(&self, path: impl AsRef<Path>) -> GameResult {
+        let path = self.save_path(path)?;
+        if self.save_dir.as_deref() == Some(path.as_path()) {
+            return Err(GameError::IoError("save directory itself can not be removed".into()));
+        }
+        let metadata = std::fs::metadata(&path).map_err(|error| GameError::IoError(error.into()))?;
+        if metadata.is_dir() {
+            std::fs::remove_dir(path)
+        } else {
+            std::fs::remove_file(path)
+        }.map_err(|error| GameError::IoError(error.into()))
+    }
+}
+
+fn is_virtual_parent(path: &str, mount_point: &str) -> bool {
+    if mount_point.is_empty() || path == mount_point {
+        false
+    } else if path.is_empty() {
+        true
+    } else {
+        mount_point.starts_with(path) && mount_point[path.len()..].starts_with('/')
     }
 }
 
 #[derive(Debug, Clone)]
-pub struct FilesystemConfig {}
+pub struct FilesystemConfig {
+    roots: Vec<(FilesystemRoot, String)>,
+    identity: Option<String>,
+    save_dir: Option<PathBuf>,
+}
 
 impl FilesystemConfig {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            roots: vec![
+                (FilesystemRoot::CurrentDir, String::new()),
+                (FilesystemRoot::ExecutableDir, String::new()),
+            ],
+            identity: None,
+            save_dir: None,
+        }
+    }
+
+    pub fn identity(mut self, identity: impl Into<String>) -> Self {
+        self.identity = Some(identity.into());
+        self
+    }
+
+    pub fn save_dir(mut self, save_dir: impl Into<PathBuf>) -> Self {
+        self.save_dir = Some(save_dir.into());
+        self
+    }
+
+    pub fn roots(mut self, roots: Vec<FilesystemRoot>) -> Self {
+        self.roots = roots.into_iter()
+            .map(|root| (root, String::new()))
+            .collect();
+        self
+    }
+
+    pub fn mount(mut self, root: FilesystemRoot, mount_point: impl Into<String>) -> Self {
+        self.roots.push((root, mount_point.into()));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Filesystem, FilesystemConfig, FilesystemRoot, PackWriter};
+    use zip::write::{ZipWriter, FileOptions};
+    use zip::CompressionMethod;
+    use std::path::PathBuf;
+    use std::io::Write;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("tge-filesystem-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_search_order() {
+        let patch_dir = temp_dir("patch");
+        let base_dir = temp_dir("base");
+        std::fs::create_dir_all(base_dir.join("images")).unwrap();
+        std::fs::write(base_dir.join("images/a.png"), b"base a").unwrap();
+        std::fs::write(base_dir.join("images/b.png"), b"base b").unwrap();
+        std::fs::create_dir_all(patch_dir.join("images")).unwrap();
+        std::fs::write(patch_dir.join("images/a.png"), b"patch a").unwrap();
+        let filesystem = Filesystem::new(FilesystemConfig::new()
+            .roots(vec![FilesystemRoot::Path(patch_dir.clone()), FilesystemRoot::Path(base_dir.clone())])).unwrap();
+        assert_eq!(filesystem.read("images/a.png").unwrap(), b"patch a");
+        assert_eq!(filesystem.read("./images/../images/b.png").unwrap(), b"base b");
+        assert!(filesystem.read("images/c.png").is_err());
+        assert!(filesystem.read("../images/a.png").is_err());
+        assert!(filesystem.is_dir("images"));
+        assert!(filesystem.is_file("images/b.png"));
+        assert!(!filesystem.exists("images/c.png"));
+        assert_eq!(filesystem.metadata("images/b.png").unwrap().size(), 6);
+        assert_eq!(filesystem.read_dir("images").unwrap(), vec!["a.png", "b.png"]);
+        std::fs::remove_dir_all(patch_dir).unwrap();
+        std::fs::remove_dir_all(base_dir).unwrap();
+    }
+
+    #[test]
+    fn test_mount_point() {
+        let dir = temp_dir("mount");
+        std::fs::write(dir.join("title.ogg"), b"music").unwrap();
+        let mut filesystem = Filesystem::new(FilesystemConfig::new().roots(Vec::new())).unwrap();
+        filesystem.mount(FilesystemRoot::Path(dir.clone()), "assets/music", true).unwrap();
+        assert_eq!(filesystem.read("assets/music/title.ogg").unwrap(), b"music");
+        assert!(filesystem.read("title.ogg").is_err());
+        assert!(filesystem.is_dir("assets"));
+        assert_eq!(filesystem.read_dir("").unwrap(), vec!["assets"]);
+        assert_eq!(filesystem.read_dir("assets").unwrap(), vec!["music"]);
+        assert!(filesystem.unmount(&FilesystemRoot::Path(dir.clone())));
+        assert!(!filesystem.exists("assets/music/title.ogg"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_archive_overlay() {
+        let dir = temp_dir("archive");
+        let mut zip = ZipWriter::new(std::fs::File::create(dir.join("base.zip")).unwrap());
+        zip.start_file("fonts/title.ttf", FileOptions::default().compression_method(CompressionMethod::Deflated)).unwrap();
+        zip.write_all(&[7; 1024]).unwrap();
+        zip.start_file("images/a.png", FileOptions::default().compression_method(CompressionMethod::Stored)).unwrap();
+        zip.write_all(b"zip a").unwrap();
+        zip.finish().unwrap();
+        let mut pack = PackWriter::new();
+        pack.add("images/a.png", b"pack a".to_vec()).unwrap();
+        pack.add("images/b.png", b"pack b".to_vec()).unwrap();
+        pack.write(std::fs::File::create(dir.join("patch.pak")).unwrap()).unwrap();
+        std::fs::create_dir_all(dir.join("mods/images")).unwrap();
+        std::fs::write(dir.join("mods/images/b.png"), b"mod b").unwrap();
+        let mut filesystem = Filesystem::new(FilesystemConfig::new().roots(vec![
+            FilesystemRoot::Archive(dir.join("patch.pak")),
+            FilesystemRoot::Archive(dir.join("base.zip")),
+        ])).unwrap();
+        filesystem.mount(FilesystemRoot::Path(dir.join("mods")), "", false).unwrap();
+        assert_eq!(filesystem.read("fonts/title.ttf").unwrap(), vec![7; 1024]);
+        assert_eq!(filesystem.read("images/a.png").unwrap(), b"pack a");
+        assert_eq!(filesystem.read("images/b.png").unwrap(), b"mod b");
+        assert!(filesystem.is_dir("fonts"));
+        assert_eq!(filesystem.metadata("fonts/title.ttf").unwrap().size(), 1024);
+        assert_eq!(filesystem.read_dir("").unwrap(), vec!["fonts", "images"]);
+        assert_eq!(filesystem.read_dir("images").unwrap(), vec!["a.png", "b.png"]);
+        assert!(filesystem.real_path("fonts/title.ttf").is_none());
+        let mut broken = b"TPAK".to_vec();
+        broken.extend_from_slice(&1u32.to_le_bytes());
+        broken.extend_from_slice(&1u32.to_le_bytes());
+        broken.extend_from_slice(&u32::MAX.to_le_bytes());
+        std::fs::write(dir.join("broken.pak"), broken).unwrap();
+        assert!(Filesystem::new(FilesystemConfig::new().roots(vec![FilesystemRoot::Archive(dir.join("broken.pak"))])).is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_save_dir() {
+        let dir = temp_dir("save");
+        let filesystem = Filesystem::new(FilesystemConfig::new().roots(Vec::new()).save_dir(dir.join("game"))).unwrap();
+        assert_eq!(filesystem.save_dir(), Some(dir.join("game").as_path()));
+        filesystem.write("saves/slot1.dat", b"level 1").unwrap();
+        filesystem.write_atomic("saves/slot1.dat", b"level 2").unwrap();
+        filesystem.append("log.txt", b"a").unwrap();
+        filesystem.append("log.txt", b"b").unwrap();
+        assert_eq!(filesystem.read("saves/slot1.dat").unwrap(), b"level 2");
+        assert_eq!(filesystem.read_to_string("log.txt").unwrap(), "ab");
+        assert_eq!(filesystem.read_dir("saves").unwrap(), vec!["slot1.dat"]);
+        assert!(filesystem.write("../escape.dat", b"").is_err());
+        assert!(!dir.join("escape.dat").exists());
+        filesystem.create_dir_all("screenshots/2020").unwrap();
+        assert!(filesystem.is_dir("screenshots/2020"));
+        filesystem.remove("screenshots/2020").unwrap();
+        filesystem.remove("saves/slot1.dat").unwrap();
+        assert!(!filesystem.exists("saves/slot1.dat"));
+        assert!(filesystem.remove("").is_err());
+        let filesystem = Filesystem::new(FilesystemConfig::new().roots(Vec::new())).unwrap();
+        assert!(filesystem.write("slot1.dat", b"").is_err());
+        assert!(Filesystem::new(FilesystemConfig::new().identity("../game")).is_err());
+        std::fs::remove_dir_all(dir).unwrap();
     }
 }
@@ -0,0 +1,241 @@
+use super::{Metadata, normalize_path};
+use crate::error::{GameError, GameResult};
+use zip::ZipArchive;
+use std::path::Path;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::collections::{HashMap, BTreeSet};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+const PACK_MAGIC: &[u8; 4] = b"TPAK";
+const PACK_VERSION: u32 = 1;
+const MAX_PREALLOCATION: u64 = 16 * 1024 * 1024;
+
+fn read_u32(reader: &mut impl Read) -> std::io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(reader: &mut impl Read) -> std::io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+enum ArchiveReader {
+    Zip(Mutex<ZipArchive<File>>),
+    Pack(Mutex<File>),
+}
+
+#[derive(Debug, Copy, Clone)]
+struct ArchiveEntry {
+    location: u64,
+    size: u64,
+}
+
+pub(crate) struct Archive {
+    reader: ArchiveReader,
+    entries: HashMap<String, ArchiveEntry>,
+    dirs: BTreeSet<String>,
+    modified: Option<SystemTime>,
+}
+
+impl Archive {
+    pub fn open(path: impl AsRef<Path>) -> GameResult<Self> {
+        let path = path.as_ref();
+        let mut file = File::open(path).map_err(|error| GameError::IoError(error.into()))?;
+        let modified = file.metadata().and_then(|metadata| metadata.modified()).ok();
+        let mut magic = [0; 4];
+        let is_pack = file.read_exact(&mut magic).is_ok() && &magic == PACK_MAGIC;
+        file.seek(SeekFrom::Start(0)).map_err(|error| GameError::IoError(error.into()))?;
+        let mut archive = if is_pack {
+            Self::open_pack(file)
+        } else {
+            Self::open_zip(file)
+        }?;
+        archive.modified = modified;
+        Ok(archive)
+    }
+
+    fn open_zip(file: File) -> GameResult<Self> {
+        let mut zip = ZipArchive::new(file).map_err(|error| GameError::IoError(error.into()))?;
+        let mut entries = HashMap::new();
+        let mut dirs = BTreeSet::new();
+        for index in 0..zip.len() {
+            let file = zip.by_index(index).map_err(|error| GameError::IoError(error.into()))?;
+            let name = normalize_path(file.name())?;
+            if file.is_dir() {
+                insert_dirs(&mut dirs, &name);
+            } else {
+                insert_parent_dirs(&mut dirs, &name);
+                entries.insert(name, ArchiveEntry { location: index as u64, size: file.size() });
+            }
+        }
+        Ok(Self {
+            reader: ArchiveReader::Zip(Mutex::new(zip)),
+            entries,
+            dirs,
+            modified: None,
+        })
+    }
+
+    fn open_pack(mut file: File) -> GameResult<Self> {
+        let (entries, dirs) = Self::read_pack_index(&mut file)?;
+        Ok(Self {
+            reader: ArchiveReader::Pack(Mutex::new(file)),
+            entries,
+            dirs,
+            modified: None,
+        })
+    }
+
+    fn read_pack_index(file: &mut File) -> GameResult<(HashMap<String, ArchiveEntry>, BTreeSet<String>)> {
+        let file_size = file.metadata().map_err(|error| GameError::IoError(error.into()))?.len();
+        let mut magic = [0; 4];
+        file.read_exact(&mut magic).map_err(|error| GameError::IoError(error.into()))?;
+        let version = read_u32(file).map_err(|error| GameError::IoError(error.into()))?;
+        if version != PACK_VERSION {
+            return Err(GameError::NotSupportedError(format!("pack version {} is not supported", version).into()));
+        }
+        let count = read_u32(file).map_err(|error| GameError::IoError(error.into()))?;
+        let mut entries = HashMap::new();
+        let mut dirs = BTreeSet::new();
+        for _ in 0..count {
+            let name_len = read_u32(file).map_err(|error| GameError::IoError(error.into()))?;
+            if name_len as u64 > file_size {
+                return Err(GameError::IoError("pack entry name is out of bounds".into()));
+            }
+            let mut name = vec![0; name_len as usize];
+            file.read_exact(&mut name).map_err(|error| GameError::IoError(error.into()))?;
+            let name = String::from_utf8(name).map_err(|error| GameError::IoError(error.into()))?;
+            let name = normalize_path(name)?;
+            let location = read_u64(file).map_err(|error| GameError::IoError(error.into()))?;
+            let size = read_u64(file).map_err(|error| GameError::IoError(error.into()))?;
+            if location.checked_add(size).map(|end| end > file_size).unwrap_or(true) {
+                return Err(GameError::IoError(format!("pack entry `{}` is out of bounds", name).into()));
+            }
+            insert_parent_dirs(&mut dirs, &name);
+            entries.insert(name, ArchiveEntry { location, size });
+        }
+        Ok((entries, dirs))
+    }
+
+    fn is_dir(&self, path: &str) -> bool {
+        path.is_empty() || self.dirs.contains(path)
+    }
+
+    pub fn read(&self, path: &str) -> Option<GameResult<Vec<u8>>> {
+        let entry = *self.entries.get(path)?;
+        let mut bytes = Vec::with_capacity(entry.size.min(MAX_PREALLOCATION) as usize);
+        let result = match &self.reader {
+            ArchiveReader::Zip(zip) => {
+                let mut zip = zip.lock().unwrap_or_else(|error| error.into_inner());
+                zip.by_index(entry.location as usize)
+                    .map_err(|error| GameError::IoError(error.into()))
+                    .and_then(|file| file.take(entry.size).read_to_end(&mut bytes).map_err(|error| GameError::IoError(error.into())))
+            }
+            ArchiveReader::Pack(file) => {
+                let mut file = file.lock().unwrap_or_else(|error| error.into_inner());
+                file.seek(SeekFrom::Start(entry.location))
+                    .and_then(|_| (&mut *file).take(entry.size).read_to_end(&mut bytes))
+                    .map_err(|error| GameError::IoError(error.into()))
+            }
+        };
+        Some(result.map(|_| bytes))
+    }
+
+    pub fn metadata(&self, path: &str) -> Option<Metadata> {
+        if let Some(entry) = self.entries.get(path) {
+            Some(Metadata::file(entry.size, self.modified))
+        } else if self.is_dir(path) {
+            Some(Metadata::dir(self.modified))
+        } else {
+            None
+        }
+    }
+
+    pub fn read_dir(&self, path: &str) -> Option<Vec<String>> {
+        if !self.is_dir(path) {
+            return None;
+        }
+        let names = self.entries.keys()
+            .chain(self.dirs.iter())
+            .filter_map(|name| {
+                let name = if path.is_empty() {
+                    name.as_str()
+                } else {
+                    name.strip_prefix(path)?.strip_prefix('/')?
+                };
+                if name.is_empty() || name.contains('/') {
+                    None
+                } else {
+                    Some(name.to_owned())
+                }
+            })
+            .collect();
+        Some(names)
+    }
+}
+
+fn insert_dirs(dirs: &mut BTreeSet<String>, path: &str) {
+    let mut end = 0;
+    for name in path.split('/') {
+        if name.is_empty() {
+            continue;
+        }
+        end += if end == 0 { name.len() } else { name.len() + 1 };
+        dirs.insert(path[..end].to_owned());
+    }
+}
+
+fn insert_parent_dirs(dirs: &mut BTreeSet<String>, path: &str) {
+    if let Some(index) = path.rfind('/') {
+        insert_dirs(dirs, &path[..index]);
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PackWriter {
+    entries: Vec<(String, Vec<u8>)>,
+}
+
+impl PackWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, path: impl AsRef<Path>, bytes: impl Into<Vec<u8>>) -> GameResult {
+        let path = normalize_path(path)?;
+        let bytes = bytes.into();
+        match self.entries.iter_mut().find(|(name, _)| *name == path) {
+            Some(entry) => entry.1 = bytes,
+            None => self.entries.push((path, bytes)),
+        }
+        Ok(())
+    }
+
+    pub fn write(&self, mut writer: impl Write) -> GameResult {
+        let index_size = self.entries.iter()
+            .map(|(name, _)| 4 + name.len() as u64 + 8 + 8)
+            .sum::<u64>();
+        let mut location = 4 + 4 + 4 + index_size;
+        let mut header = Vec::with_capacity(location as usize);
+        header.extend_from_slice(PACK_MAGIC);
+        header.extend_from_slice(&PACK_VERSION.to_le_bytes());
+        header.extend_from_slice(&(self.entries.len() as u32).to_le_bytes());
+        for (name, bytes) in &self.entries {
+            header.extend_from_slice(&(name.len() as u32).to_le_bytes());
+            header.extend_from_slice(name.as_bytes());
+            header.extend_from_slice(&location.to_le_bytes());
+            header.extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+            location += bytes.len() as u64;
+        }
+        writer.write_all(&header).map_err(|error| GameError::IoError(error.into()))?;
+        for (_, bytes) in &self.entries {
+            writer.write_all(bytes).map_err(|error| GameError::IoError(error.into()))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,34 @@
+use std::time::SystemTime;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Metadata {
+    is_dir: bool,
+    size: u64,
+    modified: Option<SystemTime>,
+}
+
+impl Metadata {
+    pub(crate) fn file(size: u64, modified: Option<SystemTime>) -> Self {
+        Self { is_dir: false, size, modified }
+    }
+
+    pub(crate) fn dir(modified: Option<SystemTime>) -> Self {
+        Self { is_dir: true, size: 0, modified }
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.is_dir
+    }
+
+    pub fn is_file(&self) -> bool {
+        !self.is_dir
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    pub fn modified(&self) -> Option<SystemTime> {
+        self.modified
+    }
+}
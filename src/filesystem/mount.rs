@@ -0,0 +1,122 @@
+use super::{Metadata, normalize_path};
+use super::archive::Archive;
+use super::path::strip_mount_point;
+use crate::error::{GameError, GameResult};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilesystemRoot {
+    CurrentDir,
+    ExecutableDir,
+    SaveDir,
+    Path(PathBuf),
+    Archive(PathBuf),
+}
+
+impl FilesystemRoot {
+    fn resolve(&self, save_dir: Option<&Path>) -> GameResult<PathBuf> {
+        match self {
+            Self::CurrentDir => std::env::current_dir()
+                .map_err(|error| GameError::IoError(error.into())),
+            Self::ExecutableDir => {
+                let path = std::env::current_exe()
+                    .map_err(|error| GameError::IoError(error.into()))?;
+                path.parent()
+                    .map(|path| path.to_path_buf())
+                    .ok_or_else(|| GameError::IoError("executable has no parent directory".into()))
+            }
+            Self::SaveDir => save_dir
+                .map(|path| path.to_path_buf())
+                .ok_or_else(|| GameError::StateError("save directory is not configured".into())),
+            Self::Path(path) | Self::Archive(path) => Ok(path.clone()),
+        }
+    }
+}
+
+#[derive(Clone)]
+enum MountSource {
+    Directory(PathBuf),
+    Archive(Arc<Archive>),
+}
+
+#[derive(Clone)]
+pub(crate) struct Mount {
+    root: FilesystemRoot,
+    mount_point: String,
+    source: MountSource,
+}
+
+impl Mount {
+    pub fn new(root: FilesystemRoot, mount_point: &str, save_dir: Option<&Path>) -> GameResult<Self> {
+        let mount_point = normalize_path(mount_point)?;
+        let source = match root {
+            FilesystemRoot::Archive(_) => MountSource::Archive(Arc::new(Archive::open(root.resolve(save_dir)?)?)),
+            _ => MountSource::Directory(root.resolve(save_dir)?),
+        };
+        Ok(Self { root, mount_point, source })
+    }
+
+    pub fn root(&self) -> &FilesystemRoot {
+        &self.root
+    }
+
+    pub fn mount_point(&self) -> &str {
+        &self.mount_point
+    }
+
+    pub fn real_path(&self, path: &str) -> Option<PathBuf> {
+        let path = strip_mount_point(path, &self.mount_point)?;
+        match &self.source {
+            MountSource::Directory(dir) => {
+                let real_path = if path.is_empty() { dir.clone() } else { dir.join(path) };
+                if real_path.exists() {
+                    Some(real_path)
+                } else {
+                    None
+                }
+            }
+            MountSource::Archive(_) => None,
+        }
+    }
+
+    pub fn read(&self, path: &str) -> Option<GameResult<Vec<u8>>> {
+        if let MountSource::Archive(archive) = &self.source {
+            return archive.read(strip_mount_point(path, &self.mount_point)?);
+        }
+        let real_path = self.real_path(path)?;
+        if real_path.is_dir() {
+            return None;
+        }
+        Some(std::fs::read(real_path).map_err(|error| GameError::IoError(error.into())))
+    }
+
+    pub fn metadata(&self, path: &str) -> Option<Metadata> {
+        if let MountSource::Archive(archive) = &self.source {
+            return archive.metadata(strip_mount_point(path, &self.mount_point)?);
+        }
+        let real_path = self.real_path(path)?;
+        let metadata = std::fs::metadata(real_path).ok()?;
+        let modified = metadata.modified().ok();
+        if metadata.is_dir() {
+            Some(Metadata::dir(modified))
+        } else {
+            Some(Metadata::file(metadata.len(), modified))
+        }
+    }
+
+    pub fn read_dir(&self, path: &str) -> Option<Vec<String>> {
+        if let MountSource::Archive(archive) = &self.source {
+            return archive.read_dir(strip_mount_point(path, &self.mount_point)?);
+        }
+        let real_path = self.real_path(path)?;
+        let entries = std::fs::read_dir(real_path).ok()?;
+        let mut names = Vec::new();
+        for entry in entries.flatten() {
+            if let Some(name) = entry.file_name().to_str() {
+                names.push(name.to_owned());
+            }
+        }
+        Some(names)
+    }
+}
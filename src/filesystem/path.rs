@@ -0,0 +1,68 @@
+use crate::error::{GameError, GameResult};
+use std::path::{Path, Component};
+
+pub fn normalize_path(path: impl AsRef<Path>) -> GameResult<String> {
+    let path = path.as_ref();
+    let mut names = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir | Component::RootDir | Component::Prefix(_) => (),
+            Component::ParentDir => names.push(".."),
+            Component::Normal(name) => {
+                let name = name.to_str()
+                    .ok_or_else(|| GameError::IoError(format!("path `{}` is not valid unicode", path.display()).into()))?;
+                names.extend(name.split('\\'));
+            }
+        }
+    }
+    let mut components = Vec::new();
+    for name in names {
+        match name {
+            "" | "." => (),
+            ".." => {
+                if components.pop().is_none() {
+                    return Err(GameError::IoError(format!("path `{}` escapes the filesystem root", path.display()).into()));
+                }
+            }
+            name => components.push(name),
+        }
+    }
+    Ok(components.join("/"))
+}
+
+pub(crate) fn strip_mount_point<'a>(path: &'a str, mount_point: &str) -> Option<&'a str> {
+    if mount_point.is_empty() {
+        Some(path)
+    } else if path == mount_point {
+        Some("")
+    } else {
+        path.strip_prefix(mount_point)
+            .and_then(|path| path.strip_prefix('/'))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_path, strip_mount_point};
+
+    #[test]
+    fn test_normalize_path() {
+        assert_eq!(normalize_path("assets/dvd-logo.png").unwrap(), "assets/dvd-logo.png");
+        assert_eq!(normalize_path("./assets//fonts/../dvd-logo.png").unwrap(), "assets/dvd-logo.png");
+        assert_eq!(normalize_path("/assets/dvd-logo.png").unwrap(), "assets/dvd-logo.png");
+        assert_eq!(normalize_path("assets\\parallax-forest\\lights.png").unwrap(), "assets/parallax-forest/lights.png");
+        assert_eq!(normalize_path("").unwrap(), "");
+        assert!(normalize_path("../secret").is_err());
+        assert!(normalize_path("assets/../../secret").is_err());
+        assert!(normalize_path("assets\\..\\..\\secret").is_err());
+    }
+
+    #[test]
+    fn test_strip_mount_point() {
+        assert_eq!(strip_mount_point("assets/bunny.png", ""), Some("assets/bunny.png"));
+        assert_eq!(strip_mount_point("assets/bunny.png", "assets"), Some("bunny.png"));
+        assert_eq!(strip_mount_point("assets", "assets"), Some(""));
+        assert_eq!(strip_mount_point("assets2/bunny.png", "assets"), None);
+        assert_eq!(strip_mount_point("bunny.png", "assets"), None);
+    }
+}
@@ -3,6 +3,9 @@ mod axis;
 mod state;
 mod device;
 mod power;
+mod rumble;
+mod calibration;
+mod mapping;
 
 use state::GamepadState;
 
@@ -10,44 +13,125 @@ pub use button::GamepadButton;
 pub use axis::GamepadAxis;
 pub use device::{GamepadId, GamepadDevice};
 pub use power::PowerInfo;
+pub use rumble::{RumbleMotor, RumbleEnvelope, RumbleLayer, RumbleEffect, RumbleHandle};
+pub use calibration::{Stick, ResponseCurve, StickSettings, GamepadCalibration};
+pub use mapping::{MappingSource, MappingError, MappingReport, GamepadMapping};
 
 use crate::error::{GameError, GameResult};
 use crate::event::KeyAction;
-use gilrs::{Gilrs, GilrsBuilder, Event};
+use crate::math::Vector;
+use crate::filesystem::Filesystem;
+use gilrs::{Gilrs, GilrsBuilder, EventType};
 use std::rc::Rc;
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+const SDL_MAPPINGS_ENV: &str = "SDL_GAMECONTROLLERCONFIG";
+const SDL_MAPPINGS_DATABASE: &str = include_str!("gamepad/sdl/gamecontrollerdb.txt");
+
+#[cfg(target_os = "linux")]
+const SDL_PLATFORM_NAME: &str = "Linux";
+#[cfg(target_os = "macos")]
+const SDL_PLATFORM_NAME: &str = "Mac OS X";
+#[cfg(target_os = "windows")]
+const SDL_PLATFORM_NAME: &str = "Windows";
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+const SDL_PLATFORM_NAME: &str = "Unknown";
 
 pub struct Gamepad {
     gilrs: Rc<RefCell<Gilrs>>,
     connected_states: HashMap<GamepadId, Rc<RefCell<GamepadState>>>,
     disconnected_states: HashMap<GamepadId, Rc<RefCell<GamepadState>>>,
+    raw_ids: HashMap<GamepadId, gilrs::GamepadId>,
+    calibration: GamepadCalibration,
+    device_calibrations: HashMap<[u8; 16], GamepadCalibration>,
+    builder_config: GamepadConfig,
+    mappings: Vec<GamepadMapping>,
+    mapping_errors: Vec<MappingError>,
+    pending_events: Vec<(GamepadId, EventType)>,
 }
 
 impl Gamepad {
-    pub(crate) fn new(gamepad_config: GamepadConfig) -> GameResult<Self> {
-        let gilrs = GilrsBuilder::new()
-            .set_axis_to_btn(gamepad_config.axis_to_button_down_value, gamepad_config.axis_to_button_up_value)
-            .build()
-            .map_err(|error| GameError::InitError(error.into()))?;
+    pub(crate) fn new(gamepad_config: GamepadConfig, filesystem: &Filesystem) -> GameResult<Self> {
+        let mut mappings = Vec::new();
+        let mut mapping_errors = Vec::new();
+        for path in gamepad_config.mapping_files.iter() {
+            let source = filesystem.read_to_string(path)?;
+            let (file_mappings, errors) = GamepadMapping::parse_database(&source);
+            insert_mappings(&mut mappings, file_mappings);
+            mapping_errors.extend(errors.into_iter().map(|error| MappingError { path: Some(path.clone()), ..error }));
+        }
+        insert_mappings(&mut mappings, gamepad_config.mappings.clone());
+        let gilrs = build_gilrs(&gamepad_config, &mappings)?;
         Ok(Self {
             gilrs: Rc::new(RefCell::new(gilrs)),
             connected_states: HashMap::new(),
             disconnected_states: HashMap::new(),
+            raw_ids: HashMap::new(),
+            calibration: gamepad_config.calibration,
+            device_calibrations: gamepad_config.device_calibrations.clone(),
+            builder_config: gamepad_config,
+            mappings,
+            mapping_errors,
+            pending_events: Vec::new(),
         })
     }
 
-    pub(crate) fn pump_events(&self) -> Vec<Event> {
-        let mut events = Vec::new();
-        while let Some(event) = self.gilrs.borrow_mut().next_event() {
-            events.push(event);
+    pub(crate) fn pump_events(&mut self) -> Vec<(GamepadId, EventType)> {
+        let mut events = std::mem::take(&mut self.pending_events);
+        loop {
+            let event = self.gilrs.borrow_mut().next_event();
+            match event {
+                Some(event) => events.push((self.gamepad_id(event.id), event.event)),
+                None => break,
+            }
         }
         events
     }
 
+    fn gamepad_id(&mut self, raw_id: gilrs::GamepadId) -> GamepadId {
+        if let Some((id, _)) = self.raw_ids.iter().find(|(_, known_raw_id)| **known_raw_id == raw_id) {
+            return *id;
+        }
+        let mut id = usize::from(raw_id);
+        while self.raw_ids.contains_key(&GamepadId::from(id)) || self.connected_states.contains_key(&GamepadId::from(id)) {
+            id += 1;
+        }
+        let id = GamepadId::from(id);
+        self.raw_ids.insert(id, raw_id);
+        id
+    }
+
+    fn new_device(&self, id: GamepadId, state: &Rc<RefCell<GamepadState>>) -> GamepadDevice {
+        GamepadDevice::new(self.gilrs.clone(), id, self.raw_ids.get(&id).copied(), state.clone())
+    }
+
+    fn uuid(&self, id: GamepadId) -> [u8; 16] {
+        match self.raw_ids.get(&id) {
+            Some(raw_id) => self.gilrs.borrow().gamepad(*raw_id).uuid(),
+            None => [0; 16],
+        }
+    }
+
+    fn resolve_calibration(&self, id: GamepadId) -> GamepadCalibration {
+        self.device_calibrations.get(&self.uuid(id)).copied().unwrap_or(self.calibration)
+    }
+
+    fn refresh_calibrations(&self) {
+        for (id, state) in self.connected_states.iter().chain(self.disconnected_states.iter()) {
+            state.borrow_mut().set_calibration(self.resolve_calibration(*id));
+        }
+    }
+
     pub(crate) fn handle_connect_event(&mut self, id: GamepadId) {
         let state = self.disconnected_states.remove(&id).unwrap_or_else(|| Rc::new(RefCell::new(GamepadState::new())));
-        state.borrow_mut().reset(true);
+        {
+            let mut state = state.borrow_mut();
+            state.reset(true);
+            state.set_calibration(self.resolve_calibration(id));
+        }
         self.connected_states.insert(id, state);
     }
 
@@ -75,9 +159,18 @@ impl Gamepad {
         }
     }
 
+    pub(crate) fn reset_states(&mut self) {
+        for state in self.connected_states.values() {
+            state.borrow_mut().reset_inputs();
+        }
+    }
+
     pub(crate) fn clear_states(&mut self) {
+        let now = Instant::now();
         for (_, state) in &self.connected_states {
-            state.borrow_mut().clear_states();
+            let mut state = state.borrow_mut();
+            state.clear_states();
+            state.clean_rumbles(now);
         }
     }
 
@@ -87,23 +180,25 @@ impl Gamepad {
             state = self.disconnected_states.get(&id);
         }
         let state = state.expect("can not find gamepad state");
-        GamepadDevice::new(self.gilrs.clone(), id, state.clone())
+        self.new_device(id, state)
     }
 
     pub fn connected_device(&self, id: GamepadId) -> Option<GamepadDevice> {
-        self.connected_states.get(&id).map(|state| {
-            GamepadDevice::new(self.gilrs.clone(), id, state.clone())
-        })
+        self.connected_states.get(&id).map(|state| self.new_device(id, state))
     }
 
     pub fn connected_devices(&self) -> Vec<GamepadDevice> {
         let mut devices = Vec::with_capacity(self.connected_states.len());
         for (id, state) in &self.connected_states {
-            devices.push(GamepadDevice::new(self.gilrs.clone(), *id, state.clone()))
+            devices.push(self.new_device(*id, state))
         }
         devices
     }
 
+    pub(crate) fn connected_ids(&self) -> impl Iterator<Item = GamepadId> + '_ {
+        self.connected_states.keys().copied()
+    }
+
     pub fn is_connected(&self, id: GamepadId) -> bool {
         self.connected_states.contains_key(&id)
     }
@@ -137,12 +232,198 @@ impl Gamepad {
             .map(|state| state.borrow().axis_value(axis))
             .unwrap_or(0.0)
     }
+
+    pub fn raw_axis_value(&self, id: GamepadId, axis: GamepadAxis) -> f32 {
+        self.connected_states.get(&id)
+            .map(|state| state.borrow().raw_axis_value(axis))
+            .unwrap_or(0.0)
+    }
+
+    pub fn stick_vector(&self, id: GamepadId, stick: Stick) -> Vector {
+        self.connected_states.get(&id)
+            .map(|state| state.borrow().stick_vector(stick))
+            .unwrap_or_else(Vector::zero)
+    }
+
+    pub fn calibration(&self) -> &GamepadCalibration {
+        &self.calibration
+    }
+
+    pub fn set_calibration(&mut self, calibration: GamepadCalibration) {
+        self.calibration = calibration;
+        self.refresh_calibrations();
+    }
+
+    pub fn device_calibration(&self, uuid: [u8; 16]) -> Option<&GamepadCalibration> {
+        self.device_calibrations.get(&uuid)
+    }
+
+    pub fn set_device_calibration(&mut self, uuid: [u8; 16], calibration: GamepadCalibration) {
+        self.device_calibrations.insert(uuid, calibration);
+        self.refresh_calibrations();
+    }
+
+    pub fn remove_device_calibration(&mut self, uuid: [u8; 16]) -> Option<GamepadCalibration> {
+        let calibration = self.device_calibrations.remove(&uuid);
+        self.refresh_calibrations();
+        calibration
+    }
+
+    pub fn mappings(&self) -> &[GamepadMapping] {
+        &self.mappings
+    }
+
+    pub fn mapping(&self, uuid: [u8; 16]) -> Option<&GamepadMapping> {
+        self.mappings.iter()
+            .rev()
+            .filter(|mapping| mapping.uuid() == uuid)
+            .find(|mapping| mapping.platform().map(|platform| platform == SDL_PLATFORM_NAME).unwrap_or(true))
+    }
+
+    /// Adding or removing mappings recreates the gilrs instance: active rumbles are stopped, connected
+    /// gamepads keep their `GamepadId` and gamepads that appear or vanish get connect / disconnect events.
+    pub fn add_mapping(&mut self, mapping: GamepadMapping) -> GameResult {
+        insert_mappings(&mut self.mappings, vec![mapping]);
+        self.rebuild()
+    }
+
+    pub fn add_mappings(&mut self, source: &str) -> GameResult<MappingReport> {
+        let (mappings, errors) = GamepadMapping::parse_database(source);
+        let added = mappings.len();
+        if added > 0 {
+            insert_mappings(&mut self.mappings, mappings);
+            self.rebuild()?;
+        }
+        Ok(MappingReport { added, errors })
+    }
+
+    pub fn load_mappings(&mut self, filesystem: &Filesystem, path: impl AsRef<Path>) -> GameResult<MappingReport> {
+        let path = path.as_ref();
+        let source = filesystem.read_to_string(path)?;
+        let mut report = self.add_mappings(&source)?;
+        for error in report.errors.iter_mut() {
+            error.path = Some(path.to_path_buf());
+        }
+        Ok(report)
+    }
+
+    pub fn mapping_errors(&self) -> &[MappingError] {
+        &self.mapping_errors
+    }
+
+    pub fn remove_mapping(&mut self, uuid: [u8; 16]) -> GameResult<bool> {
+        let count = self.mappings.len();
+        self.mappings.retain(|mapping| mapping.uuid() != uuid);
+        if self.mappings.len() == count {
+            return Ok(false);
+        }
+        self.rebuild()?;
+        Ok(true)
+    }
+
+    fn rebuild(&mut self) -> GameResult {
+        let old_devices = {
+            let gilrs = self.gilrs.borrow();
+            let mut old_devices = Vec::new();
+            for (id, raw_id) in self.raw_ids.iter() {
+                if let Some(gamepad) = gilrs.connected_gamepad(*raw_id) {
+                    old_devices.push((*id, gamepad.uuid(), gamepad.os_name().to_owned()));
+                }
+            }
+            old_devices.sort_by_key(|(id, _, _)| *id);
+            old_devices
+        };
+        let gilrs = build_gilrs(&self.builder_config, &self.mappings)?;
+        let new_devices = gilrs.gamepads()
+            .map(|(raw_id, gamepad)| (raw_id, gamepad.uuid(), gamepad.os_name().to_owned()))
+            .collect::<Vec<_>>();
+        *self.gilrs.borrow_mut() = gilrs;
+        for state in self.connected_states.values().chain(self.disconnected_states.values()) {
+            state.borrow_mut().stop_rumbles();
+        }
+        let matched = match_devices(&old_devices, &new_devices);
+        self.raw_ids = matched.iter().copied().collect();
+        for (id, _, _) in old_devices.iter() {
+            if !self.raw_ids.contains_key(id) && self.connected_states.contains_key(id) {
+                self.pending_events.push((*id, EventType::Disconnected));
+            }
+        }
+        for (raw_id, _, _) in new_devices.iter() {
+            if !matched.iter().any(|(_, matched_raw_id)| matched_raw_id == raw_id) {
+                let id = self.gamepad_id(*raw_id);
+                self.pending_events.push((id, EventType::Connected));
+            }
+        }
+        self.refresh_calibrations();
+        Ok(())
+    }
+}
+
+fn match_devices<R: Copy + PartialEq>(old_devices: &[(GamepadId, [u8; 16], String)], new_devices: &[(R, [u8; 16], String)]) -> Vec<(GamepadId, R)> {
+    let mut matched: Vec<(GamepadId, R)> = Vec::new();
+    for (id, uuid, name) in old_devices {
+        let new_device = new_devices.iter().find(|(raw_id, new_uuid, new_name)| {
+            new_uuid == uuid && new_name == name && !matched.iter().any(|(_, matched_raw_id)| matched_raw_id == raw_id)
+        });
+        if let Some((raw_id, _, _)) = new_device {
+            matched.push((*id, *raw_id));
+        }
+    }
+    matched
+}
+
+fn insert_mappings(mappings: &mut Vec<GamepadMapping>, new_mappings: Vec<GamepadMapping>) {
+    for mapping in new_mappings {
+        mappings.retain(|old| old.uuid() != mapping.uuid() || old.platform() != mapping.platform());
+        mappings.push(mapping);
+    }
+}
+
+fn build_gilrs(gamepad_config: &GamepadConfig, mappings: &[GamepadMapping]) -> GameResult<Gilrs> {
+    let builder = GilrsBuilder::new()
+        .set_axis_to_btn(gamepad_config.axis_to_button_down_value, gamepad_config.axis_to_button_up_value);
+    if mappings.is_empty() {
+        return builder
+            .add_included_mappings(gamepad_config.included_mappings)
+            .add_env_mappings(gamepad_config.env_mappings)
+            .build()
+            .map_err(|error| GameError::InitError(error.into()));
+    }
+    // gilrs applies its bundled database after `add_mappings`, so with custom mappings the database
+    // is loaded from the vendored copy instead, in front of the environment and the custom mappings.
+    let mut source = String::new();
+    if gamepad_config.included_mappings {
+        source.push_str(SDL_MAPPINGS_DATABASE);
+        source.push('\n');
+    }
+    if gamepad_config.env_mappings {
+        if let Ok(env_mappings) = std::env::var(SDL_MAPPINGS_ENV) {
+            source.push_str(&env_mappings);
+            source.push('\n');
+        }
+    }
+    for mapping in mappings {
+        source.push_str(&mapping.to_sdl_string());
+        source.push('\n');
+    }
+    builder
+        .add_included_mappings(false)
+        .add_env_mappings(false)
+        .add_mappings(&source)
+        .build()
+        .map_err(|error| GameError::InitError(error.into()))
 }
 
 #[derive(Debug, Clone)]
 pub struct GamepadConfig {
     axis_to_button_down_value: f32,
     axis_to_button_up_value: f32,
+    calibration: GamepadCalibration,
+    device_calibrations: HashMap<[u8; 16], GamepadCalibration>,
+    included_mappings: bool,
+    env_mappings: bool,
+    mapping_files: Vec<PathBuf>,
+    mappings: Vec<GamepadMapping>,
 }
 
 impl GamepadConfig {
@@ -150,6 +431,12 @@ impl GamepadConfig {
         Self {
             axis_to_button_down_value: 0.75,
             axis_to_button_up_value: 0.65,
+            calibration: GamepadCalibration::new(),
+            device_calibrations: HashMap::new(),
+            included_mappings: true,
+            env_mappings: true,
+            mapping_files: Vec::new(),
+            mappings: Vec::new(),
         }
     }
 
@@ -162,4 +449,34 @@ impl GamepadConfig {
         self.axis_to_button_up_value = value;
         self
     }
+
+    pub fn calibration(mut self, calibration: GamepadCalibration) -> Self {
+        self.calibration = calibration;
+        self
+    }
+
+    pub fn device_calibration(mut self, uuid: [u8; 16], calibration: GamepadCalibration) -> Self {
+        self.device_calibrations.insert(uuid, calibration);
+        self
+    }
+
+    pub fn included_mappings(mut self, included_mappings: bool) -> Self {
+        self.included_mappings = included_mappings;
+        self
+    }
+
+    pub fn env_mappings(mut self, env_mappings: bool) -> Self {
+        self.env_mappings = env_mappings;
+        self
+    }
+
+    pub fn mappings_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.mapping_files.push(path.into());
+        self
+    }
+
+    pub fn mapping(mut self, mapping: GamepadMapping) -> Self {
+        self.mappings.push(mapping);
+        self
+    }
 }
@@ -1,6 +1,7 @@
 use gilrs::Axis;
+use serde::{Serialize, Deserialize};
 
-#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub enum GamepadAxis {
     LeftStickX,
     LeftStickY,
@@ -13,6 +14,29 @@ pub enum GamepadAxis {
     Other(u32),
 }
 
+impl GamepadAxis {
+    pub(crate) const VARIANTS: &'static [Self] = &[
+        Self::LeftStickX,
+        Self::LeftStickY,
+        Self::LeftZ,
+        Self::RightStickX,
+        Self::RightStickY,
+        Self::RightZ,
+        Self::DPadX,
+        Self::DPadY,
+    ];
+
+    pub(crate) fn sdl_name(&self) -> Option<&'static str> {
+        match self {
+            Self::LeftStickX => Some("leftx"),
+            Self::LeftStickY => Some("lefty"),
+            Self::RightStickX => Some("rightx"),
+            Self::RightStickY => Some("righty"),
+            _ => None,
+        }
+    }
+}
+
 impl From<Axis> for GamepadAxis {
     fn from(axis: Axis) -> Self {
         match axis {
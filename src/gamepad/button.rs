@@ -1,6 +1,7 @@
 use gilrs::Button;
+use serde::{Serialize, Deserialize};
 
-#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub enum GamepadButton {
     LeftStick,
     RightStick,
@@ -23,6 +24,26 @@ pub enum GamepadButton {
 }
 
 impl GamepadButton {
+    pub(crate) const VARIANTS: &'static [Self] = &[
+        Self::LeftStick,
+        Self::RightStick,
+        Self::DPadUp,
+        Self::DPadDown,
+        Self::DPadLeft,
+        Self::DPadRight,
+        Self::North,
+        Self::South,
+        Self::West,
+        Self::East,
+        Self::LeftTrigger,
+        Self::LeftTrigger2,
+        Self::RightTrigger,
+        Self::RightTrigger2,
+        Self::Start,
+        Self::Select,
+        Self::Home,
+    ];
+
     pub fn is_stick(&self) -> bool {
         match self {
             Self::LeftStick | Self::RightStick => true,
@@ -57,6 +78,29 @@ impl GamepadButton {
             _ => false,
         }
     }
+
+    pub(crate) fn sdl_name(&self) -> Option<&'static str> {
+        match self {
+            Self::South => Some("a"),
+            Self::East => Some("b"),
+            Self::West => Some("x"),
+            Self::North => Some("y"),
+            Self::Select => Some("back"),
+            Self::Home => Some("guide"),
+            Self::Start => Some("start"),
+            Self::LeftStick => Some("leftstick"),
+            Self::RightStick => Some("rightstick"),
+            Self::LeftTrigger => Some("leftshoulder"),
+            Self::RightTrigger => Some("rightshoulder"),
+            Self::LeftTrigger2 => Some("lefttrigger"),
+            Self::RightTrigger2 => Some("righttrigger"),
+            Self::DPadUp => Some("dpup"),
+            Self::DPadDown => Some("dpdown"),
+            Self::DPadLeft => Some("dpleft"),
+            Self::DPadRight => Some("dpright"),
+            Self::Other(_) => None,
+        }
+    }
 }
 
 impl From<Button> for GamepadButton {
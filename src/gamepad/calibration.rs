@@ -0,0 +1,259 @@
+use super::GamepadAxis;
+use crate::math::Vector;
+
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum Stick {
+    Left,
+    Right,
+}
+
+impl Stick {
+    pub fn axes(&self) -> (GamepadAxis, GamepadAxis) {
+        match self {
+            Self::Left => (GamepadAxis::LeftStickX, GamepadAxis::LeftStickY),
+            Self::Right => (GamepadAxis::RightStickX, GamepadAxis::RightStickY),
+        }
+    }
+
+    pub(crate) fn from_axis(axis: GamepadAxis) -> Option<Self> {
+        match axis {
+            GamepadAxis::LeftStickX | GamepadAxis::LeftStickY => Some(Self::Left),
+            GamepadAxis::RightStickX | GamepadAxis::RightStickY => Some(Self::Right),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone)]
+pub enum ResponseCurve {
+    #[default]
+    Linear,
+    Quadratic,
+    Cubic,
+    Power(f32),
+    Custom(fn(f32) -> f32),
+}
+
+impl ResponseCurve {
+    pub fn apply(&self, value: f32) -> f32 {
+        let value = value.clamp(0.0, 1.0);
+        let value = match self {
+            Self::Linear => value,
+            Self::Quadratic => value * value,
+            Self::Cubic => value * value * value,
+            Self::Power(exponent) => value.powf(exponent.max(0.0)),
+            Self::Custom(curve) => curve(value),
+        };
+        value.clamp(0.0, 1.0)
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct StickSettings {
+    pub axial_dead_zone: f32,
+    pub radial_dead_zone: f32,
+    pub outer_dead_zone: f32,
+    pub response_curve: ResponseCurve,
+}
+
+impl StickSettings {
+    pub fn new() -> Self {
+        Self {
+            axial_dead_zone: 0.0,
+            radial_dead_zone: 0.0,
+            outer_dead_zone: 0.0,
+            response_curve: ResponseCurve::Linear,
+        }
+    }
+
+    pub fn axial_dead_zone(mut self, dead_zone: f32) -> Self {
+        self.axial_dead_zone = dead_zone;
+        self
+    }
+
+    pub fn radial_dead_zone(mut self, dead_zone: f32) -> Self {
+        self.radial_dead_zone = dead_zone;
+        self
+    }
+
+    pub fn outer_dead_zone(mut self, dead_zone: f32) -> Self {
+        self.outer_dead_zone = dead_zone;
+        self
+    }
+
+    pub fn response_curve(mut self, response_curve: ResponseCurve) -> Self {
+        self.response_curve = response_curve;
+        self
+    }
+
+    fn is_identity(&self) -> bool {
+        self.axial_dead_zone <= 0.0
+            && self.radial_dead_zone <= 0.0
+            && self.outer_dead_zone <= 0.0
+            && matches!(self.response_curve, ResponseCurve::Linear)
+    }
+
+    fn rescale(&self, magnitude: f32, inner: f32) -> f32 {
+        let inner = inner.clamp(0.0, 1.0);
+        let outer = (1.0 - self.outer_dead_zone).clamp(inner, 1.0);
+        if magnitude <= inner {
+            0.0
+        } else if magnitude >= outer {
+            1.0
+        } else {
+            (magnitude - inner) / (outer - inner)
+        }
+    }
+
+    pub fn apply(&self, raw: Vector) -> Vector {
+        if self.is_identity() {
+            return raw;
+        }
+        let axial = |value: f32| {
+            let dead_zone = self.axial_dead_zone.clamp(0.0, 1.0);
+            if value.abs() <= dead_zone {
+                0.0
+            } else {
+                value.signum() * (value.abs() - dead_zone) / (1.0 - dead_zone)
+            }
+        };
+        let x = axial(raw.x);
+        let y = axial(raw.y);
+        let magnitude = (x * x + y * y).sqrt();
+        if magnitude <= f32::EPSILON {
+            return Vector::zero();
+        }
+        let scaled = self.response_curve.apply(self.rescale(magnitude, self.radial_dead_zone));
+        Vector::new(x / magnitude * scaled, y / magnitude * scaled)
+    }
+
+    pub fn apply_axis(&self, raw: f32) -> f32 {
+        if self.is_identity() {
+            return raw;
+        }
+        let inner = self.axial_dead_zone.max(self.radial_dead_zone);
+        raw.signum() * self.response_curve.apply(self.rescale(raw.abs(), inner))
+    }
+}
+
+impl Default for StickSettings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct GamepadCalibration {
+    pub left_stick: StickSettings,
+    pub right_stick: StickSettings,
+    pub triggers: StickSettings,
+}
+
+impl GamepadCalibration {
+    pub fn new() -> Self {
+        Self {
+            left_stick: StickSettings::new(),
+            right_stick: StickSettings::new(),
+            triggers: StickSettings::new(),
+        }
+    }
+
+    pub fn left_stick(mut self, settings: StickSettings) -> Self {
+        self.left_stick = settings;
+        self
+    }
+
+    pub fn right_stick(mut self, settings: StickSettings) -> Self {
+        self.right_stick = settings;
+        self
+    }
+
+    pub fn sticks(mut self, settings: StickSettings) -> Self {
+        self.left_stick = settings;
+        self.right_stick = settings;
+        self
+    }
+
+    pub fn triggers(mut self, settings: StickSettings) -> Self {
+        self.triggers = settings;
+        self
+    }
+
+    pub fn stick(&self, stick: Stick) -> &StickSettings {
+        match stick {
+            Stick::Left => &self.left_stick,
+            Stick::Right => &self.right_stick,
+        }
+    }
+}
+
+impl Default for GamepadCalibration {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Stick, StickSettings, ResponseCurve, GamepadCalibration};
+    use crate::gamepad::{Gamepad, GamepadConfig, GamepadAxis, GamepadId};
+    use crate::filesystem::{Filesystem, FilesystemConfig};
+    use crate::math::Vector;
+
+    fn assert_near(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-4, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_stick_settings() {
+        let identity = StickSettings::new();
+        let value = identity.apply(Vector::new(0.3, -0.4));
+        assert_near(value.x, 0.3);
+        assert_near(value.y, -0.4);
+        assert_eq!(identity.apply(Vector::new(1.0, 1.0)), Vector::new(1.0, 1.0));
+        assert_eq!(identity.apply_axis(-1.0), -1.0);
+        assert_near(StickSettings::new().radial_dead_zone(0.1).apply(Vector::new(1.0, 1.0)).x, std::f32::consts::FRAC_1_SQRT_2);
+
+        let radial = StickSettings::new().radial_dead_zone(0.2).outer_dead_zone(0.1);
+        assert_eq!(radial.apply(Vector::new(0.1, 0.1)), Vector::zero());
+        assert_near(radial.apply(Vector::new(0.55, 0.0)).x, 0.5);
+        assert_near(radial.apply(Vector::new(0.0, -0.95)).y, -1.0);
+
+        let axial = StickSettings::new().axial_dead_zone(0.2);
+        let value = axial.apply(Vector::new(0.6, 0.1));
+        assert_near(value.x, 0.5);
+        assert_eq!(value.y, 0.0);
+
+        let quadratic = StickSettings::new().response_curve(ResponseCurve::Quadratic);
+        assert_near(quadratic.apply(Vector::new(0.0, 0.5)).y, 0.25);
+        let custom = StickSettings::new().response_curve(ResponseCurve::Custom(|value| if value > 0.5 { 1.0 } else { 0.0 }));
+        assert_near(custom.apply_axis(-0.6), -1.0);
+        assert_eq!(custom.apply_axis(0.4), 0.0);
+        assert_near(StickSettings::new().response_curve(ResponseCurve::Power(3.0)).apply_axis(0.5), 0.125);
+        assert_near(radial.apply_axis(0.55), 0.5);
+    }
+
+    #[test]
+    fn test_device_calibration() {
+        let calibration = GamepadCalibration::new().sticks(StickSettings::new().radial_dead_zone(0.5));
+        let mut gamepad = Gamepad::new(GamepadConfig::new().calibration(calibration), &Filesystem::new(FilesystemConfig::new().roots(Vec::new())).unwrap()).unwrap();
+        let id = GamepadId::from(3);
+        gamepad.handle_connect_event(id);
+        gamepad.handle_axis_change_event(id, GamepadAxis::LeftStickX, 0.4);
+        gamepad.handle_axis_change_event(id, GamepadAxis::LeftStickY, -0.3);
+        assert_eq!(gamepad.stick_vector(id, Stick::Left), Vector::zero());
+        assert_eq!(gamepad.raw_axis_value(id, GamepadAxis::LeftStickX), 0.4);
+        let uuid = gamepad.device(id).uuid();
+        gamepad.set_device_calibration(uuid, GamepadCalibration::new());
+        let vector = gamepad.stick_vector(id, Stick::Left);
+        assert_near(vector.x, 0.4);
+        assert_near(gamepad.axis_value(id, GamepadAxis::LeftStickY), -0.3);
+        gamepad.handle_axis_change_event(id, GamepadAxis::LeftStickX, 1.0);
+        gamepad.handle_axis_change_event(id, GamepadAxis::LeftStickY, 1.0);
+        assert_eq!(gamepad.axis_value(id, GamepadAxis::LeftStickX), 1.0);
+        gamepad.handle_axis_change_event(id, GamepadAxis::LeftStickX, 0.4);
+        gamepad.handle_axis_change_event(id, GamepadAxis::LeftStickY, -0.3);
+        gamepad.remove_device_calibration(uuid);
+        assert_eq!(gamepad.device(id).axis_value(GamepadAxis::LeftStickX), 0.0);
+    }
+}
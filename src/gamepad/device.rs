@@ -1,21 +1,47 @@
-use super::{GamepadButton, GamepadAxis, GamepadState, PowerInfo};
+use super::{GamepadButton, GamepadAxis, GamepadState, PowerInfo, RumbleEffect, RumbleHandle, GamepadCalibration, Stick, MappingSource};
+use crate::error::{GameError, GameResult};
+use crate::math::Vector;
 use gilrs::Gilrs;
 use std::rc::Rc;
 use std::cell::RefCell;
+use std::time::{Duration, Instant};
 
-pub type GamepadId = gilrs::GamepadId;
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub struct GamepadId(usize);
+
+impl From<usize> for GamepadId {
+    fn from(id: usize) -> Self {
+        Self(id)
+    }
+}
+
+impl From<GamepadId> for usize {
+    fn from(id: GamepadId) -> Self {
+        id.0
+    }
+}
+
+impl From<gilrs::GamepadId> for GamepadId {
+    fn from(id: gilrs::GamepadId) -> Self {
+        Self(id.into())
+    }
+}
 
 pub struct GamepadDevice {
     gilrs: Rc<RefCell<Gilrs>>,
     id: GamepadId,
+    raw_id: Option<gilrs::GamepadId>,
     name: String,
     state: Rc<RefCell<GamepadState>>,
 }
 
 impl GamepadDevice {
-    pub(crate) fn new(gilrs: Rc<RefCell<Gilrs>>, id: GamepadId, state: Rc<RefCell<GamepadState>>) -> Self {
-        let name = gilrs.borrow().gamepad(id).name().to_owned();
-        Self { gilrs, id, name, state }
+    pub(crate) fn new(gilrs: Rc<RefCell<Gilrs>>, id: GamepadId, raw_id: Option<gilrs::GamepadId>, state: Rc<RefCell<GamepadState>>) -> Self {
+        let name = match raw_id {
+            Some(raw_id) => gilrs.borrow().gamepad(raw_id).name().to_owned(),
+            None => "Virtual Gamepad".to_owned(),
+        };
+        Self { gilrs, id, raw_id, name, state }
     }
 
     pub fn id(&self) -> GamepadId {
@@ -26,16 +52,68 @@ impl GamepadDevice {
         &self.name
     }
 
+    pub fn is_virtual(&self) -> bool {
+        self.raw_id.is_none()
+    }
+
     pub fn uuid(&self) -> [u8; 16] {
-        self.gilrs.borrow().gamepad(self.id).uuid()
+        match self.raw_id {
+            Some(raw_id) => self.gilrs.borrow().gamepad(raw_id).uuid(),
+            None => [0; 16],
+        }
+    }
+
+    pub fn mapping_source(&self) -> MappingSource {
+        match self.raw_id {
+            Some(raw_id) => self.gilrs.borrow().gamepad(raw_id).mapping_source().into(),
+            None => MappingSource::None,
+        }
+    }
+
+    pub fn mapping_name(&self) -> Option<String> {
+        self.raw_id.and_then(|raw_id| self.gilrs.borrow().gamepad(raw_id).map_name().map(str::to_owned))
     }
 
     pub fn is_force_feedback_supported(&self) -> bool {
-        self.gilrs.borrow().gamepad(self.id).is_ff_supported()
+        match self.raw_id {
+            Some(raw_id) => self.gilrs.borrow().gamepad(raw_id).is_ff_supported(),
+            None => false,
+        }
+    }
+
+    pub fn rumble(&self, strong: f32, weak: f32, duration: Duration) -> GameResult<RumbleHandle> {
+        self.play_rumble(&RumbleEffect::rumble(strong, weak, duration))
+    }
+
+    pub fn play_rumble(&self, effect: &RumbleEffect) -> GameResult<RumbleHandle> {
+        let raw_id = self.raw_id.ok_or_else(|| GameError::NotSupportedError("virtual gamepad does not support force feedback".into()))?;
+        if !self.is_connected() {
+            return Err(GameError::StateError("gamepad is not connected".into()));
+        }
+        let mut gilrs = self.gilrs.borrow_mut();
+        let ff_effect = effect.to_builder()
+            .gamepads(&[raw_id])
+            .finish(&mut gilrs)
+            .map_err(|error| match error {
+                gilrs::ff::Error::FfNotSupported(_) => GameError::NotSupportedError(error.into()),
+                gilrs::ff::Error::Disconnected(_) => GameError::StateError(error.into()),
+                _ => GameError::RuntimeError(error.into()),
+            })?;
+        ff_effect.play().map_err(|error| GameError::RuntimeError(error.into()))?;
+        let until = effect.duration.map(|duration| Instant::now() + duration);
+        let key = self.state.borrow_mut().add_rumble(ff_effect, until);
+        Ok(RumbleHandle::new(key, Rc::downgrade(&self.state)))
+    }
+
+    pub fn stop_rumble(&self) {
+        self.state.borrow_mut().stop_rumbles();
     }
 
     pub fn power_info(&self) -> PowerInfo {
-        self.gilrs.borrow().gamepad(self.id).power_info().into()
+        match self.raw_id {
+            Some(raw_id) => self.gilrs.borrow().gamepad(raw_id).power_info().into(),
+            None => PowerInfo::Unknown,
+        }
     }
 
     pub fn is_connected(&self) -> bool {
@@ -61,6 +139,18 @@ impl GamepadDevice {
     pub fn axis_value(&self, axis: GamepadAxis) -> f32 {
         self.state.borrow().axis_value(axis)
     }
+
+    pub fn raw_axis_value(&self, axis: GamepadAxis) -> f32 {
+        self.state.borrow().raw_axis_value(axis)
+    }
+
+    pub fn stick_vector(&self, stick: Stick) -> Vector {
+        self.state.borrow().stick_vector(stick)
+    }
+
+    pub fn calibration(&self) -> GamepadCalibration {
+        *self.state.borrow().calibration()
+    }
 }
 
 impl PartialEq for GamepadDevice {
@@ -0,0 +1,241 @@
+use super::{GamepadButton, GamepadAxis};
+use crate::error::{GameError, GameResult};
+use std::path::PathBuf;
+
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum MappingSource {
+    Database,
+    Driver,
+    None,
+}
+
+impl From<gilrs::MappingSource> for MappingSource {
+    fn from(source: gilrs::MappingSource) -> Self {
+        match source {
+            gilrs::MappingSource::SdlMappings => Self::Database,
+            gilrs::MappingSource::Driver => Self::Driver,
+            gilrs::MappingSource::None => Self::None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct MappingError {
+    pub path: Option<PathBuf>,
+    pub line: usize,
+    pub error: GameError,
+}
+
+#[derive(Debug, Default)]
+pub struct MappingReport {
+    pub added: usize,
+    pub errors: Vec<MappingError>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct GamepadMapping {
+    uuid: [u8; 16],
+    name: String,
+    elements: Vec<(String, String)>,
+}
+
+impl GamepadMapping {
+    pub fn new(uuid: [u8; 16], name: impl Into<String>) -> Self {
+        Self {
+            uuid,
+            name: name.into().replace(',', " "),
+            elements: Vec::new(),
+        }
+    }
+
+    pub fn parse(source: &str) -> GameResult<Self> {
+        let mut fields = source.trim().split(',');
+        let uuid = fields.next()
+            .and_then(parse_uuid)
+            .ok_or_else(|| GameError::RuntimeError(format!("invalid gamepad mapping uuid: {}", source).into()))?;
+        let name = fields.next()
+            .ok_or_else(|| GameError::RuntimeError(format!("gamepad mapping has no name: {}", source).into()))?;
+        let mut mapping = Self::new(uuid, name);
+        for field in fields.map(str::trim).filter(|field| !field.is_empty()) {
+            let (key, value) = field.split_once(':')
+                .ok_or_else(|| GameError::RuntimeError(format!("invalid gamepad mapping element `{}`", field).into()))?;
+            mapping = mapping.element(key, value);
+        }
+        Ok(mapping)
+    }
+
+    pub fn parse_database(source: &str) -> (Vec<Self>, Vec<MappingError>) {
+        let mut mappings = Vec::new();
+        let mut errors = Vec::new();
+        for (index, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match Self::parse(line) {
+                Ok(mapping) => mappings.push(mapping),
+                Err(error) => errors.push(MappingError { path: None, line: index + 1, error }),
+            }
+        }
+        (mappings, errors)
+    }
+
+    pub fn uuid(&self) -> [u8; 16] {
+        self.uuid
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn platform(&self) -> Option<&str> {
+        self.element_value("platform")
+    }
+
+    pub fn element(mut self, key: &str, value: &str) -> Self {
+        let key = key.trim();
+        let value = value.trim();
+        match self.elements.iter_mut().find(|(element, _)| element == key) {
+            Some((_, element_value)) => *element_value = value.to_owned(),
+            None => self.elements.push((key.to_owned(), value.to_owned())),
+        }
+        self
+    }
+
+    pub fn button(self, button: GamepadButton, element: &str) -> Self {
+        match button.sdl_name() {
+            Some(key) => self.element(key, element),
+            None => self,
+        }
+    }
+
+    pub fn axis(self, axis: GamepadAxis, element: &str) -> Self {
+        match axis.sdl_name() {
+            Some(key) => self.element(key, element),
+            None => self,
+        }
+    }
+
+    pub fn with_platform(self, platform: &str) -> Self {
+        self.element("platform", platform)
+    }
+
+    pub fn button_element(&self, button: GamepadButton) -> Option<&str> {
+        button.sdl_name().and_then(|key| self.element_value(key))
+    }
+
+    pub fn axis_element(&self, axis: GamepadAxis) -> Option<&str> {
+        axis.sdl_name().and_then(|key| self.element_value(key))
+    }
+
+    fn element_value(&self, key: &str) -> Option<&str> {
+        self.elements.iter()
+            .find(|(element, _)| element == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn to_sdl_string(&self) -> String {
+        let mut output = String::new();
+        for byte in self.uuid.iter() {
+            output.push_str(&format!("{:02x}", byte));
+        }
+        output.push(',');
+        output.push_str(&self.name);
+        output.push(',');
+        for (key, value) in self.elements.iter() {
+            output.push_str(key);
+            output.push(':');
+            output.push_str(value);
+            output.push(',');
+        }
+        output
+    }
+}
+
+fn parse_uuid(source: &str) -> Option<[u8; 16]> {
+    let source = source.trim();
+    if source.len() != 32 || !source.is_ascii() {
+        return None;
+    }
+    let mut uuid = [0; 16];
+    for (index, byte) in uuid.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&source[index * 2..index * 2 + 2], 16).ok()?;
+    }
+    Some(uuid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GamepadMapping, MappingSource, MappingError};
+    use crate::gamepad::{Gamepad, GamepadConfig, GamepadButton, GamepadAxis, GamepadId, match_devices};
+    use crate::filesystem::{Filesystem, FilesystemConfig, FilesystemRoot};
+
+    #[test]
+    fn test_parse() {
+        let line = "030000005e0400008e02000014010000,Off Brand Pad,a:b1,b:b0,x:b3,y:b2,leftx:a0,lefty:a1,platform:Linux,";
+        let mapping = GamepadMapping::parse(line).unwrap();
+        assert_eq!(mapping.uuid()[..4], [0x03, 0x00, 0x00, 0x00]);
+        assert_eq!(mapping.name(), "Off Brand Pad");
+        assert_eq!(mapping.platform(), Some("Linux"));
+        assert_eq!(mapping.button_element(GamepadButton::South), Some("b1"));
+        assert_eq!(mapping.axis_element(GamepadAxis::LeftStickY), Some("a1"));
+        assert_eq!(mapping.to_sdl_string(), line);
+        let mapping = mapping.button(GamepadButton::South, "b0").button(GamepadButton::Other(3), "b9").axis(GamepadAxis::DPadX, "a5");
+        assert_eq!(mapping.button_element(GamepadButton::South), Some("b0"));
+        assert_eq!(GamepadMapping::parse(&mapping.to_sdl_string()).unwrap(), mapping);
+        assert!(GamepadMapping::parse("xinput,XInput Controller,a:b0,").is_err());
+        assert!(GamepadMapping::parse("030000005e0400008e02000014010000,Pad,a").is_err());
+        let database = format!("# Game Controller DB\n\n{}\nxinput,XInput,a:b0,\n{}", line, GamepadMapping::new([1; 16], "Other, Pad").to_sdl_string());
+        let (mappings, errors) = GamepadMapping::parse_database(&database);
+        assert_eq!(mappings.len(), 2);
+        assert_eq!(mappings[1].name(), "Other  Pad");
+        assert!(matches!(errors.as_slice(), [MappingError { path: None, line: 4, .. }]));
+    }
+
+    #[test]
+    fn test_gamepad_mappings() {
+        let dir = std::env::temp_dir().join(format!("tge-mappings-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("gamecontrollerdb.txt"), "01010101010101010101010101010101,First Pad,a:b0,platform:Linux,\nbroken\n").unwrap();
+        let filesystem = Filesystem::new(FilesystemConfig::new().roots(vec![FilesystemRoot::Path(dir.clone())])).unwrap();
+        let config = GamepadConfig::new()
+            .included_mappings(false)
+            .mappings_file("gamecontrollerdb.txt")
+            .mapping(GamepadMapping::new([2; 16], "Second Pad").button(GamepadButton::South, "b1"));
+        let gamepad = Gamepad::new(config, &filesystem);
+        std::fs::remove_dir_all(&dir).unwrap();
+        let mut gamepad = gamepad.unwrap();
+        assert_eq!(gamepad.mappings().len(), 2);
+        assert_eq!(gamepad.mapping([1; 16]).map(|mapping| mapping.name()), Some("First Pad"));
+        match gamepad.mapping_errors() {
+            [MappingError { path: Some(path), line: 2, .. }] => assert_eq!(path, std::path::Path::new("gamecontrollerdb.txt")),
+            errors => panic!("unexpected mapping errors {:?}", errors),
+        }
+        let id = GamepadId::from(9);
+        gamepad.handle_connect_event(id);
+        let report = gamepad.add_mappings("02020202020202020202020202020202,Second Pad Fixed,a:b0,\n# comment\nxinput,XInput,a:b0,\n").unwrap();
+        assert_eq!(report.added, 1);
+        assert!(matches!(report.errors.as_slice(), [MappingError { path: None, line: 3, .. }]));
+        assert_eq!(gamepad.mappings().len(), 2);
+        assert_eq!(gamepad.mapping([2; 16]).and_then(|mapping| mapping.button_element(GamepadButton::South)), Some("b0"));
+        assert!(gamepad.remove_mapping([1; 16]).unwrap());
+        assert!(!gamepad.remove_mapping([1; 16]).unwrap());
+        assert!(gamepad.mapping([1; 16]).is_none());
+        assert!(std::env::var("SDL_GAMECONTROLLERCONFIG").map(|value| !value.contains("Second Pad")).unwrap_or(true));
+        assert!(gamepad.is_connected(id));
+        assert!(gamepad.pump_events().iter().all(|(event_id, _)| *event_id != id));
+        assert_eq!(gamepad.device(id).mapping_source(), MappingSource::None);
+        assert_eq!(gamepad.device(id).mapping_name(), None);
+    }
+
+    #[test]
+    fn test_match_devices() {
+        let pad = |id: usize, uuid: u8, name: &str| (GamepadId::from(id), [uuid; 16], name.to_owned());
+        let old_devices = vec![pad(0, 1, "Pad"), pad(1, 1, "Pad"), pad(4, 2, "Stick")];
+        let new_devices = vec![(7, [2; 16], "Stick".to_owned()), (8, [1; 16], "Pad".to_owned()), (9, [1; 16], "Pad".to_owned()), (10, [3; 16], "Wheel".to_owned())];
+        let matched = match_devices(&old_devices, &new_devices);
+        assert_eq!(matched, vec![(GamepadId::from(0), 8), (GamepadId::from(1), 9), (GamepadId::from(4), 7)]);
+        let matched = match_devices(&old_devices, &new_devices[..2]);
+        assert_eq!(matched, vec![(GamepadId::from(0), 8), (GamepadId::from(4), 7)]);
+    }
+}
@@ -0,0 +1,274 @@
+use super::GamepadState;
+use gilrs::ff::{BaseEffect, BaseEffectType, EffectBuilder, Envelope, Replay, Repeat, Ticks};
+use std::rc::Weak;
+use std::cell::RefCell;
+use std::time::{Duration, Instant};
+
+const TICK_MILLIS: u32 = 50;
+
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum RumbleMotor {
+    Strong,
+    Weak,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RumbleEnvelope {
+    pub attack: Duration,
+    pub attack_level: f32,
+    pub fade: Duration,
+    pub fade_level: f32,
+}
+
+impl RumbleEnvelope {
+    pub fn new() -> Self {
+        Self {
+            attack: Duration::ZERO,
+            attack_level: 1.0,
+            fade: Duration::ZERO,
+            fade_level: 1.0,
+        }
+    }
+
+    pub fn attack(mut self, attack: Duration, level: f32) -> Self {
+        self.attack = attack;
+        self.attack_level = level;
+        self
+    }
+
+    pub fn fade(mut self, fade: Duration, level: f32) -> Self {
+        self.fade = fade;
+        self.fade_level = level;
+        self
+    }
+}
+
+impl Default for RumbleEnvelope {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RumbleLayer {
+    pub motor: RumbleMotor,
+    pub magnitude: f32,
+    pub delay: Duration,
+    pub duration: Duration,
+    pub interval: Duration,
+    pub envelope: RumbleEnvelope,
+}
+
+impl RumbleLayer {
+    pub fn new(motor: RumbleMotor, magnitude: f32, duration: Duration) -> Self {
+        Self {
+            motor,
+            magnitude,
+            delay: Duration::ZERO,
+            duration,
+            interval: Duration::ZERO,
+            envelope: RumbleEnvelope::new(),
+        }
+    }
+
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn envelope(mut self, envelope: RumbleEnvelope) -> Self {
+        self.envelope = envelope;
+        self
+    }
+
+    fn to_base_effect(self) -> BaseEffect {
+        let magnitude = (self.magnitude.clamp(0.0, 1.0) * u16::MAX as f32).round() as u16;
+        let kind = match self.motor {
+            RumbleMotor::Strong => BaseEffectType::Strong { magnitude },
+            RumbleMotor::Weak => BaseEffectType::Weak { magnitude },
+        };
+        let play_ticks = duration_to_ticks(self.duration).max(1);
+        let fade_ticks = duration_to_ticks(self.envelope.fade).min(play_ticks - 1);
+        let attack_ticks = duration_to_ticks(self.envelope.attack).min(play_ticks - 1 - fade_ticks);
+        BaseEffect {
+            kind,
+            scheduling: Replay {
+                after: ticks(duration_to_ticks(self.delay)),
+                play_for: ticks(play_ticks),
+                with_delay: ticks(duration_to_ticks(self.interval)),
+            },
+            envelope: Envelope {
+                attack_length: ticks(attack_ticks),
+                attack_level: self.envelope.attack_level.clamp(0.0, 1.0),
+                fade_length: ticks(fade_ticks),
+                fade_level: self.envelope.fade_level.clamp(0.0, 1.0),
+            },
+        }
+    }
+}
+
+fn duration_to_ticks(duration: Duration) -> u32 {
+    let millis = duration.as_millis().min(u32::MAX as u128) as u32;
+    millis.div_ceil(TICK_MILLIS)
+}
+
+fn ticks(count: u32) -> Ticks {
+    Ticks::from_ms(count.saturating_mul(TICK_MILLIS))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RumbleEffect {
+    pub layers: Vec<RumbleLayer>,
+    pub duration: Option<Duration>,
+    pub gain: f32,
+}
+
+impl RumbleEffect {
+    pub fn new() -> Self {
+        Self {
+            layers: Vec::new(),
+            duration: None,
+            gain: 1.0,
+        }
+    }
+
+    pub fn rumble(strong: f32, weak: f32, duration: Duration) -> Self {
+        Self::new()
+            .layer(RumbleLayer::new(RumbleMotor::Strong, strong, duration))
+            .layer(RumbleLayer::new(RumbleMotor::Weak, weak, duration))
+            .duration(duration)
+    }
+
+    pub fn layer(mut self, layer: RumbleLayer) -> Self {
+        self.layers.push(layer);
+        self
+    }
+
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    pub fn repeat_forever(mut self) -> Self {
+        self.duration = None;
+        self
+    }
+
+    pub fn gain(mut self, gain: f32) -> Self {
+        self.gain = gain;
+        self
+    }
+
+    pub(crate) fn to_builder(&self) -> EffectBuilder {
+        let mut builder = EffectBuilder::new();
+        for layer in self.layers.iter() {
+            builder.add_effect(layer.to_base_effect());
+        }
+        builder.repeat(match self.duration {
+            Some(duration) => Repeat::For(ticks(duration_to_ticks(duration).max(1))),
+            None => Repeat::Infinitely,
+        });
+        builder.gain(self.gain.max(0.0));
+        builder
+    }
+}
+
+impl Default for RumbleEffect {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub(crate) struct ActiveRumble {
+    pub key: u64,
+    pub effect: gilrs::ff::Effect,
+    pub until: Option<Instant>,
+}
+
+impl Drop for ActiveRumble {
+    fn drop(&mut self) {
+        let _ = self.effect.stop();
+    }
+}
+
+pub struct RumbleHandle {
+    key: u64,
+    state: Weak<RefCell<GamepadState>>,
+}
+
+impl RumbleHandle {
+    pub(crate) fn new(key: u64, state: Weak<RefCell<GamepadState>>) -> Self {
+        Self { key, state }
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.state.upgrade()
+            .map(|state| state.borrow().is_rumble_playing(self.key))
+            .unwrap_or(false)
+    }
+
+    pub fn stop(&self) {
+        if let Some(state) = self.state.upgrade() {
+            state.borrow_mut().stop_rumble(self.key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RumbleEffect, RumbleEnvelope, RumbleLayer, RumbleMotor, RumbleHandle};
+    use crate::gamepad::GamepadState;
+    use gilrs::ff::BaseEffectType;
+    use std::rc::Rc;
+    use std::cell::RefCell;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_base_effect() {
+        let layer = RumbleLayer::new(RumbleMotor::Strong, 2.0, Duration::from_millis(120))
+            .delay(Duration::from_millis(10))
+            .interval(Duration::from_millis(100))
+            .envelope(RumbleEnvelope::new().attack(Duration::from_millis(100), 0.0).fade(Duration::from_millis(500), 0.5));
+        let effect = layer.to_base_effect();
+        assert_eq!(effect.kind, BaseEffectType::Strong { magnitude: u16::MAX });
+        assert_eq!(effect.scheduling.after, gilrs::ff::Ticks::from_ms(50));
+        assert_eq!(effect.scheduling.play_for, gilrs::ff::Ticks::from_ms(150));
+        assert_eq!(effect.scheduling.with_delay, gilrs::ff::Ticks::from_ms(100));
+        assert_eq!(effect.envelope.fade_length, gilrs::ff::Ticks::from_ms(100));
+        assert_eq!(effect.envelope.attack_length, gilrs::ff::Ticks::from_ms(0));
+        let effect = RumbleEffect::rumble(1.0, 0.5, Duration::from_millis(200));
+        assert_eq!(effect.layers.len(), 2);
+        assert_eq!(effect.layers[1].motor, RumbleMotor::Weak);
+        assert_eq!(effect.duration, Some(Duration::from_millis(200)));
+        assert_eq!(effect.repeat_forever().duration, None);
+    }
+
+    #[test]
+    fn test_handle() {
+        let mut gilrs = gilrs::Gilrs::new().unwrap();
+        let state = Rc::new(RefCell::new(GamepadState::new()));
+        let now = Instant::now();
+        let effect = RumbleEffect::rumble(0.5, 0.5, Duration::from_millis(100));
+        let key = state.borrow_mut().add_rumble(effect.to_builder().finish(&mut gilrs).unwrap(), Some(now + Duration::from_millis(100)));
+        let short = RumbleHandle::new(key, Rc::downgrade(&state));
+        let key = state.borrow_mut().add_rumble(RumbleEffect::new().to_builder().finish(&mut gilrs).unwrap(), None);
+        let forever = RumbleHandle::new(key, Rc::downgrade(&state));
+        assert!(short.is_playing() && forever.is_playing());
+        state.borrow_mut().clean_rumbles(now + Duration::from_millis(200));
+        assert!(!short.is_playing() && forever.is_playing());
+        let key = state.borrow_mut().add_rumble(RumbleEffect::new().to_builder().finish(&mut gilrs).unwrap(), None);
+        let other = RumbleHandle::new(key, Rc::downgrade(&state));
+        forever.stop();
+        assert!(!forever.is_playing() && other.is_playing());
+        state.borrow_mut().reset(false);
+        assert!(!other.is_playing());
+        drop(state);
+        assert!(!other.is_playing());
+        other.stop();
+    }
+}
@@ -1,12 +1,18 @@
-use super::{GamepadButton, GamepadAxis};
+use super::{GamepadButton, GamepadAxis, GamepadCalibration, Stick};
+use super::rumble::ActiveRumble;
 use crate::event::{KeyState, KeyAction};
+use crate::math::Vector;
 use std::collections::HashMap;
+use std::time::Instant;
 
 pub struct GamepadState {
     connected: bool,
     button_states: HashMap<GamepadButton, KeyState>,
     button_values: HashMap<GamepadButton, f32>,
     axis_values: HashMap<GamepadAxis, f32>,
+    calibration: GamepadCalibration,
+    rumbles: Vec<ActiveRumble>,
+    next_rumble_key: u64,
 }
 
 impl GamepadState {
@@ -16,6 +22,9 @@ impl GamepadState {
             button_states: HashMap::new(),
             button_values: HashMap::new(),
             axis_values: HashMap::new(),
+            calibration: GamepadCalibration::new(),
+            rumbles: Vec::new(),
+            next_rumble_key: 0,
         }
     }
 
@@ -41,11 +50,41 @@ impl GamepadState {
         });
     }
 
+    pub fn reset_inputs(&mut self) {
+        self.button_states.clear();
+        self.button_values.clear();
+        self.axis_values.clear();
+    }
+
     pub fn reset(&mut self, connected: bool) {
         self.connected = connected;
         self.button_states.clear();
         self.button_values.clear();
         self.axis_values.clear();
+        self.rumbles.clear();
+    }
+
+    pub fn add_rumble(&mut self, effect: gilrs::ff::Effect, until: Option<Instant>) -> u64 {
+        let key = self.next_rumble_key;
+        self.next_rumble_key += 1;
+        self.rumbles.push(ActiveRumble { key, effect, until });
+        key
+    }
+
+    pub fn is_rumble_playing(&self, key: u64) -> bool {
+        self.rumbles.iter().any(|rumble| rumble.key == key)
+    }
+
+    pub fn stop_rumble(&mut self, key: u64) {
+        self.rumbles.retain(|rumble| rumble.key != key);
+    }
+
+    pub fn stop_rumbles(&mut self) {
+        self.rumbles.clear();
+    }
+
+    pub fn clean_rumbles(&mut self, now: Instant) {
+        self.rumbles.retain(|rumble| rumble.until.map(|until| until > now).unwrap_or(true));
     }
 
     pub fn is_connected(&self) -> bool {
@@ -77,7 +116,34 @@ impl GamepadState {
         *self.button_values.get(&button).unwrap_or(&0.0)
     }
 
-    pub fn axis_value(&self, axis: GamepadAxis) -> f32 {
+    pub fn calibration(&self) -> &GamepadCalibration {
+        &self.calibration
+    }
+
+    pub fn set_calibration(&mut self, calibration: GamepadCalibration) {
+        self.calibration = calibration;
+    }
+
+    pub fn raw_axis_value(&self, axis: GamepadAxis) -> f32 {
         *self.axis_values.get(&axis).unwrap_or(&0.0)
     }
+
+    pub fn axis_value(&self, axis: GamepadAxis) -> f32 {
+        match Stick::from_axis(axis) {
+            Some(stick) => {
+                let vector = self.stick_vector(stick);
+                if axis == stick.axes().0 { vector.x } else { vector.y }
+            }
+            None => match axis {
+                GamepadAxis::LeftZ | GamepadAxis::RightZ => self.calibration.triggers.apply_axis(self.raw_axis_value(axis)),
+                _ => self.raw_axis_value(axis),
+            },
+        }
+    }
+
+    pub fn stick_vector(&self, stick: Stick) -> Vector {
+        let (x_axis, y_axis) = stick.axes();
+        let raw = Vector::new(self.raw_axis_value(x_axis), self.raw_axis_value(y_axis));
+        self.calibration.stick(stick).apply(raw)
+    }
 }
@@ -11,9 +11,12 @@ mod canvas;
 mod font;
 mod texture_ref;
 mod params;
+mod hot_reload;
 
 use opengl::BufferUsage;
 use renderer::{Renderer, RendererBuilder};
+use hot_reload::HotReload;
+pub(crate) use hot_reload::{WatchedAsset, WatchPaths, watch_paths};
 
 pub use opengl::{PrimitiveType, FilterMode, Filter, WrapMode, Wrap};
 pub use program::Program;
@@ -29,12 +32,14 @@ pub use params::{MeshDrawParams, SpriteDrawParams, TextLayoutGravity, TextDrawPa
 
 use crate::error::{GameError, GameResult};
 use crate::math::{Position, Size, Region, Viewport, Transform};
-use winit::window::Window;
+use crate::filesystem::Filesystem;
+use crate::event::Event;
+use crate::window::WindowContext;
 use winit::dpi::{LogicalPosition, LogicalSize, PhysicalSize};
-use glutin::{ContextWrapper, PossiblyCurrent};
 use glow::{Context, HasContext};
 use glam::{Vec4, Mat4};
 use std::rc::Rc;
+use std::time::Duration;
 
 const SPRITE_VERTEX_COUNT: usize = 4;
 const SPRITE_ELEMENT_COUNT: usize = 6;
@@ -50,7 +55,7 @@ struct DrawCommand {
 }
 
 pub struct Graphics {
-    context_wrapper: Rc<ContextWrapper<PossiblyCurrent, Window>>,
+    context: Rc<WindowContext>,
     gl: Rc<Context>,
     size: Size,
     viewport: Viewport,
@@ -68,12 +73,13 @@ pub struct Graphics {
     vertices: Vec<Vertex>,
     elements: Vec<u16>,
     draw_command: DrawCommand,
+    hot_reload: HotReload,
 }
 
 impl Graphics {
-    pub(crate) fn new(graphics_config: GraphicsConfig, context_wrapper: Rc<ContextWrapper<PossiblyCurrent, Window>>, gl: Rc<Context>) -> GameResult<Self> {
-        let physical_size = context_wrapper.window().inner_size();
-        let scale_factor = context_wrapper.window().scale_factor();
+    pub(crate) fn new(graphics_config: GraphicsConfig, context: Rc<WindowContext>, gl: Rc<Context>) -> GameResult<Self> {
+        let physical_size = context.inner_size();
+        let scale_factor = context.scale_factor();
         let logical_size = physical_size.to_logical(scale_factor);
         let size = Size::new(logical_size.width, logical_size.height);
         let viewport = Viewport::new(0.0, 0.0, logical_size.width, logical_size.height);
@@ -113,7 +119,7 @@ impl Graphics {
         }
 
         Ok(Self {
-            context_wrapper,
+            context,
             gl,
             size,
             viewport,
@@ -131,11 +137,12 @@ impl Graphics {
             vertices,
             elements,
             draw_command,
+            hot_reload: HotReload::new(graphics_config.hot_reload, graphics_config.hot_reload_interval),
         })
     }
 
     pub(crate) fn resize(&mut self, physical_size: PhysicalSize<u32>, scale_factor: f64) {
-        self.context_wrapper.resize(physical_size);
+        self.context.resize(physical_size);
         if self.canvas.is_none() {
             let logical_size = physical_size.to_logical(scale_factor);
             self.size = Size::new(logical_size.width, logical_size.height);
@@ -162,7 +169,7 @@ impl Graphics {
 
     pub(crate) fn present(&mut self) -> GameResult {
         self.flush();
-        self.context_wrapper.swap_buffers()
+        self.context.swap_buffers()
             .map_err(|error| GameError::RuntimeError(error.into()))
     }
 
@@ -174,8 +181,45 @@ impl Graphics {
         }
     }
 
-    fn window(&self) -> &Window {
-        self.context_wrapper.window()
+    pub fn is_hot_reload(&self) -> bool {
+        self.hot_reload.is_enabled()
+    }
+
+    pub fn set_hot_reload(&mut self, hot_reload: bool) {
+        self.hot_reload.set_enabled(hot_reload);
+    }
+
+    pub fn hot_reload_interval(&self) -> Duration {
+        self.hot_reload.interval()
+    }
+
+    pub fn set_hot_reload_interval(&mut self, interval: Duration) {
+        self.hot_reload.set_interval(interval);
+    }
+
+    pub(crate) fn watch_asset(&mut self, asset: WatchedAsset, paths: WatchPaths) {
+        self.hot_reload.watch(asset, paths);
+    }
+
+    pub(crate) fn poll_hot_reload(&mut self, filesystem: &Filesystem) -> Vec<Event> {
+        let outcomes = self.hot_reload.poll(filesystem, self.gl.clone());
+        let mut events = Vec::with_capacity(outcomes.len());
+        for outcome in outcomes {
+            match outcome.result {
+                Ok(program) => {
+                    if program.map(|program| Rc::ptr_eq(&program, &self.program)).unwrap_or(false) {
+                        self.program.bind();
+                        self.program.set_uniform_matrix_4("u_projection", &self.projection_matrix.to_cols_array());
+                    }
+                    events.push(Event::AssetReload(outcome.path));
+                }
+                Err(error) => events.push(Event::AssetReloadError {
+                    path: outcome.path,
+                    message: error.to_string(),
+                }),
+            }
+        }
+        events
     }
 
     pub(crate) fn gl(&self) -> Rc<Context> {
@@ -207,7 +251,7 @@ impl Graphics {
                 }
                 self.projection_matrix = Mat4::orthographic_rh_gl(0.0, self.viewport.width, 0.0, self.viewport.height, -1.0, 1.0);
             } else {
-                let scale_factor = self.window().scale_factor();
+                let scale_factor = self.context.scale_factor();
                 let physical_viewport = {
                     let physical_position = LogicalPosition::new(self.viewport.x, self.viewport.y).to_physical::<i32>(scale_factor);
                     let physical_size = LogicalSize::new(self.viewport.width, self.viewport.height).to_physical::<i32>(scale_factor);
@@ -300,8 +344,8 @@ impl Graphics {
                 }
                 self.projection_matrix = Mat4::orthographic_rh_gl(0.0, self.size.width, 0.0, self.size.height, -1.0, 1.0);
             } else {
-                let physical_size = self.window().inner_size();
-                let scale_factor = self.window().scale_factor();
+                let physical_size = self.context.inner_size();
+                let scale_factor = self.context.scale_factor();
                 let logical_size = physical_size.to_logical(scale_factor);
                 self.size = Size::new(logical_size.width, logical_size.height);
                 self.viewport = Viewport::new(0.0, 0.0, logical_size.width, logical_size.height);
@@ -451,7 +495,7 @@ impl Graphics {
             if self.canvas.is_some() {
                 1.0
             } else {
-                self.window().scale_factor() as f32
+                self.context.scale_factor() as f32
             }
         });
 
@@ -582,6 +626,8 @@ pub struct GraphicsConfig {
     default_wrap: Wrap,
     renderer_vertex_size: usize,
     renderer_element_size: usize,
+    hot_reload: bool,
+    hot_reload_interval: Duration,
 }
 
 impl GraphicsConfig {
@@ -591,6 +637,8 @@ impl GraphicsConfig {
             default_wrap: Wrap::default(),
             renderer_vertex_size: SPRITE_VERTEX_COUNT * 2048,
             renderer_element_size: SPRITE_ELEMENT_COUNT * 2048,
+            hot_reload: false,
+            hot_reload_interval: Duration::from_millis(500),
         }
     }
 
@@ -619,4 +667,14 @@ impl GraphicsConfig {
         self.renderer_element_size = SPRITE_ELEMENT_COUNT * size;
         self
     }
+
+    pub fn hot_reload(mut self, hot_reload: bool) -> Self {
+        self.hot_reload = hot_reload;
+        self
+    }
+
+    pub fn hot_reload_interval(mut self, interval: Duration) -> Self {
+        self.hot_reload_interval = interval;
+        self
+    }
 }
@@ -40,7 +40,7 @@ impl Canvas {
         self.texture.filter()
     }
 
-    pub fn set_filter(&mut self, filter: Filter) {
+    pub fn set_filter(&self, filter: Filter) {
         self.texture.set_filter(filter)
     }
 
@@ -48,7 +48,7 @@ impl Canvas {
         self.texture.wrap()
     }
 
-    pub fn set_wrap(&mut self, wrap: Wrap) {
+    pub fn set_wrap(&self, wrap: Wrap) {
         self.texture.set_wrap(wrap)
     }
 
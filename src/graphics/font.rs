@@ -1,13 +1,21 @@
-use super::{Graphics, opengl, Filter, Texture};
+use super::{Graphics, opengl, Filter, Texture, WatchedAsset, watch_paths};
 use crate::error::{GameError, GameResult};
 use crate::math::{Size, Region};
 use crate::engine::Engine;
+use crate::loader::LoadHandle;
 use fontdue::{FontSettings, Metrics};
 use std::path::Path;
 use std::collections::HashMap;
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
 use std::cell::RefCell;
 
+const DEFAULT_CACHE_SIZE: u32 = 1024;
+
+fn parse_font(bytes: &[u8]) -> GameResult<fontdue::Font> {
+    fontdue::Font::from_bytes(bytes, FontSettings::default())
+        .map_err(|error| GameError::InitError(error.into()))
+}
+
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct GlyphDrawInfo {
     pub uv_bounds: Region,
@@ -66,15 +74,18 @@ struct Cache {
 }
 
 pub struct Font {
-    font: fontdue::Font,
-    cache: RefCell<Cache>,
+    font: Rc<RefCell<fontdue::Font>>,
+    cache: Rc<RefCell<Cache>>,
     hidpi_scale_factor: Option<f32>,
 }
 
 impl Font {
     pub(crate) fn new(graphics: &mut Graphics, bytes: &[u8], cache_texture_size: u32) -> GameResult<Self> {
-        let font = fontdue::Font::from_bytes(bytes, FontSettings::default())
-            .map_err(|error| GameError::InitError(error.into()))?;
+        let font = parse_font(bytes)?;
+        Self::from_font(graphics, font, cache_texture_size)
+    }
+
+    fn from_font(graphics: &mut Graphics, font: fontdue::Font, cache_texture_size: u32) -> GameResult<Self> {
         let cache_texture = Texture::for_font_cache(graphics, cache_texture_size)?;
         let cache = Cache {
             texture: cache_texture,
@@ -83,23 +94,47 @@ impl Font {
             rows: Vec::new(),
         };
         Ok(Self {
-            font,
-            cache: RefCell::new(cache),
+            font: Rc::new(RefCell::new(font)),
+            cache: Rc::new(RefCell::new(cache)),
             hidpi_scale_factor: None,
         })
     }
 
     pub fn from_bytes(graphics: &mut Graphics, bytes: &[u8]) -> GameResult<Self> {
-        Self::new(graphics, bytes, 1024)
+        Self::new(graphics, bytes, DEFAULT_CACHE_SIZE)
     }
 
     pub fn load(engine: &mut Engine, path: impl AsRef<Path>) -> GameResult<Self> {
+        let path = path.as_ref();
         let bytes = engine.filesystem().read(path)?;
-        Self::from_bytes(engine.graphics(), &bytes)
+        let font = Self::from_bytes(engine.graphics(), &bytes)?;
+        engine.watch_asset(WatchedAsset::Font(font.watch_handle()), &[path]);
+        Ok(font)
+    }
+
+    pub fn load_async(engine: &mut Engine, path: impl AsRef<Path>) -> LoadHandle<Self> {
+        let path = path.as_ref().to_path_buf();
+        let filesystem = engine.filesystem().clone();
+        engine.loader().spawn(move || {
+            let paths = watch_paths(&filesystem, &[&path]);
+            let bytes = filesystem.read(&path)?;
+            Ok((parse_font(&bytes)?, paths))
+        }, |graphics, (font, paths)| {
+            let font = Self::from_font(graphics, font, DEFAULT_CACHE_SIZE)?;
+            graphics.watch_asset(WatchedAsset::Font(font.watch_handle()), paths);
+            Ok(font)
+        })
+    }
+
+    pub(crate) fn watch_handle(&self) -> FontWatchHandle {
+        FontWatchHandle {
+            font: Rc::downgrade(&self.font),
+            cache: Rc::downgrade(&self.cache),
+        }
     }
 
     pub(crate) fn line_metrics(&self, px: f32) -> LineMetrics {
-        let horizontal_line_metrics = self.font.horizontal_line_metrics(px)
+        let horizontal_line_metrics = self.font.borrow().horizontal_line_metrics(px)
             .expect("no horizontal line metrics");
         LineMetrics {
             ascent: horizontal_line_metrics.ascent,
@@ -110,7 +145,7 @@ impl Font {
     }
 
     pub(crate) fn glyph_metrics(&self, c: char, px: f32) -> GlyphMetrics {
-        let metrics = self.font.metrics(c, px);
+        let metrics = self.font.borrow().metrics(c, px);
         GlyphMetrics {
             advance_width: metrics.advance_width,
         }
@@ -132,7 +167,7 @@ impl Font {
             let draw_info = GlyphDrawInfo::new(*metrics, hidpi_scale_factor, *uv);
             return Ok(CachedBy::Existed(draw_info));
         }
-        let metrics = self.font.metrics(c, px);
+        let metrics = self.font.borrow().metrics(c, px);
         let glyph_size = Size::new(metrics.width as u32, metrics.height as u32);
         let glyph_cache_size = Size::new(glyph_size.width + 1, glyph_size.height + 1);
         let cache_texture_size = cache.texture_size;
@@ -156,7 +191,7 @@ impl Font {
             }
         }
         if let Some(region) = region {
-            let (_, bitmap) = self.font.rasterize(c, px);
+            let (_, bitmap) = self.font.borrow().rasterize(c, px);
             let mut pixels = Vec::with_capacity(bitmap.len() * 4);
             for alpha in bitmap {
                 pixels.push(255);
@@ -204,8 +239,8 @@ impl Font {
         self.cache.borrow().texture.filter()
     }
 
-    pub fn set_filter(&mut self, filter: Filter) {
-        self.cache.borrow_mut().texture.set_filter(filter)
+    pub fn set_filter(&self, filter: Filter) {
+        self.cache.borrow().texture.set_filter(filter)
     }
 
     pub fn hidpi_scale_factor(&self) -> Option<f32> {
@@ -216,3 +251,25 @@ impl Font {
         self.hidpi_scale_factor = hidpi_scale_factor;
     }
 }
+
+pub(crate) struct FontWatchHandle {
+    font: Weak<RefCell<fontdue::Font>>,
+    cache: Weak<RefCell<Cache>>,
+}
+
+impl FontWatchHandle {
+    pub fn is_alive(&self) -> bool {
+        self.font.strong_count() > 0
+    }
+
+    pub fn reload(&self, bytes: &[u8]) -> GameResult {
+        if let (Some(font), Some(cache)) = (self.font.upgrade(), self.cache.upgrade()) {
+            let new_font = parse_font(bytes)?;
+            *font.borrow_mut() = new_font;
+            let mut cache = cache.borrow_mut();
+            cache.draw_infos.clear();
+            cache.rows.clear();
+        }
+        Ok(())
+    }
+}
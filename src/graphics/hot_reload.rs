@@ -0,0 +1,204 @@
+use super::{opengl, Image};
+use super::texture::TextureWatchHandle;
+use super::font::FontWatchHandle;
+use super::program::ProgramWatchHandle;
+use crate::error::GameResult;
+use crate::filesystem::Filesystem;
+use glow::Context;
+use std::rc::Rc;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+pub(crate) type WatchPaths = Vec<(PathBuf, Option<SystemTime>)>;
+
+pub(crate) fn watch_paths(filesystem: &Filesystem, paths: &[&Path]) -> WatchPaths {
+    paths.iter()
+        .map(|path| {
+            let modified = filesystem.metadata(path).ok().and_then(|metadata| metadata.modified());
+            (path.to_path_buf(), modified)
+        })
+        .collect()
+}
+
+pub(crate) enum WatchedAsset {
+    Texture(TextureWatchHandle),
+    Font(FontWatchHandle),
+    Program(ProgramWatchHandle),
+}
+
+impl WatchedAsset {
+    fn is_alive(&self) -> bool {
+        match self {
+            Self::Texture(handle) => handle.is_alive(),
+            Self::Font(handle) => handle.is_alive(),
+            Self::Program(handle) => handle.is_alive(),
+        }
+    }
+
+    fn reload(&self, paths: &[(PathBuf, Option<SystemTime>)], filesystem: &Filesystem, gl: Rc<Context>) -> GameResult<Option<Rc<opengl::Program>>> {
+        match self {
+            Self::Texture(handle) => {
+                let bytes = filesystem.read(&paths[0].0)?;
+                handle.reload(&Image::from_bytes(&bytes)?);
+                Ok(None)
+            }
+            Self::Font(handle) => {
+                let bytes = filesystem.read(&paths[0].0)?;
+                handle.reload(&bytes)?;
+                Ok(None)
+            }
+            Self::Program(handle) => {
+                let vertex_shader_source = filesystem.read_to_string(&paths[0].0)?;
+                let fragment_shader_source = filesystem.read_to_string(&paths[1].0)?;
+                handle.reload(gl, &vertex_shader_source, &fragment_shader_source)
+            }
+        }
+    }
+}
+
+struct WatchEntry {
+    asset: WatchedAsset,
+    paths: WatchPaths,
+}
+
+pub(crate) struct ReloadOutcome {
+    pub path: PathBuf,
+    pub result: GameResult<Option<Rc<opengl::Program>>>,
+}
+
+pub(crate) struct HotReload {
+    enabled: bool,
+    interval: Duration,
+    last_poll: Instant,
+    entries: Vec<WatchEntry>,
+}
+
+impl HotReload {
+    pub fn new(enabled: bool, interval: Duration) -> Self {
+        Self {
+            enabled,
+            interval,
+            last_poll: Instant::now(),
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.interval = interval;
+    }
+
+    pub fn watch(&mut self, asset: WatchedAsset, paths: WatchPaths) {
+        self.entries.push(WatchEntry { asset, paths });
+    }
+
+    pub fn poll(&mut self, filesystem: &Filesystem, gl: Rc<Context>) -> Vec<ReloadOutcome> {
+        self.entries.retain(|entry| entry.asset.is_alive());
+        let now = Instant::now();
+        if !self.enabled || now.duration_since(self.last_poll) < self.interval {
+            return Vec::new();
+        }
+        self.last_poll = now;
+        let mut outcomes = Vec::new();
+        for entry in self.entries.iter_mut() {
+            let mut changed_path = None;
+            for (path, modified) in entry.paths.iter_mut() {
+                let current = filesystem.metadata(&path).ok().and_then(|metadata| metadata.modified());
+                if current != *modified {
+                    *modified = current;
+                    if changed_path.is_none() {
+                        changed_path = Some(path.clone());
+                    }
+                }
+            }
+            if let Some(path) = changed_path {
+                let result = entry.asset.reload(&entry.paths, filesystem, gl.clone());
+                outcomes.push(ReloadOutcome { path, result });
+            }
+        }
+        outcomes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::engine::{Engine, EngineBuilder};
+    use crate::event::Event;
+    use crate::filesystem::{FilesystemConfig, FilesystemRoot};
+    use crate::graphics::{GraphicsConfig, Program};
+    use std::path::{Path, PathBuf};
+    use std::time::{Duration, SystemTime};
+
+    const VERTEX_SHADER_SOURCE: &str = include_str!("shaders/default.vert");
+    const FRAGMENT_SHADER_SOURCE: &str = include_str!("shaders/default.frag");
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("tge-hot-reload-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("default.vert"), VERTEX_SHADER_SOURCE).unwrap();
+        std::fs::write(dir.join("default.frag"), FRAGMENT_SHADER_SOURCE).unwrap();
+        dir
+    }
+
+    fn build_engine(dir: &Path, hot_reload: bool) -> Engine {
+        EngineBuilder::new()
+            .headless(true)
+            .filesystem_config(FilesystemConfig::new().roots(vec![FilesystemRoot::Path(dir.to_path_buf())]))
+            .graphics_config(GraphicsConfig::new().hot_reload(hot_reload).hot_reload_interval(Duration::ZERO))
+            .build()
+            .unwrap()
+    }
+
+    fn touch(path: &Path, contents: &str, offset: u64) {
+        std::fs::write(path, contents).unwrap();
+        let file = std::fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(offset)).unwrap();
+    }
+
+    fn poll(engine: &mut Engine) -> Vec<Event> {
+        let filesystem = engine.filesystem().clone();
+        engine.graphics().poll_hot_reload(&filesystem)
+    }
+
+    #[test]
+    fn test_poll_and_swap() {
+        let dir = temp_dir("swap");
+        let mut engine = build_engine(&dir, false);
+        let program = Program::load(&mut engine, "default.vert", "default.frag").unwrap();
+        let id = program.program().id();
+        assert!(poll(&mut engine).is_empty());
+        engine.graphics().set_hot_reload(true);
+        assert!(poll(&mut engine).is_empty());
+        touch(&dir.join("default.frag"), FRAGMENT_SHADER_SOURCE, 10);
+        let events = poll(&mut engine);
+        assert!(matches!(events.as_slice(), [Event::AssetReload(path)] if path == Path::new("default.frag")));
+        assert_ne!(program.program().id(), id);
+        assert!(poll(&mut engine).is_empty());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_broken_shader() {
+        let dir = temp_dir("broken");
+        let mut engine = build_engine(&dir, true);
+        let program = Program::load(&mut engine, "default.vert", "default.frag").unwrap();
+        let id = program.program().id();
+        touch(&dir.join("default.vert"), "#version 330 core\nvoid main() { broken }\n", 10);
+        let events = poll(&mut engine);
+        assert!(matches!(events.as_slice(), [Event::AssetReloadError { path, .. }] if path == Path::new("default.vert")));
+        assert_eq!(program.program().id(), id);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,6 +1,7 @@
 use crate::error::{GameError, GameResult};
 use crate::math::Size;
 use crate::engine::Engine;
+use crate::loader::LoadHandle;
 use std::path::Path;
 
 #[derive(Clone)]
@@ -30,6 +31,15 @@ impl Image {
         Self::from_bytes(&bytes)
     }
 
+    pub fn load_async(engine: &mut Engine, path: impl AsRef<Path>) -> LoadHandle<Self> {
+        let path = path.as_ref().to_path_buf();
+        let filesystem = engine.filesystem().clone();
+        engine.loader().spawn(move || {
+            let bytes = filesystem.read(&path)?;
+            Self::from_bytes(&bytes)
+        }, |_, image| Ok(image))
+    }
+
     pub fn size(&self) -> Size<u32> {
         self.size
     }
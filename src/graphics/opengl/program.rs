@@ -1,11 +1,12 @@
 use glow::{Context, HasContext};
 use std::rc::Rc;
+use std::cell::Cell;
 
 pub type ProgramId = <Context as HasContext>::Program;
 
 pub struct Program {
     gl: Rc<Context>,
-    id: ProgramId,
+    id: Cell<ProgramId>,
 }
 
 impl Program {
@@ -40,16 +41,20 @@ impl Program {
 
             program_id
         };
-        Ok(Self { gl, id })
+        Ok(Self { gl, id: Cell::new(id) })
     }
 
     pub fn id(&self) -> ProgramId {
-        self.id
+        self.id.get()
+    }
+
+    pub fn swap(&self, other: &Self) {
+        self.id.swap(&other.id);
     }
 
     pub fn bind(&self) {
         unsafe {
-            self.gl.use_program(Some(self.id));
+            self.gl.use_program(Some(self.id.get()));
         }
     }
 
@@ -61,7 +66,7 @@ impl Program {
 
     pub fn set_uniform_matrix_4(&self, name: &str, mat4: &[f32; 16]) {
         unsafe {
-            let location = self.gl.get_uniform_location(self.id, name);
+            let location = self.gl.get_uniform_location(self.id.get(), name);
             self.gl.uniform_matrix_4_f32_slice(location.as_ref(), false, mat4);
         }
     }
@@ -70,13 +75,13 @@ impl Program {
 impl Drop for Program {
     fn drop(&mut self) {
         unsafe {
-            self.gl.delete_program(self.id);
+            self.gl.delete_program(self.id.get());
         }
     }
 }
 
 impl PartialEq for Program {
     fn eq(&self, other: &Self) -> bool {
-        self.id == other.id
+        self.id.get() == other.id.get()
     }
 }
@@ -1,8 +1,9 @@
-use super::{Graphics, opengl};
+use super::{Graphics, opengl, WatchedAsset, watch_paths};
 use crate::error::{GameError, GameResult};
 use crate::engine::Engine;
+use crate::loader::LoadHandle;
 use glow::Context;
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
 use std::path::Path;
 
 const DEFAULT_VERTEX_SHADER_SOURCE: &str = include_str!("shaders/default.vert");
@@ -23,9 +24,29 @@ impl Program {
     }
 
     pub fn load(engine: &mut Engine, vertex_shader_path: impl AsRef<Path>, fragment_shader_path: impl AsRef<Path>) -> GameResult<Self> {
+        let vertex_shader_path = vertex_shader_path.as_ref();
+        let fragment_shader_path = fragment_shader_path.as_ref();
         let vertex_shader_source = engine.filesystem().read_to_string(vertex_shader_path)?;
         let fragment_shader_source = engine.filesystem().read_to_string(fragment_shader_path)?;
-        Self::new(engine.graphics(), &vertex_shader_source, &fragment_shader_source)
+        let program = Self::new(engine.graphics(), &vertex_shader_source, &fragment_shader_source)?;
+        engine.watch_asset(WatchedAsset::Program(program.watch_handle()), &[vertex_shader_path, fragment_shader_path]);
+        Ok(program)
+    }
+
+    pub fn load_async(engine: &mut Engine, vertex_shader_path: impl AsRef<Path>, fragment_shader_path: impl AsRef<Path>) -> LoadHandle<Self> {
+        let vertex_shader_path = vertex_shader_path.as_ref().to_path_buf();
+        let fragment_shader_path = fragment_shader_path.as_ref().to_path_buf();
+        let filesystem = engine.filesystem().clone();
+        engine.loader().spawn(move || {
+            let paths = watch_paths(&filesystem, &[&vertex_shader_path, &fragment_shader_path]);
+            let vertex_shader_source = filesystem.read_to_string(&vertex_shader_path)?;
+            let fragment_shader_source = filesystem.read_to_string(&fragment_shader_path)?;
+            Ok((vertex_shader_source, fragment_shader_source, paths))
+        }, |graphics, (vertex_shader_source, fragment_shader_source, paths)| {
+            let program = Self::new(graphics, vertex_shader_source, fragment_shader_source)?;
+            graphics.watch_asset(WatchedAsset::Program(program.watch_handle()), paths);
+            Ok(program)
+        })
     }
 
     pub(crate) fn default(gl: Rc<Context>) -> GameResult<Rc<opengl::Program>> {
@@ -40,4 +61,32 @@ impl Program {
     pub(crate) fn program(&self) -> &Rc<opengl::Program> {
         &self.program
     }
+
+    pub(crate) fn watch_handle(&self) -> ProgramWatchHandle {
+        ProgramWatchHandle {
+            program: Rc::downgrade(&self.program),
+        }
+    }
+}
+
+pub(crate) struct ProgramWatchHandle {
+    program: Weak<opengl::Program>,
+}
+
+impl ProgramWatchHandle {
+    pub fn is_alive(&self) -> bool {
+        self.program.strong_count() > 0
+    }
+
+    pub fn reload(&self, gl: Rc<Context>, vertex_shader_source: &str, fragment_shader_source: &str) -> GameResult<Option<Rc<opengl::Program>>> {
+        match self.program.upgrade() {
+            Some(program) => {
+                let new_program = opengl::Program::new(gl, vertex_shader_source, fragment_shader_source)
+                    .map_err(|error| GameError::InitError(error.into()))?;
+                program.swap(&new_program);
+                Ok(Some(program))
+            }
+            None => Ok(None),
+        }
+    }
 }
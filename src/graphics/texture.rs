@@ -1,17 +1,19 @@
-use super::{Graphics, opengl, FilterMode, Filter, WrapMode, Wrap, Image, validate_pixels};
+use super::{Graphics, opengl, FilterMode, Filter, WrapMode, Wrap, Image, WatchedAsset, watch_paths, validate_pixels};
 use crate::error::{GameError, GameResult};
 use crate::math::{Size, Region};
 use crate::engine::Engine;
+use crate::loader::LoadHandle;
 use glow::Context;
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
+use std::cell::Cell;
 use std::path::Path;
 
 pub struct Texture {
     texture: Rc<opengl::Texture>,
-    size: Size<u32>,
-    filter: Filter,
-    mipmap_generated: bool,
-    wrap: Wrap,
+    size: Rc<Cell<Size<u32>>>,
+    filter: Rc<Cell<Filter>>,
+    mipmap_generated: Rc<Cell<bool>>,
+    wrap: Cell<Wrap>,
 }
 
 impl Texture {
@@ -35,10 +37,10 @@ impl Texture {
         texture.unbind();
         Ok(Self {
             texture: Rc::new(texture),
-            size,
-            filter,
-            mipmap_generated: generate_mipmap,
-            wrap,
+            size: Rc::new(Cell::new(size)),
+            filter: Rc::new(Cell::new(filter)),
+            mipmap_generated: Rc::new(Cell::new(generate_mipmap)),
+            wrap: Cell::new(wrap),
         })
     }
 
@@ -54,8 +56,25 @@ impl Texture {
     }
 
     pub fn load(engine: &mut Engine, path: impl AsRef<Path>) -> GameResult<Self> {
+        let path = path.as_ref();
         let image = Image::load(engine, path)?;
-        Self::from_image(engine.graphics(), &image)
+        let texture = Self::from_image(engine.graphics(), &image)?;
+        engine.watch_asset(WatchedAsset::Texture(texture.watch_handle()), &[path]);
+        Ok(texture)
+    }
+
+    pub fn load_async(engine: &mut Engine, path: impl AsRef<Path>) -> LoadHandle<Self> {
+        let path = path.as_ref().to_path_buf();
+        let filesystem = engine.filesystem().clone();
+        engine.loader().spawn(move || {
+            let paths = watch_paths(&filesystem, &[&path]);
+            let bytes = filesystem.read(&path)?;
+            Ok((Image::from_bytes(&bytes)?, paths))
+        }, |graphics, (image, paths)| {
+            let texture = Self::from_image(graphics, &image)?;
+            graphics.watch_asset(WatchedAsset::Texture(texture.watch_handle()), paths);
+            Ok(texture)
+        })
     }
 
     pub(crate) fn white_1_1(gl: Rc<Context>) -> GameResult<Rc<opengl::Texture>> {
@@ -86,10 +105,10 @@ impl Texture {
         texture.unbind();
         Ok(Self {
             texture: Rc::new(texture),
-            size,
-            filter,
-            mipmap_generated: generate_mipmap,
-            wrap,
+            size: Rc::new(Cell::new(size)),
+            filter: Rc::new(Cell::new(filter)),
+            mipmap_generated: Rc::new(Cell::new(generate_mipmap)),
+            wrap: Cell::new(wrap),
         })
     }
 
@@ -97,37 +116,46 @@ impl Texture {
         &self.texture
     }
 
+    pub(crate) fn watch_handle(&self) -> TextureWatchHandle {
+        TextureWatchHandle {
+            texture: Rc::downgrade(&self.texture),
+            size: Rc::downgrade(&self.size),
+            filter: Rc::downgrade(&self.filter),
+            mipmap_generated: Rc::downgrade(&self.mipmap_generated),
+        }
+    }
+
     pub fn size(&self) -> Size<u32> {
-        self.size
+        self.size.get()
     }
 
     pub fn filter(&self) -> Filter {
-        self.filter
+        self.filter.get()
     }
 
-    pub fn set_filter(&mut self, filter: Filter) {
-        if self.filter != filter {
+    pub fn set_filter(&self, filter: Filter) {
+        if self.filter.get() != filter {
             self.texture.bind();
             self.texture.set_filter(filter);
-            if !self.mipmap_generated && filter.mipmap.is_some() {
+            if !self.mipmap_generated.get() && filter.mipmap.is_some() {
                 self.texture.generate_mipmap();
-                self.mipmap_generated = true;
+                self.mipmap_generated.set(true);
             }
             self.texture.unbind();
-            self.filter = filter;
+            self.filter.set(filter);
         }
     }
 
     pub fn wrap(&self) -> Wrap {
-        self.wrap
+        self.wrap.get()
     }
 
-    pub fn set_wrap(&mut self, wrap: Wrap) {
-        if self.wrap != wrap {
+    pub fn set_wrap(&self, wrap: Wrap) {
+        if self.wrap.get() != wrap {
             self.texture.bind();
             self.texture.set_wrap(wrap);
             self.texture.unbind();
-            self.wrap = wrap;
+            self.wrap.set(wrap);
         }
     }
 
@@ -138,13 +166,8 @@ impl Texture {
         }
         self.texture.bind();
         self.texture.init_image(size.width, size.height, pixels);
-        self.size = size;
-        if self.filter.mipmap.is_some() {
-            self.texture.generate_mipmap();
-            self.mipmap_generated = true;
-        } else {
-            self.mipmap_generated = false;
-        }
+        self.size.set(size);
+        refresh_mipmap(&self.texture, self.filter.get(), &self.mipmap_generated);
         self.texture.unbind();
         Ok(())
     }
@@ -168,12 +191,7 @@ impl Texture {
             region.height,
             pixels,
         );
-        if self.filter.mipmap.is_some() {
-            self.texture.generate_mipmap();
-            self.mipmap_generated = true;
-        } else {
-            self.mipmap_generated = false;
-        }
+        refresh_mipmap(&self.texture, self.filter.get(), &self.mipmap_generated);
         self.texture.unbind();
         Ok(())
     }
@@ -182,13 +200,42 @@ impl Texture {
         let size = size.into();
         self.texture.bind();
         self.texture.init_image(size.width, size.height, None);
-        self.size = size;
-        if self.filter.mipmap.is_some() {
-            self.texture.generate_mipmap();
-            self.mipmap_generated = true;
-        } else {
-            self.mipmap_generated = false;
-        }
+        self.size.set(size);
+        refresh_mipmap(&self.texture, self.filter.get(), &self.mipmap_generated);
         self.texture.unbind();
     }
 }
+
+fn refresh_mipmap(texture: &opengl::Texture, filter: Filter, mipmap_generated: &Cell<bool>) {
+    if filter.mipmap.is_some() {
+        texture.generate_mipmap();
+        mipmap_generated.set(true);
+    } else {
+        mipmap_generated.set(false);
+    }
+}
+
+pub(crate) struct TextureWatchHandle {
+    texture: Weak<opengl::Texture>,
+    size: Weak<Cell<Size<u32>>>,
+    filter: Weak<Cell<Filter>>,
+    mipmap_generated: Weak<Cell<bool>>,
+}
+
+impl TextureWatchHandle {
+    pub fn is_alive(&self) -> bool {
+        self.texture.strong_count() > 0
+    }
+
+    pub fn reload(&self, image: &Image) {
+        if let (Some(texture), Some(size), Some(filter), Some(mipmap_generated)) =
+            (self.texture.upgrade(), self.size.upgrade(), self.filter.upgrade(), self.mipmap_generated.upgrade()) {
+            let image_size = image.size();
+            texture.bind();
+            texture.init_image(image_size.width, image_size.height, Some(image.pixels()));
+            refresh_mipmap(&texture, filter.get(), &mipmap_generated);
+            texture.unbind();
+            size.set(image_size);
+        }
+    }
+}
@@ -45,6 +45,16 @@ impl Keyboard {
         self.modifiers_state = state;
     }
 
+    pub(crate) fn reset_states(&mut self) {
+        self.key_states.clear();
+        self.modifiers_state = ModifiersState {
+            shift: false,
+            ctrl: false,
+            alt: false,
+            logo: false,
+        };
+    }
+
     pub(crate) fn clear_states(&mut self) {
         self.key_states.retain(|_, state| match state {
             KeyState::Down | KeyState::Hold => {
@@ -1,6 +1,7 @@
 use winit::event::{VirtualKeyCode, ScanCode};
+use serde::{Serialize, Deserialize};
 
-#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub enum KeyCode {
     Esc,
     Num1,
@@ -139,6 +140,127 @@ pub enum KeyCode {
     Other(u32),
 }
 
+impl KeyCode {
+    pub(crate) const VARIANTS: &'static [Self] = &[
+        Self::Esc,
+        Self::Num1,
+        Self::Num2,
+        Self::Num3,
+        Self::Num4,
+        Self::Num5,
+        Self::Num6,
+        Self::Num7,
+        Self::Num8,
+        Self::Num9,
+        Self::Num0,
+        Self::A,
+        Self::B,
+        Self::C,
+        Self::D,
+        Self::E,
+        Self::F,
+        Self::G,
+        Self::H,
+        Self::I,
+        Self::J,
+        Self::K,
+        Self::L,
+        Self::M,
+        Self::N,
+        Self::O,
+        Self::P,
+        Self::Q,
+        Self::R,
+        Self::S,
+        Self::T,
+        Self::U,
+        Self::V,
+        Self::W,
+        Self::X,
+        Self::Y,
+        Self::Z,
+        Self::F1,
+        Self::F2,
+        Self::F3,
+        Self::F4,
+        Self::F5,
+        Self::F6,
+        Self::F7,
+        Self::F8,
+        Self::F9,
+        Self::F10,
+        Self::F11,
+        Self::F12,
+        Self::F13,
+        Self::F14,
+        Self::F15,
+        Self::F16,
+        Self::F17,
+        Self::F18,
+        Self::F19,
+        Self::F20,
+        Self::F21,
+        Self::F22,
+        Self::F23,
+        Self::F24,
+        Self::PrintScreen,
+        Self::ScrollLock,
+        Self::Pause,
+        Self::Grave,
+        Self::Minus,
+        Self::Equals,
+        Self::LBracket,
+        Self::RBracket,
+        Self::Backslash,
+        Self::Semicolon,
+        Self::Apostrophe,
+        Self::Comma,
+        Self::Period,
+        Self::Slash,
+        Self::Tab,
+        Self::CapsLock,
+        Self::LShift,
+        Self::RShift,
+        Self::LCtrl,
+        Self::RCtrl,
+        Self::LAlt,
+        Self::RAlt,
+        Self::LWin,
+        Self::RWin,
+        Self::Backspace,
+        Self::Enter,
+        Self::Space,
+        Self::Menu,
+        Self::Insert,
+        Self::Delete,
+        Self::Home,
+        Self::End,
+        Self::PageUp,
+        Self::PageDown,
+        Self::Up,
+        Self::Down,
+        Self::Left,
+        Self::Right,
+        Self::NumLock,
+        Self::Numpad0,
+        Self::Numpad1,
+        Self::Numpad2,
+        Self::Numpad3,
+        Self::Numpad4,
+        Self::Numpad5,
+        Self::Numpad6,
+        Self::Numpad7,
+        Self::Numpad8,
+        Self::Numpad9,
+        Self::NumpadAdd,
+        Self::NumpadSubtract,
+        Self::NumpadMultiply,
+        Self::NumpadDivide,
+        Self::NumpadDecimal,
+        Self::NumpadEnter,
+    ];
+}
+
 impl From<(Option<VirtualKeyCode>, ScanCode)> for KeyCode {
     fn from((key_code, scan_code): (Option<VirtualKeyCode>, ScanCode)) -> Self {
         match key_code {
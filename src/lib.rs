@@ -6,11 +6,19 @@ pub mod filesystem;
 pub mod window;
 pub mod graphics;
 pub mod timer;
+pub mod tween;
 pub mod keyboard;
 pub mod mouse;
 pub mod touch;
 pub mod touchpad;
 pub mod gamepad;
+pub mod action;
+pub mod clipboard;
+pub mod text_edit;
 pub mod audio;
+pub mod loader;
+pub mod assets;
 pub mod game;
+pub mod scene;
+pub mod replay;
 pub mod prelude;
@@ -0,0 +1,360 @@
+mod handle;
+
+pub use handle::LoadHandle;
+
+use handle::LoadState;
+use crate::error::{GameError, GameResult};
+use crate::graphics::Graphics;
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{self, Sender, Receiver};
+use std::thread::JoinHandle;
+
+#[derive(Debug, Copy, Clone)]
+enum WorkerErrorKind {
+    Io,
+    Init,
+    State,
+    Runtime,
+    NotSupported,
+}
+
+struct WorkerError {
+    kind: WorkerErrorKind,
+    message: String,
+}
+
+impl From<GameError> for WorkerError {
+    fn from(error: GameError) -> Self {
+        let (kind, source) = match error {
+            GameError::IoError(source) => (WorkerErrorKind::Io, source),
+            GameError::InitError(source) => (WorkerErrorKind::Init, source),
+            GameError::StateError(source) => (WorkerErrorKind::State, source),
+            GameError::RuntimeError(source) => (WorkerErrorKind::Runtime, source),
+            GameError::NotSupportedError(source) => (WorkerErrorKind::NotSupported, source),
+        };
+        Self { kind, message: source.to_string() }
+    }
+}
+
+impl WorkerError {
+    fn from_panic(payload: Box<dyn Any + Send>) -> Self {
+        let message = payload.downcast_ref::<&str>().map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_owned());
+        Self {
+            kind: WorkerErrorKind::Runtime,
+            message: format!("loader job panicked: {}", message),
+        }
+    }
+}
+
+impl From<WorkerError> for GameError {
+    fn from(error: WorkerError) -> Self {
+        let source = error.message.into();
+        match error.kind {
+            WorkerErrorKind::Io => Self::IoError(source),
+            WorkerErrorKind::Init => Self::InitError(source),
+            WorkerErrorKind::State => Self::StateError(source),
+            WorkerErrorKind::Runtime => Self::RuntimeError(source),
+            WorkerErrorKind::NotSupported => Self::NotSupportedError(source),
+        }
+    }
+}
+
+type JobOutput = Result<Box<dyn Any + Send>, WorkerError>;
+
+struct Job {
+    id: u64,
+    decode: Box<dyn FnOnce() -> JobOutput + Send>,
+}
+
+trait PendingLoad {
+    fn set_decoded(&self);
+
+    fn is_abandoned(&self) -> bool;
+
+    fn finish(self: Box<Self>, graphics: &mut Graphics, output: JobOutput);
+}
+
+struct Pending<T, D, F> {
+    state: Rc<RefCell<LoadState<T>>>,
+    finish: F,
+    _decoded: std::marker::PhantomData<D>,
+}
+
+impl<T, D, F> PendingLoad for Pending<T, D, F>
+    where
+        D: 'static,
+        F: FnOnce(&mut Graphics, D) -> GameResult<T>,
+{
+    fn set_decoded(&self) {
+        *self.state.borrow_mut() = LoadState::Decoded;
+    }
+
+    fn is_abandoned(&self) -> bool {
+        Rc::strong_count(&self.state) == 1
+    }
+
+    fn finish(self: Box<Self>, graphics: &mut Graphics, output: JobOutput) {
+        let result = match output {
+            Ok(decoded) => match decoded.downcast::<D>() {
+                Ok(decoded) => (self.finish)(graphics, *decoded),
+                Err(_) => Err(GameError::RuntimeError("decoded asset type mismatch".into())),
+            },
+            Err(error) => Err(error.into()),
+        };
+        *self.state.borrow_mut() = match result {
+            Ok(asset) => LoadState::Ready(asset),
+            Err(error) => LoadState::Failed(error),
+        };
+    }
+}
+
+pub struct Loader {
+    sender: Option<Sender<Job>>,
+    receiver: Receiver<(u64, JobOutput)>,
+    workers: Vec<JoinHandle<()>>,
+    next_job_id: u64,
+    pending: HashMap<u64, Box<dyn PendingLoad>>,
+    decoded: VecDeque<(u64, JobOutput)>,
+    uploads_per_frame: usize,
+    total_count: usize,
+    completed_count: usize,
+}
+
+impl Loader {
+    pub(crate) fn new(loader_config: LoaderConfig) -> GameResult<Self> {
+        let worker_count = loader_config.worker_count.unwrap_or_else(|| {
+            std::thread::available_parallelism()
+                .map(|count| count.get().min(4))
+                .unwrap_or(1)
+        }).max(1);
+        let (sender, job_receiver) = mpsc::channel::<Job>();
+        let (output_sender, receiver) = mpsc::channel();
+        let job_receiver = Arc::new(Mutex::new(job_receiver));
+        let mut workers = Vec::with_capacity(worker_count);
+        for index in 0..worker_count {
+            let job_receiver = job_receiver.clone();
+            let output_sender = output_sender.clone();
+            let worker = std::thread::Builder::new()
+                .name(format!("tge-loader-{}", index))
+                .spawn(move || loop {
+                    let job = match job_receiver.lock() {
+                        Ok(job_receiver) => job_receiver.recv(),
+                        Err(_) => break,
+                    };
+                    let job = match job {
+                        Ok(job) => job,
+                        Err(_) => break,
+                    };
+                    let output = match panic::catch_unwind(AssertUnwindSafe(job.decode)) {
+                        Ok(output) => output,
+                        Err(payload) => Err(WorkerError::from_panic(payload)),
+                    };
+                    if output_sender.send((job.id, output)).is_err() {
+                        break;
+                    }
+                })
+                .map_err(|error| GameError::InitError(error.into()))?;
+            workers.push(worker);
+        }
+        Ok(Self {
+            sender: Some(sender),
+            receiver,
+            workers,
+            next_job_id: 0,
+            pending: HashMap::new(),
+            decoded: VecDeque::new(),
+            uploads_per_frame: loader_config.uploads_per_frame.max(1),
+            total_count: 0,
+            completed_count: 0,
+        })
+    }
+
+    pub fn spawn<T, D, F, G>(&mut self, decode: F, finish: G) -> LoadHandle<T>
+        where
+            T: 'static,
+            D: Send + 'static,
+            F: FnOnce() -> GameResult<D> + Send + 'static,
+            G: FnOnce(&mut Graphics, D) -> GameResult<T> + 'static,
+    {
+        let handle = LoadHandle::new();
+        if self.pending.is_empty() {
+            self.total_count = 0;
+            self.completed_count = 0;
+        }
+        let id = self.next_job_id;
+        self.next_job_id += 1;
+        let job = Job {
+            id,
+            decode: Box::new(move || {
+                decode()
+                    .map(|decoded| Box::new(decoded) as Box<dyn Any + Send>)
+                    .map_err(WorkerError::from)
+            }),
+        };
+        let sent = self.sender.as_ref()
+            .map(|sender| sender.send(job).is_ok())
+            .unwrap_or(false);
+        if sent {
+            self.pending.insert(id, Box::new(Pending {
+                state: handle.state().clone(),
+                finish,
+                _decoded: std::marker::PhantomData,
+            }));
+            self.total_count += 1;
+        } else {
+            *handle.state().borrow_mut() = LoadState::Failed(GameError::StateError("loader workers are stopped".into()));
+        }
+        handle
+    }
+
+    pub(crate) fn pump(&mut self, graphics: &mut Graphics) {
+        while let Ok((id, output)) = self.receiver.try_recv() {
+            if let Some(pending) = self.pending.get(&id) {
+                pending.set_decoded();
+            }
+            self.decoded.push_back((id, output));
+        }
+        let mut uploads = 0;
+        while uploads < self.uploads_per_frame {
+            let (id, output) = match self.decoded.pop_front() {
+                Some(decoded) => decoded,
+                None => break,
+            };
+            if let Some(pending) = self.pending.remove(&id) {
+                if !pending.is_abandoned() {
+                    pending.finish(graphics, output);
+                    uploads += 1;
+                }
+                self.completed_count += 1;
+            }
+        }
+    }
+
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    pub fn progress(&self) -> f32 {
+        if self.total_count == 0 {
+            1.0
+        } else {
+            self.completed_count as f32 / self.total_count as f32
+        }
+    }
+
+    pub fn uploads_per_frame(&self) -> usize {
+        self.uploads_per_frame
+    }
+
+    pub fn set_uploads_per_frame(&mut self, uploads_per_frame: usize) {
+        self.uploads_per_frame = uploads_per_frame.max(1);
+    }
+}
+
+impl Drop for Loader {
+    fn drop(&mut self) {
+        self.sender.take();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LoaderConfig {
+    worker_count: Option<usize>,
+    uploads_per_frame: usize,
+}
+
+impl LoaderConfig {
+    pub fn new() -> Self {
+        Self {
+            worker_count: None,
+            uploads_per_frame: 8,
+        }
+    }
+
+    pub fn worker_count(mut self, worker_count: usize) -> Self {
+        self.worker_count = Some(worker_count);
+        self
+    }
+
+    pub fn uploads_per_frame(mut self, uploads_per_frame: usize) -> Self {
+        self.uploads_per_frame = uploads_per_frame;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Loader, LoaderConfig, LoadHandle};
+    use crate::engine::EngineBuilder;
+    use crate::error::GameError;
+    use crate::graphics::Graphics;
+    use std::time::{Duration, Instant};
+
+    fn pump_until_idle(loader: &mut Loader, graphics: &mut Graphics) {
+        let start = Instant::now();
+        while !loader.is_idle() {
+            assert!(start.elapsed() < Duration::from_secs(10));
+            loader.pump(graphics);
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn test_completion() {
+        let mut engine = EngineBuilder::new().headless(true).build().unwrap();
+        let mut loader = Loader::new(LoaderConfig::new().worker_count(2).uploads_per_frame(1)).unwrap();
+        assert_eq!(loader.progress(), 1.0);
+        let handles: Vec<LoadHandle<u32>> = (0..4)
+            .map(|index| loader.spawn(move || Ok(index), |_, index| Ok(index * 10)))
+            .collect();
+        assert_eq!(loader.pending_count(), 4);
+        assert_eq!(loader.progress(), 0.0);
+        let start = Instant::now();
+        while loader.pending_count() == 4 {
+            assert!(start.elapsed() < Duration::from_secs(10));
+            loader.pump(engine.graphics());
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(loader.pending_count(), 3);
+        assert_eq!(loader.progress(), 0.25);
+        pump_until_idle(&mut loader, engine.graphics());
+        assert_eq!(loader.progress(), 1.0);
+        for (index, handle) in handles.iter().enumerate() {
+            assert!(handle.is_ready());
+            assert_eq!(handle.take().unwrap().unwrap(), index as u32 * 10);
+            assert!(handle.take().is_none());
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut engine = EngineBuilder::new().headless(true).build().unwrap();
+        let mut loader = Loader::new(LoaderConfig::new().worker_count(1)).unwrap();
+        let failed: LoadHandle<u32> = loader.spawn(|| Err(GameError::IoError("missing".into())), |_, value: u32| Ok(value));
+        let panicked: LoadHandle<u32> = loader.spawn(|| -> crate::error::GameResult<u32> { panic!("broken decoder") }, |_, value| Ok(value));
+        let finished: LoadHandle<u32> = loader.spawn(|| Ok(1), |_, _| Err(GameError::InitError("upload failed".into())));
+        let ok: LoadHandle<u32> = loader.spawn(|| Ok(2), |_, value| Ok(value));
+        pump_until_idle(&mut loader, engine.graphics());
+        assert!(matches!(failed.take(), Some(Err(GameError::IoError(_)))));
+        match panicked.take() {
+            Some(Err(GameError::RuntimeError(error))) => assert!(error.to_string().contains("broken decoder")),
+            _ => panic!("expected a runtime error"),
+        }
+        assert!(matches!(finished.take(), Some(Err(GameError::InitError(_)))));
+        assert_eq!(ok.take().unwrap().unwrap(), 2);
+    }
+}
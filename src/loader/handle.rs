@@ -0,0 +1,61 @@
+use crate::error::{GameError, GameResult};
+use std::rc::Rc;
+use std::cell::RefCell;
+
+pub(crate) enum LoadState<T> {
+    Queued,
+    Decoded,
+    Ready(T),
+    Failed(GameError),
+    Taken,
+}
+
+pub struct LoadHandle<T> {
+    state: Rc<RefCell<LoadState<T>>>,
+}
+
+impl<T> LoadHandle<T> {
+    pub(crate) fn new() -> Self {
+        Self {
+            state: Rc::new(RefCell::new(LoadState::Queued)),
+        }
+    }
+
+    pub(crate) fn state(&self) -> &Rc<RefCell<LoadState<T>>> {
+        &self.state
+    }
+
+    pub fn is_ready(&self) -> bool {
+        matches!(*self.state.borrow(), LoadState::Ready(_))
+    }
+
+    pub fn is_failed(&self) -> bool {
+        matches!(*self.state.borrow(), LoadState::Failed(_))
+    }
+
+    pub fn is_done(&self) -> bool {
+        !matches!(*self.state.borrow(), LoadState::Queued | LoadState::Decoded)
+    }
+
+    pub fn progress(&self) -> f32 {
+        match *self.state.borrow() {
+            LoadState::Queued => 0.0,
+            LoadState::Decoded => 0.5,
+            _ => 1.0,
+        }
+    }
+
+    pub fn take(&self) -> Option<GameResult<T>> {
+        let mut state = self.state.borrow_mut();
+        match *state {
+            LoadState::Ready(_) | LoadState::Failed(_) => {
+                match std::mem::replace(&mut *state, LoadState::Taken) {
+                    LoadState::Ready(asset) => Some(Ok(asset)),
+                    LoadState::Failed(error) => Some(Err(error)),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
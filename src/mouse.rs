@@ -7,14 +7,13 @@ pub use button::MouseButton;
 use crate::error::{GameError, GameResult};
 use crate::math::Vector;
 use crate::event::{KeyState, KeyAction};
-use crate::window::LogicalPosition;
+use crate::window::{LogicalPosition, WindowContext};
 use winit::window::Window;
-use glutin::{ContextWrapper, PossiblyCurrent};
 use std::rc::Rc;
 use std::collections::HashMap;
 
 pub struct Mouse {
-    context_wrapper: Rc<ContextWrapper<PossiblyCurrent, Window>>,
+    context: Rc<WindowContext>,
     cursor_icon: CursorIcon,
     cursor_visible: bool,
     position: LogicalPosition,
@@ -24,12 +23,13 @@ pub struct Mouse {
 }
 
 impl Mouse {
-    pub(crate) fn new(mouse_config: MouseConfig, context_wrapper: Rc<ContextWrapper<PossiblyCurrent, Window>>) -> GameResult<Self> {
-        let window = context_wrapper.window();
-        window.set_cursor_icon(mouse_config.cursor_icon.into());
-        window.set_cursor_visible(mouse_config.cursor_visible);
+    pub(crate) fn new(mouse_config: MouseConfig, context: Rc<WindowContext>) -> GameResult<Self> {
+        if let Some(window) = context.window() {
+            window.set_cursor_icon(mouse_config.cursor_icon.into());
+            window.set_cursor_visible(mouse_config.cursor_visible);
+        }
         Ok(Self {
-            context_wrapper,
+            context,
             cursor_icon: mouse_config.cursor_icon,
             cursor_visible: mouse_config.cursor_visible,
             position: LogicalPosition::zero(),
@@ -39,8 +39,8 @@ impl Mouse {
         })
     }
 
-    fn window(&self) -> &Window {
-        self.context_wrapper.window()
+    fn window(&self) -> Option<&Window> {
+        self.context.window()
     }
 
     pub(crate) fn handle_move_event(&mut self, position: LogicalPosition) {
@@ -63,6 +63,13 @@ impl Mouse {
         self.button_states.insert(button, action.into());
     }
 
+    pub(crate) fn reset_states(&mut self) {
+        self.position = LogicalPosition::zero();
+        self.inside_window = false;
+        self.wheel_scroll_delta = Vector::zero();
+        self.button_states.clear();
+    }
+
     pub(crate) fn clear_states(&mut self) {
         self.wheel_scroll_delta = Vector::zero();
         self.button_states.retain(|_, state| match state {
@@ -79,7 +86,9 @@ impl Mouse {
     }
 
     pub fn set_cursor_icon(&mut self, cursor_icon: CursorIcon) {
-        self.window().set_cursor_icon(cursor_icon.into());
+        if let Some(window) = self.window() {
+            window.set_cursor_icon(cursor_icon.into());
+        }
         self.cursor_icon = cursor_icon;
     }
 
@@ -88,7 +97,9 @@ impl Mouse {
     }
 
     pub fn set_cursor_visible(&mut self, cursor_visible: bool) {
-        self.window().set_cursor_visible(cursor_visible);
+        if let Some(window) = self.window() {
+            window.set_cursor_visible(cursor_visible);
+        }
         self.cursor_visible = cursor_visible;
     }
 
@@ -106,8 +117,10 @@ impl Mouse {
 
     pub fn set_position(&mut self, position: impl Into<LogicalPosition>) -> GameResult {
         let position = position.into();
-        self.window().set_cursor_position(winit::dpi::LogicalPosition::new(position.x, position.y))
-            .map_err(|error| GameError::NotSupportedError(error.into()))?;
+        if let Some(window) = self.window() {
+            window.set_cursor_position(winit::dpi::LogicalPosition::new(position.x, position.y))
+                .map_err(|error| GameError::NotSupportedError(error.into()))?;
+        }
         self.position = position;
         Ok(())
     }
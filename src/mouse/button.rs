@@ -1,5 +1,6 @@
+use serde::{Serialize, Deserialize};
 
-#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub enum MouseButton {
     Left,
     Right,
@@ -7,6 +8,14 @@ pub enum MouseButton {
     Other(u32),
 }
 
+impl MouseButton {
+    pub(crate) const VARIANTS: &'static [Self] = &[
+        Self::Left,
+        Self::Right,
+        Self::Middle,
+    ];
+}
+
 impl From<winit::event::MouseButton> for MouseButton {
     fn from(button: winit::event::MouseButton) -> Self {
         match button {
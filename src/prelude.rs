@@ -2,14 +2,22 @@ pub use crate::error::{GameError, GameResult};
 pub use crate::math::{Vector, Position, Size, Region, Viewport, Angle, Transform};
 pub use crate::engine::{Engine, EngineBuilder};
 pub use crate::event::{KeyAction, TouchPhase, Event};
-pub use crate::filesystem::{Filesystem, FilesystemConfig};
+pub use crate::filesystem::{Filesystem, FilesystemConfig, FilesystemRoot, Metadata, PackWriter};
 pub use crate::window::{Window, WindowConfig, Icon, LogicalPosition, PhysicalPosition, LogicalSize, PhysicalSize, FullscreenMode};
 pub use crate::graphics::{Graphics, GraphicsConfig, PrimitiveType, FilterMode, Filter, WrapMode, Wrap, Program, Color, Vertex, Image, Texture, Canvas, Font, TextureRef, MeshDrawParams, SpriteDrawParams, TextLayoutGravity, TextDrawParams};
-pub use crate::timer::{Timer, TimerConfig};
+pub use crate::timer::{Timer, TimerConfig, TimerHandle};
+pub use crate::tween::{Easing, Tweenable, Animation, Tween, TweenValue, Sequence, Parallel};
 pub use crate::keyboard::{Keyboard, KeyboardConfig, KeyCode, ModifiersState};
 pub use crate::mouse::{Mouse, MouseConfig, CursorIcon, MouseButton};
-pub use crate::touch::{Touch, TouchConfig};
+pub use crate::touch::{Touch, TouchConfig, TouchEmulation, Gesture, SwipeDirection};
 pub use crate::touchpad::{Touchpad, TouchpadConfig};
-pub use crate::gamepad::{Gamepad, GamepadConfig, GamepadButton, GamepadAxis, GamepadId, GamepadDevice, PowerInfo};
-pub use crate::audio::{Audio, AudioConfig};
+pub use crate::gamepad::{Gamepad, GamepadConfig, GamepadButton, GamepadAxis, GamepadId, GamepadDevice, PowerInfo, RumbleMotor, RumbleEnvelope, RumbleLayer, RumbleEffect, RumbleHandle, Stick, ResponseCurve, StickSettings, GamepadCalibration, MappingSource, GamepadMapping};
+pub use crate::action::{Actions, ActionsConfig, ActionMap, Binding, AxisDirection, AxisSide, InputDevice};
+pub use crate::clipboard::{Clipboard, ClipboardConfig, ClipboardBackend};
+pub use crate::text_edit::{TextEdit, TextEditDrawParams};
+pub use crate::audio::{Audio, AudioConfig, AudioBackend, AudioBus, Ducking, Attenuation, SpatialSettings, Sound, Music, PlayParams, VoiceId, Voice};
+pub use crate::loader::{Loader, LoaderConfig, LoadHandle};
+pub use crate::assets::{Assets, AssetUsage, AssetStats};
 pub use crate::game::Game;
+pub use crate::scene::{Scene, SceneTransition, SceneStack};
+pub use crate::replay::{InputRecording, RecordedEvent};
@@ -0,0 +1,278 @@
+mod codec;
+
+use codec::{write_u32, write_u64, write_event, read_u32, read_u64, read_event};
+use crate::error::{GameError, GameResult};
+use crate::event::Event;
+use crate::filesystem::Filesystem;
+use std::path::Path;
+use std::time::Duration;
+
+const RECORDING_MAGIC: &[u8; 4] = b"TREC";
+const RECORDING_VERSION: u32 = 2;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedEvent {
+    pub frame: u64,
+    pub event: Event,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputRecording {
+    seed: u64,
+    fixed_update_duration: Option<Duration>,
+    frame_deltas: Vec<Duration>,
+    events: Vec<RecordedEvent>,
+}
+
+impl InputRecording {
+    pub fn new(seed: u64, fixed_update_duration: Option<Duration>) -> Self {
+        Self {
+            seed,
+            fixed_update_duration,
+            frame_deltas: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn fixed_update_duration(&self) -> Option<Duration> {
+        self.fixed_update_duration
+    }
+
+    pub fn frame_count(&self) -> u64 {
+        self.frame_deltas.len() as u64
+    }
+
+    pub fn frame_delta(&self, frame: u64) -> Option<Duration> {
+        self.frame_deltas.get(frame as usize).copied()
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.frame_deltas.iter().sum()
+    }
+
+    pub fn events(&self) -> &[RecordedEvent] {
+        &self.events
+    }
+
+    pub fn push_frame(&mut self, delta_time: Duration) {
+        self.frame_deltas.push(delta_time);
+    }
+
+    pub fn push_event(&mut self, event: Event) -> bool {
+        if event.is_input() {
+            let frame = self.frame_count();
+            self.events.push(RecordedEvent { frame, event });
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn to_bytes(&self) -> GameResult<Vec<u8>> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(RECORDING_MAGIC);
+        write_u32(&mut bytes, RECORDING_VERSION);
+        write_u64(&mut bytes, self.seed);
+        write_u64(&mut bytes, self.fixed_update_duration.map(|duration| duration.as_nanos() as u64).unwrap_or(0));
+        write_u64(&mut bytes, self.frame_deltas.len() as u64);
+        for delta_time in self.frame_deltas.iter() {
+            write_u64(&mut bytes, delta_time.as_nanos().min(u64::MAX as u128) as u64);
+        }
+        write_u64(&mut bytes, self.events.len() as u64);
+        for recorded in self.events.iter() {
+            write_u64(&mut bytes, recorded.frame);
+            if !write_event(&mut bytes, &recorded.event) {
+                return Err(GameError::NotSupportedError(format!("event `{:?}` can not be recorded", recorded.event).into()));
+            }
+        }
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> GameResult<Self> {
+        let mut reader = bytes;
+        if reader.len() < RECORDING_MAGIC.len() || &reader[..RECORDING_MAGIC.len()] != RECORDING_MAGIC {
+            return Err(GameError::NotSupportedError("not an input recording".into()));
+        }
+        reader = &reader[RECORDING_MAGIC.len()..];
+        let reader = &mut reader;
+        let version = read_u32(reader).map_err(|error| GameError::IoError(error.into()))?;
+        if version == 0 || version > RECORDING_VERSION {
+            return Err(GameError::NotSupportedError(format!("input recording version {} is not supported", version).into()));
+        }
+        let seed = read_u64(reader).map_err(|error| GameError::IoError(error.into()))?;
+        let fixed_update_duration = match read_u64(reader).map_err(|error| GameError::IoError(error.into()))? {
+            0 => None,
+            nanos => Some(Duration::from_nanos(nanos)),
+        };
+        let frame_count = read_u64(reader).map_err(|error| GameError::IoError(error.into()))?;
+        let mut frame_deltas = Vec::new();
+        for _ in 0..frame_count {
+            let nanos = match version {
+                1 => read_u32(reader).map(u64::from),
+                _ => read_u64(reader),
+            }.map_err(|error| GameError::IoError(error.into()))?;
+            frame_deltas.push(Duration::from_nanos(nanos));
+        }
+        let event_count = read_u64(reader).map_err(|error| GameError::IoError(error.into()))?;
+        let mut events = Vec::new();
+        for _ in 0..event_count {
+            let frame = read_u64(reader).map_err(|error| GameError::IoError(error.into()))?;
+            let event = read_event(reader).map_err(|error| GameError::IoError(error.into()))?;
+            events.push(RecordedEvent { frame, event });
+        }
+        Ok(Self { seed, fixed_update_duration, frame_deltas, events })
+    }
+
+    pub fn save(&self, filesystem: &Filesystem, path: impl AsRef<Path>) -> GameResult {
+        filesystem.write_atomic(path, self.to_bytes()?)
+    }
+
+    pub fn load(filesystem: &Filesystem, path: impl AsRef<Path>) -> GameResult<Self> {
+        let bytes = filesystem.read(path)?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+pub(crate) enum Replay {
+    Idle,
+    Recording(InputRecording),
+    Replaying {
+        recording: InputRecording,
+        frame: u64,
+        event_index: usize,
+    },
+}
+
+impl Replay {
+    pub fn is_recording(&self) -> bool {
+        matches!(self, Self::Recording(_))
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        matches!(self, Self::Replaying { .. })
+    }
+
+    pub fn seed(&self) -> Option<u64> {
+        match self {
+            Self::Idle => None,
+            Self::Recording(recording) => Some(recording.seed()),
+            Self::Replaying { recording, .. } => Some(recording.seed()),
+        }
+    }
+
+    pub fn record_event(&mut self, event: &Event) {
+        if let Self::Recording(recording) = self {
+            recording.push_event(event.clone());
+        }
+    }
+
+    pub fn take_frame_events(&mut self) -> Vec<Event> {
+        let mut events = Vec::new();
+        if let Self::Replaying { recording, frame, event_index } = self {
+            while let Some(recorded) = recording.events.get(*event_index) {
+                if recorded.frame > *frame {
+                    break;
+                }
+                events.push(recorded.event.clone());
+                *event_index += 1;
+            }
+        }
+        events
+    }
+
+    pub fn frame_delta(&self) -> Option<Duration> {
+        match self {
+            Self::Replaying { recording, frame, .. } => recording.frame_delta(*frame),
+            _ => None,
+        }
+    }
+
+    pub fn end_frame(&mut self, delta_time: Duration) -> bool {
+        match self {
+            Self::Idle => false,
+            Self::Recording(recording) => {
+                recording.push_frame(delta_time);
+                false
+            }
+            Self::Replaying { recording, frame, .. } => {
+                *frame += 1;
+                if *frame >= recording.frame_count() {
+                    *self = Self::Idle;
+                    true
+                } else {
+                    false
+                }
+            }
+        }
+    }
+
+    pub fn take(&mut self) -> Option<InputRecording> {
+        match std::mem::replace(self, Self::Idle) {
+            Self::Idle => None,
+            Self::Recording(recording) => Some(recording),
+            Self::Replaying { recording, .. } => Some(recording),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InputRecording, Replay};
+    use crate::event::{Event, KeyAction, TouchPhase};
+    use crate::keyboard::KeyCode;
+    use crate::mouse::MouseButton;
+    use crate::gamepad::{GamepadAxis, GamepadId};
+    use crate::math::Vector;
+    use std::time::Duration;
+
+    #[test]
+    fn test_bytes() {
+        let mut recording = InputRecording::new(42, Some(Duration::from_millis(10)));
+        assert!(recording.push_event(Event::KeyboardInput { key: KeyCode::Space, action: KeyAction::Down, repeated: false }));
+        assert!(!recording.push_event(Event::WindowClose));
+        recording.push_frame(Duration::from_millis(16));
+        recording.push_event(Event::KeyboardInput { key: KeyCode::Other(99), action: KeyAction::Up, repeated: false });
+        recording.push_event(Event::MouseInput { button: MouseButton::Right, action: KeyAction::Down });
+        recording.push_event(Event::Touch { id: 3, phase: TouchPhase::Move, position: Vector::new(1.5, -2.0) });
+        recording.push_event(Event::GamepadAxisChange { id: GamepadId::from(1), axis: GamepadAxis::LeftStickY, value: -0.5 });
+        recording.push_event(Event::ReceiveChar('好'));
+        recording.push_event(Event::ImePreedit { text: "にほ".to_owned(), cursor: Some((0, 6)) });
+        recording.push_event(Event::ImeCommit("日本".to_owned()));
+        recording.push_frame(Duration::from_secs(10));
+        let bytes = recording.to_bytes().unwrap();
+        let decoded = InputRecording::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, recording);
+        assert_eq!(decoded.events()[1].frame, 1);
+        assert_eq!(decoded.frame_delta(1), Some(Duration::from_secs(10)));
+        let mut unencodable = InputRecording::new(1, None);
+        unencodable.events.push(super::RecordedEvent { frame: 0, event: Event::WindowClose });
+        assert!(unencodable.to_bytes().is_err());
+        assert!(InputRecording::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(InputRecording::from_bytes(b"nope").is_err());
+    }
+
+    #[test]
+    fn test_replay() {
+        let mut recording = InputRecording::new(7, None);
+        recording.push_event(Event::MouseEnterWindow);
+        recording.push_frame(Duration::from_millis(10));
+        recording.push_frame(Duration::from_millis(20));
+        recording.push_event(Event::MouseLeaveWindow);
+        recording.push_frame(Duration::from_millis(30));
+        let mut replay = Replay::Replaying { recording, frame: 0, event_index: 0 };
+        assert_eq!(replay.seed(), Some(7));
+        assert_eq!(replay.take_frame_events(), vec![Event::MouseEnterWindow]);
+        assert_eq!(replay.frame_delta(), Some(Duration::from_millis(10)));
+        assert!(!replay.end_frame(Duration::from_millis(1)));
+        assert!(replay.take_frame_events().is_empty());
+        assert!(!replay.end_frame(Duration::from_millis(1)));
+        assert_eq!(replay.take_frame_events(), vec![Event::MouseLeaveWindow]);
+        assert_eq!(replay.frame_delta(), Some(Duration::from_millis(30)));
+        assert!(replay.end_frame(Duration::from_millis(1)));
+        assert!(!replay.is_replaying());
+    }
+}
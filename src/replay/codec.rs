@@ -0,0 +1,412 @@
+use crate::event::{Event, KeyAction, TouchPhase};
+use crate::keyboard::{KeyCode, ModifiersState};
+use crate::mouse::MouseButton;
+use crate::gamepad::{GamepadButton, GamepadAxis, GamepadId};
+use crate::math::Vector;
+use std::io::{self, Read};
+
+pub(crate) fn write_u8(bytes: &mut Vec<u8>, value: u8) {
+    bytes.push(value);
+}
+
+pub(crate) fn write_u32(bytes: &mut Vec<u8>, value: u32) {
+    bytes.extend_from_slice(&value.to_le_bytes());
+}
+
+pub(crate) fn write_u64(bytes: &mut Vec<u8>, value: u64) {
+    bytes.extend_from_slice(&value.to_le_bytes());
+}
+
+fn write_f32(bytes: &mut Vec<u8>, value: f32) {
+    bytes.extend_from_slice(&value.to_le_bytes());
+}
+
+fn write_vector(bytes: &mut Vec<u8>, vector: Vector) {
+    write_f32(bytes, vector.x);
+    write_f32(bytes, vector.y);
+}
+
+pub(crate) fn read_u8(reader: &mut impl Read) -> io::Result<u8> {
+    let mut bytes = [0; 1];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes[0])
+}
+
+pub(crate) fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+pub(crate) fn read_u64(reader: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn read_f32(reader: &mut impl Read) -> io::Result<f32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(f32::from_le_bytes(bytes))
+}
+
+fn read_vector(reader: &mut impl Read) -> io::Result<Vector> {
+    let x = read_f32(reader)?;
+    let y = read_f32(reader)?;
+    Ok(Vector::new(x, y))
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn write_variant<T: PartialEq>(bytes: &mut Vec<u8>, variants: &[T], value: &T, other: Option<u32>) -> bool {
+    match other {
+        Some(other) => {
+            write_u32(bytes, 0);
+            write_u32(bytes, other);
+        }
+        None => match variants.iter().position(|variant| variant == value) {
+            Some(index) => write_u32(bytes, index as u32 + 1),
+            None => return false,
+        },
+    }
+    true
+}
+
+fn read_variant<T: Copy>(reader: &mut impl Read, variants: &[T], other: impl FnOnce(u32) -> T) -> io::Result<T> {
+    match read_u32(reader)? {
+        0 => Ok(other(read_u32(reader)?)),
+        index => variants.get(index as usize - 1)
+            .copied()
+            .ok_or_else(|| invalid_data("unknown variant in input recording")),
+    }
+}
+
+fn write_key(bytes: &mut Vec<u8>, key: KeyCode) -> bool {
+    let other = match key {
+        KeyCode::Other(code) => Some(code),
+        _ => None,
+    };
+    write_variant(bytes, KeyCode::VARIANTS, &key, other)
+}
+
+fn write_mouse_button(bytes: &mut Vec<u8>, button: MouseButton) -> bool {
+    let other = match button {
+        MouseButton::Other(code) => Some(code),
+        _ => None,
+    };
+    write_variant(bytes, MouseButton::VARIANTS, &button, other)
+}
+
+fn write_gamepad_button(bytes: &mut Vec<u8>, button: GamepadButton) -> bool {
+    let other = match button {
+        GamepadButton::Other(code) => Some(code),
+        _ => None,
+    };
+    write_variant(bytes, GamepadButton::VARIANTS, &button, other)
+}
+
+fn write_gamepad_axis(bytes: &mut Vec<u8>, axis: GamepadAxis) -> bool {
+    let other = match axis {
+        GamepadAxis::Other(code) => Some(code),
+        _ => None,
+    };
+    write_variant(bytes, GamepadAxis::VARIANTS, &axis, other)
+}
+
+fn write_action(bytes: &mut Vec<u8>, action: KeyAction) {
+    write_u8(bytes, match action {
+        KeyAction::Down => 0,
+        KeyAction::Up => 1,
+    });
+}
+
+fn read_action(reader: &mut impl Read) -> io::Result<KeyAction> {
+    match read_u8(reader)? {
+        0 => Ok(KeyAction::Down),
+        1 => Ok(KeyAction::Up),
+        _ => Err(invalid_data("unknown key action in input recording")),
+    }
+}
+
+fn write_phase(bytes: &mut Vec<u8>, phase: TouchPhase) {
+    write_u8(bytes, match phase {
+        TouchPhase::Start => 0,
+        TouchPhase::Move => 1,
+        TouchPhase::End => 2,
+        TouchPhase::Cancel => 3,
+    });
+}
+
+fn read_phase(reader: &mut impl Read) -> io::Result<TouchPhase> {
+    match read_u8(reader)? {
+        0 => Ok(TouchPhase::Start),
+        1 => Ok(TouchPhase::Move),
+        2 => Ok(TouchPhase::End),
+        3 => Ok(TouchPhase::Cancel),
+        _ => Err(invalid_data("unknown touch phase in input recording")),
+    }
+}
+
+fn write_str(bytes: &mut Vec<u8>, string: &str) {
+    write_u32(bytes, string.len() as u32);
+    bytes.extend_from_slice(string.as_bytes());
+}
+
+fn read_string(reader: &mut impl Read) -> io::Result<String> {
+    let len = read_u32(reader)? as u64;
+    let mut bytes = Vec::new();
+    reader.take(len).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 != len {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "truncated string in input recording"));
+    }
+    String::from_utf8(bytes).map_err(|_| invalid_data("invalid string in input recording"))
+}
+
+fn write_gamepad_id(bytes: &mut Vec<u8>, id: GamepadId) {
+    write_u64(bytes, usize::from(id) as u64);
+}
+
+fn read_gamepad_id(reader: &mut impl Read) -> io::Result<GamepadId> {
+    Ok(GamepadId::from(read_u64(reader)? as usize))
+}
+
+pub(crate) fn write_event(bytes: &mut Vec<u8>, event: &Event) -> bool {
+    match event {
+        Event::ReceiveChar(char) => {
+            write_u8(bytes, 1);
+            write_u32(bytes, *char as u32);
+        }
+        Event::KeyboardInput { key, action, repeated } => {
+            write_u8(bytes, 2);
+            if !write_key(bytes, *key) {
+                return false;
+            }
+            write_action(bytes, *action);
+            write_u8(bytes, *repeated as u8);
+        }
+        Event::ModifiersChange(state) => {
+            write_u8(bytes, 3);
+            write_u8(bytes, state.shift as u8 | (state.ctrl as u8) << 1 | (state.alt as u8) << 2 | (state.logo as u8) << 3);
+        }
+        Event::MouseMove(position) => {
+            write_u8(bytes, 4);
+            write_vector(bytes, *position);
+        }
+        Event::MouseEnterWindow => write_u8(bytes, 5),
+        Event::MouseLeaveWindow => write_u8(bytes, 6),
+        Event::MouseWheelScroll(delta) => {
+            write_u8(bytes, 7);
+            write_vector(bytes, *delta);
+        }
+        Event::MouseInput { button, action } => {
+            write_u8(bytes, 8);
+            if !write_mouse_button(bytes, *button) {
+                return false;
+            }
+            write_action(bytes, *action);
+        }
+        Event::Touch { id, phase, position } => {
+            write_u8(bytes, 9);
+            write_u64(bytes, *id);
+            write_phase(bytes, *phase);
+            write_vector(bytes, *position);
+        }
+        Event::TouchpadScroll { delta, phase } => {
+            write_u8(bytes, 10);
+            write_vector(bytes, *delta);
+            write_phase(bytes, *phase);
+        }
+        Event::TouchpadPress { pressure, click_stage } => {
+            write_u8(bytes, 11);
+            write_f32(bytes, *pressure);
+            write_u64(bytes, *click_stage as u64);
+        }
+        Event::GamepadConnect(id) => {
+            write_u8(bytes, 12);
+            write_gamepad_id(bytes, *id);
+        }
+        Event::GamepadDisconnect(id) => {
+            write_u8(bytes, 13);
+            write_gamepad_id(bytes, *id);
+        }
+        Event::GamepadButtonInput { id, button, action } => {
+            write_u8(bytes, 14);
+            write_gamepad_id(bytes, *id);
+            if !write_gamepad_button(bytes, *button) {
+                return false;
+            }
+            write_action(bytes, *action);
+        }
+        Event::GamepadButtonChange { id, button, value } => {
+            write_u8(bytes, 15);
+            write_gamepad_id(bytes, *id);
+            if !write_gamepad_button(bytes, *button) {
+                return false;
+            }
+            write_f32(bytes, *value);
+        }
+        Event::GamepadAxisChange { id, axis, value } => {
+            write_u8(bytes, 16);
+            write_gamepad_id(bytes, *id);
+            if !write_gamepad_axis(bytes, *axis) {
+                return false;
+            }
+            write_f32(bytes, *value);
+        }
+        Event::ImeEnable => write_u8(bytes, 17),
+        Event::ImePreedit { text, cursor } => {
+            write_u8(bytes, 18);
+            write_str(bytes, text);
+            match cursor {
+                Some((start, end)) => {
+                    write_u8(bytes, 1);
+                    write_u64(bytes, *start as u64);
+                    write_u64(bytes, *end as u64);
+                }
+                None => write_u8(bytes, 0),
+            }
+        }
+        Event::ImeCommit(text) => {
+            write_u8(bytes, 19);
+            write_str(bytes, text);
+        }
+        Event::ImeDisable => write_u8(bytes, 20),
+        _ => return false,
+    }
+    true
+}
+
+pub(crate) fn read_event(reader: &mut impl Read) -> io::Result<Event> {
+    let event = match read_u8(reader)? {
+        1 => {
+            let char = std::char::from_u32(read_u32(reader)?)
+                .ok_or_else(|| invalid_data("invalid char in input recording"))?;
+            Event::ReceiveChar(char)
+        }
+        2 => {
+            let key = read_variant(reader, KeyCode::VARIANTS, KeyCode::Other)?;
+            let action = read_action(reader)?;
+            let repeated = read_u8(reader)? != 0;
+            Event::KeyboardInput { key, action, repeated }
+        }
+        3 => {
+            let bits = read_u8(reader)?;
+            Event::ModifiersChange(ModifiersState {
+                shift: bits & 1 != 0,
+                ctrl: bits & 2 != 0,
+                alt: bits & 4 != 0,
+                logo: bits & 8 != 0,
+            })
+        }
+        4 => Event::MouseMove(read_vector(reader)?),
+        5 => Event::MouseEnterWindow,
+        6 => Event::MouseLeaveWindow,
+        7 => Event::MouseWheelScroll(read_vector(reader)?),
+        8 => {
+            let button = read_variant(reader, MouseButton::VARIANTS, MouseButton::Other)?;
+            let action = read_action(reader)?;
+            Event::MouseInput { button, action }
+        }
+        9 => {
+            let id = read_u64(reader)?;
+            let phase = read_phase(reader)?;
+            let position = read_vector(reader)?;
+            Event::Touch { id, phase, position }
+        }
+        10 => {
+            let delta = read_vector(reader)?;
+            let phase = read_phase(reader)?;
+            Event::TouchpadScroll { delta, phase }
+        }
+        11 => {
+            let pressure = read_f32(reader)?;
+            let click_stage = read_u64(reader)? as i64;
+            Event::TouchpadPress { pressure, click_stage }
+        }
+        12 => Event::GamepadConnect(read_gamepad_id(reader)?),
+        13 => Event::GamepadDisconnect(read_gamepad_id(reader)?),
+        14 => {
+            let id = read_gamepad_id(reader)?;
+            let button = read_variant(reader, GamepadButton::VARIANTS, GamepadButton::Other)?;
+            let action = read_action(reader)?;
+            Event::GamepadButtonInput { id, button, action }
+        }
+        15 => {
+            let id = read_gamepad_id(reader)?;
+            let button = read_variant(reader, GamepadButton::VARIANTS, GamepadButton::Other)?;
+            let value = read_f32(reader)?;
+            Event::GamepadButtonChange { id, button, value }
+        }
+        16 => {
+            let id = read_gamepad_id(reader)?;
+            let axis = read_variant(reader, GamepadAxis::VARIANTS, GamepadAxis::Other)?;
+            let value = read_f32(reader)?;
+            Event::GamepadAxisChange { id, axis, value }
+        }
+        17 => Event::ImeEnable,
+        18 => {
+            let text = read_string(reader)?;
+            let cursor = match read_u8(reader)? {
+                0 => None,
+                _ => {
+                    let start = read_u64(reader)? as usize;
+                    let end = read_u64(reader)? as usize;
+                    Some((start, end))
+                }
+            };
+            Event::ImePreedit { text, cursor }
+        }
+        19 => Event::ImeCommit(read_string(reader)?),
+        20 => Event::ImeDisable,
+        _ => return Err(invalid_data("unknown event in input recording")),
+    };
+    Ok(event)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{write_variant, read_variant, write_u32, read_string};
+    use crate::keyboard::KeyCode;
+    use crate::mouse::MouseButton;
+    use crate::gamepad::{GamepadButton, GamepadAxis};
+    use std::fmt::Debug;
+
+    fn enum_len(source: &str, name: &str) -> usize {
+        let start = source.find(&format!("pub enum {} {{", name)).unwrap();
+        let body = &source[start..];
+        let body = &body[..body.find("\n}").unwrap()];
+        body.lines()
+            .skip(1)
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with("//") && !line.starts_with("Other("))
+            .count()
+    }
+
+    fn assert_round_trip<T: Copy + PartialEq + Debug>(variants: &[T], other: fn(u32) -> T, source: &str, name: &str) {
+        assert_eq!(variants.len(), enum_len(source, name), "`{}::VARIANTS` is out of date", name);
+        for variant in variants {
+            let mut bytes = Vec::new();
+            assert!(write_variant(&mut bytes, variants, variant, None));
+            assert_eq!(read_variant(&mut bytes.as_slice(), variants, other).unwrap(), *variant);
+        }
+        assert!(!write_variant(&mut Vec::new(), &variants[1..], &variants[0], None));
+    }
+
+    #[test]
+    fn test_variants() {
+        assert_round_trip(KeyCode::VARIANTS, KeyCode::Other, include_str!("../keyboard/code.rs"), "KeyCode");
+        assert_round_trip(MouseButton::VARIANTS, MouseButton::Other, include_str!("../mouse/button.rs"), "MouseButton");
+        assert_round_trip(GamepadButton::VARIANTS, GamepadButton::Other, include_str!("../gamepad/button.rs"), "GamepadButton");
+        assert_round_trip(GamepadAxis::VARIANTS, GamepadAxis::Other, include_str!("../gamepad/axis.rs"), "GamepadAxis");
+    }
+
+    #[test]
+    fn test_truncated_string() {
+        let mut bytes = Vec::new();
+        write_u32(&mut bytes, u32::MAX);
+        bytes.extend_from_slice(b"abc");
+        assert!(read_string(&mut bytes.as_slice()).is_err());
+    }
+}
@@ -0,0 +1,283 @@
+use crate::error::GameResult;
+use crate::engine::Engine;
+use crate::event::Event;
+use crate::game::Game;
+
+pub enum SceneTransition {
+    None,
+    Push(Box<dyn Scene>),
+    Pop,
+    Replace(Box<dyn Scene>),
+    Reset(Box<dyn Scene>),
+}
+
+impl SceneTransition {
+    pub fn push(scene: impl Scene + 'static) -> Self {
+        Self::Push(Box::new(scene))
+    }
+
+    pub fn replace(scene: impl Scene + 'static) -> Self {
+        Self::Replace(Box::new(scene))
+    }
+
+    pub fn reset(scene: impl Scene + 'static) -> Self {
+        Self::Reset(Box::new(scene))
+    }
+}
+
+pub trait Scene {
+    fn update(&mut self, engine: &mut Engine) -> GameResult<SceneTransition>;
+
+    fn render(&mut self, engine: &mut Engine) -> GameResult;
+
+    fn event(&mut self, _engine: &mut Engine, _event: Event) -> GameResult<(bool, SceneTransition)> {
+        Ok((false, SceneTransition::None))
+    }
+
+    fn enter(&mut self, _engine: &mut Engine) -> GameResult {
+        Ok(())
+    }
+
+    fn exit(&mut self, _engine: &mut Engine) -> GameResult {
+        Ok(())
+    }
+
+    fn is_transparent(&self) -> bool {
+        false
+    }
+
+    fn is_blocking_input(&self) -> bool {
+        true
+    }
+}
+
+pub struct SceneStack {
+    scenes: Vec<Box<dyn Scene>>,
+    started: bool,
+}
+
+impl SceneStack {
+    pub fn new(scene: impl Scene + 'static) -> Self {
+        Self {
+            scenes: vec![Box::new(scene)],
+            started: false,
+        }
+    }
+
+    fn start(&mut self, engine: &mut Engine) -> GameResult {
+        if !self.started {
+            self.started = true;
+            if let Some(scene) = self.scenes.last_mut() {
+                scene.enter(engine)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.scenes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scenes.is_empty()
+    }
+
+    pub fn push(&mut self, engine: &mut Engine, mut scene: Box<dyn Scene>) -> GameResult {
+        self.start(engine)?;
+        engine.assets().mark_scene_change();
+        scene.enter(engine)?;
+        self.scenes.push(scene);
+        Ok(())
+    }
+
+    pub fn pop(&mut self, engine: &mut Engine) -> GameResult<Option<Box<dyn Scene>>> {
+        self.start(engine)?;
+        let mut scene = match self.scenes.pop() {
+            Some(scene) => scene,
+            None => return Ok(None),
+        };
+        engine.assets().mark_scene_change();
+        scene.exit(engine)?;
+        Ok(Some(scene))
+    }
+
+    pub fn replace(&mut self, engine: &mut Engine, scene: Box<dyn Scene>) -> GameResult<Option<Box<dyn Scene>>> {
+        let previous = self.pop(engine)?;
+        self.push(engine, scene)?;
+        Ok(previous)
+    }
+
+    pub fn reset(&mut self, engine: &mut Engine, scene: Box<dyn Scene>) -> GameResult {
+        while self.pop(engine)?.is_some() {}
+        self.push(engine, scene)
+    }
+
+    pub fn apply(&mut self, engine: &mut Engine, transition: SceneTransition) -> GameResult {
+        match transition {
+            SceneTransition::None => (),
+            SceneTransition::Push(scene) => self.push(engine, scene)?,
+            SceneTransition::Pop => {
+                self.pop(engine)?;
+            }
+            SceneTransition::Replace(scene) => {
+                self.replace(engine, scene)?;
+            }
+            SceneTransition::Reset(scene) => self.reset(engine, scene)?,
+        }
+        if self.scenes.is_empty() {
+            engine.quit();
+        }
+        Ok(())
+    }
+}
+
+impl Game for SceneStack {
+    fn update(&mut self, engine: &mut Engine) -> GameResult {
+        self.start(engine)?;
+        let transition = match self.scenes.last_mut() {
+            Some(scene) => scene.update(engine)?,
+            None => SceneTransition::None,
+        };
+        self.apply(engine, transition)
+    }
+
+    fn render(&mut self, engine: &mut Engine) -> GameResult {
+        self.start(engine)?;
+        let first = self.scenes.iter()
+            .rposition(|scene| !scene.is_transparent())
+            .unwrap_or(0);
+        for scene in self.scenes[first..].iter_mut() {
+            scene.render(engine)?;
+        }
+        Ok(())
+    }
+
+    fn event(&mut self, engine: &mut Engine, event: Event) -> GameResult<bool> {
+        self.start(engine)?;
+        let mut prevent = false;
+        for scene in self.scenes.iter_mut().rev() {
+            let (scene_prevent, transition) = scene.event(engine, event.clone())?;
+            prevent |= scene_prevent;
+            if !matches!(transition, SceneTransition::None) {
+                self.apply(engine, transition)?;
+                break;
+            }
+            if scene.is_blocking_input() {
+                break;
+            }
+        }
+        Ok(prevent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Scene, SceneStack, SceneTransition};
+    use crate::error::GameResult;
+    use crate::engine::{Engine, EngineBuilder};
+    use crate::event::Event;
+    use crate::game::Game;
+    use crate::assets::Assets;
+    use std::rc::Rc;
+    use std::cell::RefCell;
+
+    struct LogScene {
+        name: &'static str,
+        log: Rc<RefCell<Vec<String>>>,
+        transparent: bool,
+        blocking_input: bool,
+        event_transition: Option<SceneTransition>,
+    }
+
+    impl LogScene {
+        fn new(name: &'static str, log: &Rc<RefCell<Vec<String>>>) -> Self {
+            Self { name, log: log.clone(), transparent: false, blocking_input: true, event_transition: None }
+        }
+
+        fn push_log(&self, action: &str) {
+            self.log.borrow_mut().push(format!("{}:{}", self.name, action));
+        }
+    }
+
+    impl Scene for LogScene {
+        fn update(&mut self, _engine: &mut Engine) -> GameResult<SceneTransition> {
+            self.push_log("update");
+            Ok(SceneTransition::None)
+        }
+
+        fn render(&mut self, _engine: &mut Engine) -> GameResult {
+            self.push_log("render");
+            Ok(())
+        }
+
+        fn event(&mut self, _engine: &mut Engine, _event: Event) -> GameResult<(bool, SceneTransition)> {
+            self.push_log("event");
+            Ok((false, self.event_transition.take().unwrap_or(SceneTransition::None)))
+        }
+
+        fn enter(&mut self, _engine: &mut Engine) -> GameResult {
+            self.push_log("enter");
+            Ok(())
+        }
+
+        fn exit(&mut self, _engine: &mut Engine) -> GameResult {
+            self.push_log("exit");
+            Ok(())
+        }
+
+        fn is_transparent(&self) -> bool {
+            self.transparent
+        }
+
+        fn is_blocking_input(&self) -> bool {
+            self.blocking_input
+        }
+    }
+
+    fn take_log(log: &Rc<RefCell<Vec<String>>>) -> Vec<String> {
+        std::mem::take(&mut *log.borrow_mut())
+    }
+
+    #[test]
+    fn test_transitions() {
+        let mut engine = EngineBuilder::new().headless(true).build().unwrap();
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut stack = SceneStack::new(LogScene::new("a", &log));
+        stack.update(&mut engine).unwrap();
+        assert_eq!(take_log(&log), ["a:enter", "a:update"]);
+        Assets::load_with(&mut engine, "asset", |_, _| Ok(1u32)).unwrap();
+        assert!(engine.assets().usage("asset").unwrap().used_in_current_scene);
+        stack.push(&mut engine, Box::new(LogScene::new("b", &log))).unwrap();
+        assert!(!engine.assets().usage("asset").unwrap().used_in_current_scene);
+        stack.replace(&mut engine, Box::new(LogScene::new("c", &log))).unwrap();
+        assert_eq!(take_log(&log), ["b:enter", "b:exit", "c:enter"]);
+        stack.reset(&mut engine, Box::new(LogScene::new("d", &log))).unwrap();
+        assert_eq!(take_log(&log), ["c:exit", "a:exit", "d:enter"]);
+        assert_eq!(stack.len(), 1);
+        stack.apply(&mut engine, SceneTransition::Pop).unwrap();
+        assert_eq!(take_log(&log), ["d:exit"]);
+        assert!(stack.is_empty());
+        assert!(!engine.is_running());
+    }
+
+    #[test]
+    fn test_render_and_input() {
+        let mut engine = EngineBuilder::new().headless(true).build().unwrap();
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut stack = SceneStack::new(LogScene::new("a", &log));
+        stack.push(&mut engine, Box::new(LogScene::new("b", &log))).unwrap();
+        stack.push(&mut engine, Box::new(LogScene { transparent: true, blocking_input: false, ..LogScene::new("c", &log) })).unwrap();
+        take_log(&log);
+        stack.render(&mut engine).unwrap();
+        assert_eq!(take_log(&log), ["b:render", "c:render"]);
+        stack.event(&mut engine, Event::MouseEnterWindow).unwrap();
+        assert_eq!(take_log(&log), ["c:event", "b:event"]);
+
+        stack.apply(&mut engine, SceneTransition::Pop).unwrap();
+        stack.push(&mut engine, Box::new(LogScene { event_transition: Some(SceneTransition::Pop), ..LogScene::new("d", &log) })).unwrap();
+        take_log(&log);
+        stack.event(&mut engine, Event::MouseEnterWindow).unwrap();
+        assert_eq!(take_log(&log), ["d:event", "d:exit"]);
+        assert_eq!(stack.len(), 2);
+    }
+}
@@ -0,0 +1,606 @@
+use crate::event::{Event, KeyAction};
+use crate::keyboard::{KeyCode, ModifiersState};
+use crate::graphics::{Graphics, Font, Color, Vertex, TextureRef, MeshDrawParams, TextDrawParams};
+use crate::math::{Position, Vector, Region, Transform};
+use std::ops::Range;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Preedit {
+    text: String,
+    cursor: Option<(usize, usize)>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum CharClass {
+    Space,
+    Word,
+    Punctuation,
+}
+
+fn char_class(c: char) -> CharClass {
+    if c.is_whitespace() {
+        CharClass::Space
+    } else if c.is_alphanumeric() || c == '_' {
+        CharClass::Word
+    } else {
+        CharClass::Punctuation
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TextEdit {
+    text: String,
+    caret: usize,
+    anchor: Option<usize>,
+    preedit: Option<Preedit>,
+    pending_commit: String,
+    multiline: bool,
+    max_length: Option<usize>,
+}
+
+impl TextEdit {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn set_text(&mut self, text: impl Into<String>) {
+        self.text = self.filter(&text.into());
+        if let Some(max_length) = self.max_length {
+            if let Some((index, _)) = self.text.char_indices().nth(max_length) {
+                self.text.truncate(index);
+            }
+        }
+        self.caret = self.text.len();
+        self.anchor = None;
+        self.preedit = None;
+    }
+
+    pub fn clear(&mut self) {
+        self.set_text(String::new());
+    }
+
+    pub fn is_multiline(&self) -> bool {
+        self.multiline
+    }
+
+    pub fn set_multiline(&mut self, multiline: bool) {
+        self.multiline = multiline;
+        if !multiline {
+            self.set_text(self.text.clone());
+        }
+    }
+
+    pub fn max_length(&self) -> Option<usize> {
+        self.max_length
+    }
+
+    pub fn set_max_length(&mut self, max_length: Option<usize>) {
+        self.max_length = max_length;
+        self.set_text(self.text.clone());
+    }
+
+    pub fn caret(&self) -> usize {
+        self.caret
+    }
+
+    pub fn set_caret(&mut self, caret: usize) {
+        self.caret = self.floor_boundary(caret);
+        self.anchor = None;
+    }
+
+    pub fn selection(&self) -> Option<Range<usize>> {
+        match self.anchor {
+            Some(anchor) if anchor != self.caret => Some(anchor.min(self.caret)..anchor.max(self.caret)),
+            _ => None,
+        }
+    }
+
+    pub fn selected_text(&self) -> &str {
+        match self.selection() {
+            Some(selection) => &self.text[selection],
+            None => "",
+        }
+    }
+
+    pub fn select(&mut self, selection: Range<usize>) {
+        self.anchor = Some(self.floor_boundary(selection.start));
+        self.caret = self.floor_boundary(selection.end);
+    }
+
+    pub fn select_all(&mut self) {
+        self.select(0..self.text.len());
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.anchor = None;
+    }
+
+    pub fn preedit(&self) -> Option<&str> {
+        self.preedit.as_ref().map(|preedit| preedit.text.as_str())
+    }
+
+    pub fn is_composing(&self) -> bool {
+        self.preedit.is_some()
+    }
+
+    fn filter(&self, text: &str) -> String {
+        text.chars()
+            .filter(|c| if *c == '\n' { self.multiline } else { !c.is_control() })
+            .collect()
+    }
+
+    fn floor_boundary(&self, mut index: usize) -> usize {
+        index = index.min(self.text.len());
+        while !self.text.is_char_boundary(index) {
+            index -= 1;
+        }
+        index
+    }
+
+    fn prev_boundary(&self, index: usize) -> usize {
+        self.text[..index].char_indices()
+            .next_back()
+            .map(|(index, _)| index)
+            .unwrap_or(0)
+    }
+
+    fn next_boundary(&self, index: usize) -> usize {
+        self.text[index..].chars()
+            .next()
+            .map(|c| index + c.len_utf8())
+            .unwrap_or(index)
+    }
+
+    fn prev_word_boundary(&self, index: usize) -> usize {
+        let mut chars = self.text[..index].char_indices().rev().peekable();
+        while let Some((_, c)) = chars.peek() {
+            if char_class(*c) != CharClass::Space {
+                break;
+            }
+            chars.next();
+        }
+        let class = match chars.peek() {
+            Some((_, c)) => char_class(*c),
+            None => return 0,
+        };
+        let mut boundary = index;
+        for (index, c) in chars {
+            if char_class(c) != class {
+                break;
+            }
+            boundary = index;
+        }
+        boundary.min(index)
+    }
+
+    fn next_word_boundary(&self, index: usize) -> usize {
+        let mut chars = self.text[index..].char_indices().peekable();
+        if let Some((_, c)) = chars.peek() {
+            let class = char_class(*c);
+            if class != CharClass::Space {
+                while let Some((_, c)) = chars.peek() {
+                    if char_class(*c) != class {
+                        break;
+                    }
+                    chars.next();
+                }
+            }
+        }
+        while let Some((_, c)) = chars.peek() {
+            if char_class(*c) != CharClass::Space || *c == '\n' && self.multiline {
+                break;
+            }
+            chars.next();
+        }
+        chars.peek().map(|(offset, _)| index + offset).unwrap_or(self.text.len())
+    }
+
+    fn line_start(&self, index: usize) -> usize {
+        self.text[..index].rfind('\n').map(|index| index + 1).unwrap_or(0)
+    }
+
+    fn line_end(&self, index: usize) -> usize {
+        self.text[index..].find('\n').map(|offset| index + offset).unwrap_or(self.text.len())
+    }
+
+    fn vertical_target(&self, up: bool) -> usize {
+        let line_start = self.line_start(self.caret);
+        let column = self.text[line_start..self.caret].chars().count();
+        let target_start = if up {
+            if line_start == 0 {
+                return 0;
+            }
+            self.line_start(line_start - 1)
+        } else {
+            let line_end = self.line_end(self.caret);
+            if line_end == self.text.len() {
+                return self.text.len();
+            }
+            line_end + 1
+        };
+        let target_end = self.line_end(target_start);
+        self.text[target_start..target_end].char_indices()
+            .nth(column)
+            .map(|(offset, _)| target_start + offset)
+            .unwrap_or(target_end)
+    }
+
+    fn move_to(&mut self, caret: usize, select: bool) {
+        if select {
+            if self.anchor.is_none() {
+                self.anchor = Some(self.caret);
+            }
+        } else {
+            self.anchor = None;
+        }
+        self.caret = caret;
+    }
+
+    pub fn move_left(&mut self, word: bool, select: bool) {
+        if let (Some(selection), false) = (self.selection(), select) {
+            self.move_to(selection.start, false);
+            return;
+        }
+        let caret = if word { self.prev_word_boundary(self.caret) } else { self.prev_boundary(self.caret) };
+        self.move_to(caret, select);
+    }
+
+    pub fn move_right(&mut self, word: bool, select: bool) {
+        if let (Some(selection), false) = (self.selection(), select) {
+            self.move_to(selection.end, false);
+            return;
+        }
+        let caret = if word { self.next_word_boundary(self.caret) } else { self.next_boundary(self.caret) };
+        self.move_to(caret, select);
+    }
+
+    pub fn move_up(&mut self, select: bool) {
+        let caret = self.vertical_target(true);
+        self.move_to(caret, select);
+    }
+
+    pub fn move_down(&mut self, select: bool) {
+        let caret = self.vertical_target(false);
+        self.move_to(caret, select);
+    }
+
+    pub fn move_home(&mut self, document: bool, select: bool) {
+        let caret = if document { 0 } else { self.line_start(self.caret) };
+        self.move_to(caret, select);
+    }
+
+    pub fn move_end(&mut self, document: bool, select: bool) {
+        let caret = if document { self.text.len() } else { self.line_end(self.caret) };
+        self.move_to(caret, select);
+    }
+
+    pub fn delete_selection(&mut self) -> bool {
+        match self.selection() {
+            Some(selection) => {
+                self.caret = selection.start;
+                self.text.replace_range(selection, "");
+                self.anchor = None;
+                true
+            }
+            None => {
+                self.anchor = None;
+                false
+            }
+        }
+    }
+
+    pub fn insert(&mut self, text: &str) -> bool {
+        let mut text = self.filter(text);
+        self.delete_selection();
+        if let Some(max_length) = self.max_length {
+            let available = max_length.saturating_sub(self.text.chars().count());
+            if let Some((index, _)) = text.char_indices().nth(available) {
+                text.truncate(index);
+            }
+        }
+        if text.is_empty() {
+            return false;
+        }
+        self.text.insert_str(self.caret, &text);
+        self.caret += text.len();
+        true
+    }
+
+    pub fn delete_backward(&mut self, word: bool) -> bool {
+        if self.delete_selection() {
+            return true;
+        }
+        let start = if word { self.prev_word_boundary(self.caret) } else { self.prev_boundary(self.caret) };
+        if start == self.caret {
+            return false;
+        }
+        self.text.replace_range(start..self.caret, "");
+        self.caret = start;
+        true
+    }
+
+    pub fn delete_forward(&mut self, word: bool) -> bool {
+        if self.delete_selection() {
+            return true;
+        }
+        let end = if word { self.next_word_boundary(self.caret) } else { self.next_boundary(self.caret) };
+        if end == self.caret {
+            return false;
+        }
+        self.text.replace_range(self.caret..end, "");
+        true
+    }
+
+    pub fn handle_event(&mut self, event: &Event, modifiers: ModifiersState) -> bool {
+        if !matches!(event, Event::ReceiveChar(_) | Event::ImePreedit { .. }) {
+            self.pending_commit.clear();
+        }
+        match event {
+            Event::ReceiveChar(c) => {
+                if self.pending_commit.starts_with(*c) {
+                    self.pending_commit.drain(..c.len_utf8());
+                    return true;
+                }
+                self.pending_commit.clear();
+                if c.is_control() || modifiers.ctrl || modifiers.logo || self.preedit.is_some() {
+                    return false;
+                }
+                self.insert(c.encode_utf8(&mut [0; 4]))
+            }
+            Event::ImePreedit { text, cursor } => {
+                self.preedit = if text.is_empty() {
+                    None
+                } else {
+                    Some(Preedit { text: text.clone(), cursor: *cursor })
+                };
+                true
+            }
+            Event::ImeCommit(text) => {
+                self.preedit = None;
+                self.insert(text);
+                self.pending_commit = text.clone();
+                true
+            }
+            Event::ImeDisable => {
+                self.preedit = None;
+                false
+            }
+            Event::KeyboardInput { key, action: KeyAction::Down, .. } => {
+                if self.preedit.is_some() {
+                    return false;
+                }
+                let word = modifiers.ctrl || modifiers.alt;
+                let select = modifiers.shift;
+                match key {
+                    KeyCode::Left => self.move_left(word, select),
+                    KeyCode::Right => self.move_right(word, select),
+                    KeyCode::Up if self.multiline => self.move_up(select),
+                    KeyCode::Down if self.multiline => self.move_down(select),
+                    KeyCode::Home => self.move_home(modifiers.ctrl, select),
+                    KeyCode::End => self.move_end(modifiers.ctrl, select),
+                    KeyCode::Backspace => {
+                        self.delete_backward(word);
+                    }
+                    KeyCode::Delete => {
+                        self.delete_forward(word);
+                    }
+                    KeyCode::Enter | KeyCode::NumpadEnter if self.multiline => {
+                        self.insert("\n");
+                    }
+                    KeyCode::A if modifiers.ctrl || modifiers.logo => self.select_all(),
+                    _ => return false,
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn draw(&self, graphics: &mut Graphics, font: &Font, params: impl Into<Option<TextEditDrawParams>>, transform: impl Into<Option<Transform>>) {
+        let params = params.into().unwrap_or_default();
+        let transform = transform.into().unwrap_or_default();
+        let text_params = TextDrawParams {
+            wrap_width: None,
+            wrap_height: None,
+            horizontal_gravity: None,
+            vertical_gravity: None,
+            ..params.text.clone()
+        };
+        let text_size = text_params.text_size.unwrap_or(16.0);
+        let line_metrics = font.line_metrics(text_size);
+        let char_spacing = text_params.char_spacing.unwrap_or(0.0);
+        let line_height = text_params.line_height.unwrap_or(line_metrics.height);
+        let line_spacing = text_params.line_spacing.unwrap_or(line_metrics.line_gap);
+        let origin = text_params.origin.unwrap_or_else(Position::zero);
+        let text_color = text_params.color.unwrap_or(Color::WHITE);
+
+        let (display, caret, highlight, underline) = match &self.preedit {
+            Some(preedit) => {
+                let mut display = String::with_capacity(self.text.len() + preedit.text.len());
+                display.push_str(&self.text[..self.caret]);
+                display.push_str(&preedit.text);
+                display.push_str(&self.text[self.caret..]);
+                let caret = self.caret + preedit.cursor.map(|(start, _)| start).unwrap_or(preedit.text.len());
+                (display, caret, None, Some(self.caret..self.caret + preedit.text.len()))
+            }
+            None => (self.text.clone(), self.caret, self.selection(), None),
+        };
+
+        let mut positions = Vec::with_capacity(display.len() + 1);
+        let mut position = Position::zero();
+        let mut line = 0;
+        for (index, c) in display.char_indices() {
+            positions.push((index, position, line));
+            if c == '\n' {
+                line += 1;
+                position.x = 0.0;
+                if position.y > 0.0 {
+                    position.y += line_spacing;
+                }
+                position.y += line_height;
+            } else if !c.is_control() {
+                if position.x > 0.0 {
+                    position.x += char_spacing;
+                }
+                position.x += font.glyph_metrics(c, text_size).advance_width;
+            }
+        }
+        positions.push((display.len(), position, line));
+        let position_of = |index: usize| positions.iter()
+            .find(|(offset, _, _)| *offset >= index)
+            .map(|(_, position, line)| (*position, *line))
+            .unwrap_or((position, line));
+
+        let draw_range = |graphics: &mut Graphics, range: Range<usize>, top: f32, height: f32, color: Color| {
+            let (mut start, mut start_line) = position_of(range.start);
+            for (offset, end, end_line) in positions.iter().filter(|(offset, _, _)| *offset > range.start && *offset <= range.end) {
+                if *end_line != start_line || *offset == range.end {
+                    let right = if *end_line != start_line {
+                        start.x.max(positions.iter()
+                            .filter(|(_, _, line)| *line == start_line)
+                            .map(|(_, position, _)| position.x)
+                            .fold(0.0, f32::max))
+                    } else {
+                        end.x
+                    };
+                    let region = Region::new(start.x - origin.x, start.y + top - origin.y, right - start.x, height);
+                    draw_rect(graphics, region, color, transform);
+                    start = *end;
+                    start_line = *end_line;
+                }
+            }
+        };
+
+        if let Some(highlight) = highlight {
+            draw_range(graphics, highlight, 0.0, line_height, params.selection_color.unwrap_or(Color::new(0.2, 0.4, 1.0, 0.5)));
+        }
+        graphics.draw_text(font, &display, text_params, transform);
+        if let Some(underline) = underline {
+            let thickness = params.caret_width.unwrap_or(1.0);
+            draw_range(graphics, underline, line_height - thickness, thickness, text_color);
+        }
+        if params.caret_visible.unwrap_or(true) {
+            let (position, _) = position_of(caret);
+            let caret_width = params.caret_width.unwrap_or(1.0);
+            let region = Region::new(position.x - origin.x, position.y - origin.y, caret_width, line_height);
+            draw_rect(graphics, region, params.caret_color.unwrap_or(text_color), transform);
+        }
+    }
+}
+
+fn draw_rect(graphics: &mut Graphics, region: Region, color: Color, transform: Transform) {
+    if region.width <= 0.0 || region.height <= 0.0 {
+        return;
+    }
+    let vertices = vec![
+        Vertex::new(region.top_left(), Vector::zero(), color),
+        Vertex::new(region.top_right(), Vector::zero(), color),
+        Vertex::new(region.bottom_left(), Vector::zero(), color),
+        Vertex::new(region.bottom_right(), Vector::zero(), color),
+    ];
+    let params = MeshDrawParams::default()
+        .vertices(vertices)
+        .elements(Some(vec![0, 2, 1, 1, 2, 3]));
+    graphics.draw_mesh(TextureRef::None, params, transform);
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TextEditDrawParams {
+    pub text: TextDrawParams,
+    pub caret_visible: Option<bool>,
+    pub caret_width: Option<f32>,
+    pub caret_color: Option<Color>,
+    pub selection_color: Option<Color>,
+}
+
+impl TextEditDrawParams {
+    pub fn text(mut self, text: TextDrawParams) -> Self {
+        self.text = text;
+        self
+    }
+
+    pub fn caret_visible(mut self, visible: bool) -> Self {
+        self.caret_visible = Some(visible);
+        self
+    }
+
+    pub fn caret_width(mut self, width: f32) -> Self {
+        self.caret_width = Some(width);
+        self
+    }
+
+    pub fn caret_color(mut self, color: impl Into<Color>) -> Self {
+        self.caret_color = Some(color.into());
+        self
+    }
+
+    pub fn selection_color(mut self, color: impl Into<Color>) -> Self {
+        self.selection_color = Some(color.into());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TextEdit;
+    use crate::event::{Event, KeyAction};
+    use crate::keyboard::{KeyCode, ModifiersState};
+
+    fn modifiers(shift: bool, ctrl: bool) -> ModifiersState {
+        ModifiersState { shift, ctrl, alt: false, logo: false }
+    }
+
+    fn key(key: KeyCode) -> Event {
+        Event::KeyboardInput { key, action: KeyAction::Down, repeated: false }
+    }
+
+    #[test]
+    fn test_editing() {
+        let mut edit = TextEdit::new();
+        for c in "hello wörld".chars() {
+            assert!(edit.handle_event(&Event::ReceiveChar(c), modifiers(false, false)));
+        }
+        assert_eq!(edit.text(), "hello wörld");
+        edit.handle_event(&key(KeyCode::Left), modifiers(true, true));
+        assert_eq!(edit.selected_text(), "wörld");
+        edit.handle_event(&key(KeyCode::Backspace), modifiers(false, false));
+        assert_eq!(edit.text(), "hello ");
+        edit.handle_event(&key(KeyCode::Backspace), modifiers(false, true));
+        assert_eq!(edit.text(), "");
+        edit.set_text("ab cd");
+        edit.handle_event(&key(KeyCode::Home), modifiers(false, false));
+        edit.handle_event(&key(KeyCode::Right), modifiers(false, true));
+        assert_eq!(edit.caret(), 3);
+        edit.handle_event(&key(KeyCode::Delete), modifiers(false, false));
+        assert_eq!(edit.text(), "ab d");
+        assert!(!edit.handle_event(&key(KeyCode::Enter), modifiers(false, false)));
+        edit.handle_event(&key(KeyCode::A), modifiers(false, true));
+        edit.insert("x\ny");
+        assert_eq!(edit.text(), "xy");
+        edit.set_max_length(Some(3));
+        edit.insert("zzz");
+        assert_eq!(edit.text(), "xyz");
+    }
+
+    #[test]
+    fn test_ime() {
+        let mut edit = TextEdit::new();
+        edit.set_multiline(true);
+        edit.set_text("a\nb");
+        edit.handle_event(&key(KeyCode::Up), modifiers(false, false));
+        assert_eq!(edit.caret(), 1);
+        edit.handle_event(&Event::ImePreedit { text: "にほ".to_owned(), cursor: Some((6, 6)) }, modifiers(false, false));
+        assert_eq!(edit.preedit(), Some("にほ"));
+        assert!(!edit.handle_event(&key(KeyCode::Left), modifiers(false, false)));
+        edit.handle_event(&Event::ImePreedit { text: String::new(), cursor: None }, modifiers(false, false));
+        edit.handle_event(&Event::ImeCommit("日本".to_owned()), modifiers(false, false));
+        edit.handle_event(&Event::ReceiveChar('日'), modifiers(false, false));
+        edit.handle_event(&Event::ReceiveChar('本'), modifiers(false, false));
+        assert_eq!(edit.text(), "a日本\nb");
+        assert!(!edit.is_composing());
+    }
+}
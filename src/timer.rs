@@ -1,10 +1,50 @@
+mod scheduler;
+
+pub use scheduler::TimerHandle;
+
+use scheduler::Scheduler;
 use crate::error::GameResult;
 use std::time::{Instant, Duration};
+use std::collections::VecDeque;
+
+fn fps_of(frame_time: Duration) -> f32 {
+    let secs = frame_time.as_secs_f32();
+    if secs > 0.0 {
+        1.0 / secs
+    } else {
+        0.0
+    }
+}
+
+fn duration_of(fps: f32) -> Option<Duration> {
+    if fps > 0.0 {
+        Some(Duration::try_from_secs_f32(1.0 / fps).unwrap_or(Duration::MAX))
+    } else {
+        None
+    }
+}
+
+fn duration_rem(duration: Duration, step: Duration) -> Duration {
+    let nanos = duration.as_nanos() % step.as_nanos();
+    Duration::from_nanos(nanos as u64)
+}
 
 pub struct Timer {
     frame_duration: Duration,
     last_frame_instant: Instant,
     delta_time: Duration,
+    fixed_update_duration: Option<Duration>,
+    max_update_steps: u32,
+    accumulator: Duration,
+    update_steps: u32,
+    time_scale: f32,
+    paused: bool,
+    frame_history: VecDeque<Duration>,
+    frame_history_size: usize,
+    frame_count: u64,
+    elapsed: Duration,
+    game_time: Duration,
+    scheduler: Scheduler,
 }
 
 impl Timer {
@@ -13,24 +53,75 @@ impl Timer {
             frame_duration: timer_config.frame_duration,
             last_frame_instant: Instant::now(),
             delta_time: Duration::new(0, 0),
+            fixed_update_duration: timer_config.fixed_update_duration,
+            max_update_steps: timer_config.max_update_steps,
+            accumulator: Duration::new(0, 0),
+            update_steps: 0,
+            time_scale: timer_config.time_scale,
+            paused: false,
+            frame_history: VecDeque::with_capacity(timer_config.frame_history_size),
+            frame_history_size: timer_config.frame_history_size,
+            frame_count: 0,
+            elapsed: Duration::new(0, 0),
+            game_time: Duration::new(0, 0),
+            scheduler: Scheduler::new(),
         })
     }
 
     pub(crate) fn reset_tick(&mut self) {
         self.last_frame_instant = Instant::now();
         self.delta_time = Duration::new(0, 0);
+        self.accumulator = Duration::new(0, 0);
+        self.update_steps = 0;
     }
 
-    pub(crate) fn tick_and_check(&mut self) -> bool {
-        let now_instant = Instant::now();
-        let delta_time = now_instant.duration_since(self.last_frame_instant);
+    pub(crate) fn check(&self) -> Option<Duration> {
+        let delta_time = Instant::now().duration_since(self.last_frame_instant);
         if delta_time >= self.frame_duration {
-            self.last_frame_instant = now_instant;
-            self.delta_time = delta_time;
-            true
+            Some(delta_time)
         } else {
-            false
+            None
+        }
+    }
+
+    pub(crate) fn tick_with(&mut self, delta_time: Duration) {
+        self.last_frame_instant = Instant::now();
+        self.advance(delta_time);
+    }
+
+    fn advance(&mut self, delta_time: Duration) {
+        self.delta_time = delta_time;
+        self.frame_count += 1;
+        self.elapsed += delta_time;
+        if self.frame_history_size > 0 {
+            if self.frame_history.len() >= self.frame_history_size {
+                self.frame_history.pop_front();
+            }
+            self.frame_history.push_back(delta_time);
         }
+        match self.fixed_update_duration {
+            Some(step) => {
+                self.accumulator += delta_time;
+                let steps = (self.accumulator.as_nanos() / step.as_nanos()).min(self.max_update_steps as u128) as u32;
+                self.accumulator -= step * steps;
+                if steps == self.max_update_steps && self.accumulator >= step {
+                    self.accumulator = duration_rem(self.accumulator, step);
+                }
+                self.update_steps = steps;
+            }
+            None => self.update_steps = 1,
+        }
+        let game_delta_time = self.delta_time() * self.update_steps;
+        self.game_time += game_delta_time;
+        self.scheduler.advance(game_delta_time);
+    }
+
+    pub(crate) fn update_steps(&self) -> u32 {
+        self.update_steps
+    }
+
+    pub(crate) fn take_fired_timers(&mut self) -> Vec<TimerHandle> {
+        self.scheduler.take_fired()
     }
 
     pub fn frame_duration(&self) -> Duration {
@@ -42,11 +133,43 @@ impl Timer {
     }
 
     pub fn fps(&self) -> f32 {
-        1.0 / self.frame_duration.as_secs_f32()
+        fps_of(self.frame_duration)
     }
 
     pub fn set_fps(&mut self, fps: f32) {
-        self.frame_duration = Duration::from_secs_f32(1.0 / fps);
+        self.frame_duration = duration_of(fps).unwrap_or_default();
+    }
+
+    pub fn fixed_update_duration(&self) -> Option<Duration> {
+        self.fixed_update_duration
+    }
+
+    pub fn set_fixed_update_duration(&mut self, fixed_update_duration: Option<Duration>) {
+        self.fixed_update_duration = fixed_update_duration.filter(|duration| !duration.is_zero());
+        self.accumulator = Duration::new(0, 0);
+    }
+
+    pub fn set_fixed_update_fps(&mut self, fps: f32) {
+        self.set_fixed_update_duration(duration_of(fps));
+    }
+
+    pub fn is_fixed_update(&self) -> bool {
+        self.fixed_update_duration.is_some()
+    }
+
+    pub fn max_update_steps(&self) -> u32 {
+        self.max_update_steps
+    }
+
+    pub fn set_max_update_steps(&mut self, max_update_steps: u32) {
+        self.max_update_steps = max_update_steps.max(1);
+    }
+
+    pub fn interpolation_alpha(&self) -> f32 {
+        match self.fixed_update_duration {
+            Some(step) => (self.accumulator.as_secs_f32() / step.as_secs_f32()).clamp(0.0, 1.0),
+            None => 1.0,
+        }
     }
 
     pub fn real_time_fps(&self) -> f32 {
@@ -58,7 +181,136 @@ impl Timer {
         }
     }
 
+    pub fn average_fps(&self) -> f32 {
+        if self.frame_history.is_empty() {
+            return 0.0;
+        }
+        let total = self.frame_history.iter().sum::<Duration>().as_secs_f32();
+        if total > 0.0 {
+            self.frame_history.len() as f32 / total
+        } else {
+            0.0
+        }
+    }
+
+    pub fn min_fps(&self) -> f32 {
+        self.frame_history.iter().max()
+            .map(|frame_time| fps_of(*frame_time))
+            .unwrap_or(0.0)
+    }
+
+    pub fn max_fps(&self) -> f32 {
+        self.frame_history.iter().min()
+            .map(|frame_time| fps_of(*frame_time))
+            .unwrap_or(0.0)
+    }
+
+    pub fn percentile_frame_time(&self, percentile: f32) -> Duration {
+        if self.frame_history.is_empty() {
+            return Duration::new(0, 0);
+        }
+        let mut frame_times = self.frame_history.iter().copied().collect::<Vec<_>>();
+        frame_times.sort();
+        let index = ((frame_times.len() - 1) as f32 * percentile.clamp(0.0, 1.0)).round() as usize;
+        frame_times[index]
+    }
+
+    pub fn percentile_fps(&self, percentile: f32) -> f32 {
+        fps_of(self.percentile_frame_time(percentile))
+    }
+
+    pub fn frame_history(&self) -> impl Iterator<Item = Duration> + '_ {
+        self.frame_history.iter().copied()
+    }
+
+    pub fn frame_history_size(&self) -> usize {
+        self.frame_history_size
+    }
+
+    pub fn set_frame_history_size(&mut self, frame_history_size: usize) {
+        self.frame_history_size = frame_history_size;
+        while self.frame_history.len() > frame_history_size {
+            self.frame_history.pop_front();
+        }
+    }
+
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn game_time(&self) -> Duration {
+        self.game_time
+    }
+
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        self.time_scale = time_scale.max(0.0);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn schedule(&mut self, delay: Duration) -> TimerHandle {
+        self.scheduler.schedule(delay, None)
+    }
+
+    pub fn schedule_repeating(&mut self, interval: Duration) -> TimerHandle {
+        self.scheduler.schedule(interval, Some(interval))
+    }
+
+    pub fn cancel(&mut self, handle: TimerHandle) -> bool {
+        self.scheduler.cancel(handle)
+    }
+
+    pub fn cancel_all(&mut self) {
+        self.scheduler.cancel_all();
+    }
+
+    pub fn is_scheduled(&self, handle: TimerHandle) -> bool {
+        self.scheduler.is_scheduled(handle)
+    }
+
+    pub fn remaining(&self, handle: TimerHandle) -> Option<Duration> {
+        self.scheduler.remaining(handle)
+    }
+
+    pub fn is_fired(&self, handle: TimerHandle) -> bool {
+        self.scheduler.fire_count(handle) > 0
+    }
+
+    pub fn fire_count(&self, handle: TimerHandle) -> u32 {
+        self.scheduler.fire_count(handle)
+    }
+
     pub fn delta_time(&self) -> Duration {
+        if self.paused {
+            Duration::new(0, 0)
+        } else {
+            self.fixed_update_duration.unwrap_or(self.delta_time).mul_f32(self.time_scale)
+        }
+    }
+
+    pub fn unscaled_delta_time(&self) -> Duration {
         self.delta_time
     }
 }
@@ -66,12 +318,20 @@ impl Timer {
 #[derive(Debug, Clone)]
 pub struct TimerConfig {
     frame_duration: Duration,
+    fixed_update_duration: Option<Duration>,
+    max_update_steps: u32,
+    time_scale: f32,
+    frame_history_size: usize,
 }
 
 impl TimerConfig {
     pub fn new() -> Self {
         Self {
             frame_duration: Duration::from_secs_f32(1.0 / 60.0),
+            fixed_update_duration: None,
+            max_update_steps: 5,
+            time_scale: 1.0,
+            frame_history_size: 120,
         }
     }
 
@@ -81,7 +341,150 @@ impl TimerConfig {
     }
 
     pub fn fps(mut self, fps: f32) -> Self {
-        self.frame_duration = Duration::from_secs_f32(1.0 / fps);
+        self.frame_duration = duration_of(fps).unwrap_or_default();
+        self
+    }
+
+    pub fn fixed_update_duration(mut self, fixed_update_duration: Option<Duration>) -> Self {
+        self.fixed_update_duration = fixed_update_duration.filter(|duration| !duration.is_zero());
+        self
+    }
+
+    pub fn fixed_update_fps(mut self, fps: f32) -> Self {
+        self.fixed_update_duration = duration_of(fps).filter(|duration| !duration.is_zero());
+        self
+    }
+
+    pub fn max_update_steps(mut self, max_update_steps: u32) -> Self {
+        self.max_update_steps = max_update_steps.max(1);
+        self
+    }
+
+    pub fn time_scale(mut self, time_scale: f32) -> Self {
+        self.time_scale = time_scale.max(0.0);
+        self
+    }
+
+    pub fn frame_history_size(mut self, frame_history_size: usize) -> Self {
+        self.frame_history_size = frame_history_size;
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Timer, TimerConfig};
+    use std::time::Duration;
+
+    #[test]
+    fn test_variable_update() {
+        let mut timer = Timer::new(TimerConfig::new()).unwrap();
+        timer.advance(Duration::from_millis(20));
+        assert_eq!(timer.update_steps(), 1);
+        assert_eq!(timer.delta_time(), Duration::from_millis(20));
+        assert_eq!(timer.interpolation_alpha(), 1.0);
+    }
+
+    #[test]
+    fn test_fixed_update() {
+        let mut timer = Timer::new(TimerConfig::new()
+            .fixed_update_duration(Some(Duration::from_millis(10)))
+            .max_update_steps(3)).unwrap();
+        timer.advance(Duration::from_millis(25));
+        assert_eq!(timer.update_steps(), 2);
+        assert_eq!(timer.delta_time(), Duration::from_millis(10));
+        assert_eq!(timer.unscaled_delta_time(), Duration::from_millis(25));
+        assert!((timer.interpolation_alpha() - 0.5).abs() < 1e-6);
+        timer.advance(Duration::from_millis(4));
+        assert_eq!(timer.update_steps(), 0);
+        assert!((timer.interpolation_alpha() - 0.9).abs() < 1e-6);
+        timer.advance(Duration::from_millis(1000));
+        assert_eq!(timer.update_steps(), 3);
+        assert!((timer.interpolation_alpha() - 0.9).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_statistics() {
+        let mut timer = Timer::new(TimerConfig::new().frame_history_size(4)).unwrap();
+        for millis in [10, 20, 40, 10, 20] {
+            timer.advance(Duration::from_millis(millis));
+        }
+        assert_eq!(timer.frame_count(), 5);
+        assert_eq!(timer.elapsed(), Duration::from_millis(100));
+        assert_eq!(timer.frame_history().collect::<Vec<_>>().len(), 4);
+        assert!((timer.average_fps() - 1000.0 / 22.5).abs() < 1e-3);
+        assert!((timer.min_fps() - 25.0).abs() < 1e-3);
+        assert!((timer.max_fps() - 100.0).abs() < 1e-3);
+        assert_eq!(timer.percentile_frame_time(1.0), Duration::from_millis(40));
+        assert_eq!(timer.percentile_frame_time(0.0), Duration::from_millis(10));
+    }
+
+    #[test]
+    fn test_time_scale() {
+        let mut timer = Timer::new(TimerConfig::new().time_scale(0.5)).unwrap();
+        timer.advance(Duration::from_millis(20));
+        assert_eq!(timer.delta_time(), Duration::from_millis(10));
+        assert_eq!(timer.unscaled_delta_time(), Duration::from_millis(20));
+        timer.pause();
+        timer.advance(Duration::from_millis(20));
+        assert_eq!(timer.delta_time(), Duration::from_millis(0));
+        assert_eq!(timer.unscaled_delta_time(), Duration::from_millis(20));
+        assert_eq!(timer.game_time(), Duration::from_millis(10));
+        assert_eq!(timer.elapsed(), Duration::from_millis(40));
+    }
+
+    #[test]
+    fn test_invalid_fps() {
+        let mut timer = Timer::new(TimerConfig::new().fps(0.0).fixed_update_fps(f32::NAN)).unwrap();
+        assert_eq!(timer.frame_duration(), Duration::new(0, 0));
+        assert_eq!(timer.fps(), 0.0);
+        assert_eq!(timer.fixed_update_duration(), None);
+        timer.set_fixed_update_fps(50.0);
+        assert_eq!(timer.fixed_update_duration(), Some(Duration::from_millis(20)));
+        timer.set_fixed_update_fps(0.0);
+        assert_eq!(timer.fixed_update_duration(), None);
+        timer.set_fixed_update_fps(f32::INFINITY);
+        assert_eq!(timer.fixed_update_duration(), None);
+        timer.set_fps(-30.0);
+        assert_eq!(timer.frame_duration(), Duration::new(0, 0));
+        timer.set_fps(f32::MIN_POSITIVE);
+        assert_eq!(timer.frame_duration(), Duration::MAX);
+        timer.set_fps(50.0);
+        assert_eq!(timer.frame_duration(), Duration::from_millis(20));
+    }
+
+    #[test]
+    fn test_scheduler() {
+        let mut timer = Timer::new(TimerConfig::new()).unwrap();
+        let once = timer.schedule(Duration::from_millis(30));
+        let repeating = timer.schedule_repeating(Duration::from_millis(20));
+        let cancelled = timer.schedule(Duration::from_millis(10));
+        assert!(timer.cancel(cancelled));
+        timer.advance(Duration::from_millis(25));
+        assert!(!timer.is_fired(once));
+        assert_eq!(timer.remaining(once), Some(Duration::from_millis(5)));
+        assert_eq!(timer.fire_count(repeating), 1);
+        assert_eq!(timer.take_fired_timers(), vec![repeating]);
+        timer.pause();
+        timer.advance(Duration::from_millis(100));
+        assert!(!timer.is_fired(repeating));
+        timer.resume();
+        timer.set_time_scale(2.0);
+        timer.advance(Duration::from_millis(20));
+        assert!(timer.is_fired(once));
+        assert!(!timer.is_scheduled(once));
+        assert_eq!(timer.fire_count(repeating), 2);
+        assert_eq!(timer.take_fired_timers(), vec![once, repeating]);
+        timer.advance(Duration::from_millis(1));
+        assert!(!timer.is_fired(once));
+        assert!(!timer.cancel(once));
+        assert!(!timer.is_fired(cancelled));
+        timer.set_time_scale(1.0);
+        timer.take_fired_timers();
+        let fast = timer.schedule_repeating(Duration::from_millis(1));
+        timer.advance(Duration::from_millis(10_000));
+        assert_eq!(timer.fire_count(fast), 10_000);
+        assert_eq!(timer.remaining(fast), Some(Duration::from_millis(1)));
+        assert_eq!(timer.take_fired_timers().iter().filter(|handle| **handle == fast).count(), 1);
+    }
+}
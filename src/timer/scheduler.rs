@@ -0,0 +1,109 @@
+use super::duration_rem;
+use std::time::Duration;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub struct TimerHandle(u64);
+
+struct ScheduledTimer {
+    remaining: Duration,
+    interval: Option<Duration>,
+    fire_count: u32,
+    finished: bool,
+}
+
+pub(crate) struct Scheduler {
+    next_id: u64,
+    timers: BTreeMap<u64, ScheduledTimer>,
+    fired: Vec<TimerHandle>,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self {
+            next_id: 0,
+            timers: BTreeMap::new(),
+            fired: Vec::new(),
+        }
+    }
+
+    pub fn schedule(&mut self, delay: Duration, interval: Option<Duration>) -> TimerHandle {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.timers.insert(id, ScheduledTimer {
+            remaining: delay,
+            interval,
+            fire_count: 0,
+            finished: false,
+        });
+        TimerHandle(id)
+    }
+
+    pub fn cancel(&mut self, handle: TimerHandle) -> bool {
+        match self.timers.remove(&handle.0) {
+            Some(timer) => !timer.finished,
+            None => false,
+        }
+    }
+
+    pub fn cancel_all(&mut self) {
+        self.timers.clear();
+        self.fired.clear();
+    }
+
+    pub fn is_scheduled(&self, handle: TimerHandle) -> bool {
+        self.timers.get(&handle.0)
+            .map(|timer| !timer.finished)
+            .unwrap_or(false)
+    }
+
+    pub fn remaining(&self, handle: TimerHandle) -> Option<Duration> {
+        self.timers.get(&handle.0)
+            .filter(|timer| !timer.finished)
+            .map(|timer| timer.remaining)
+    }
+
+    pub fn fire_count(&self, handle: TimerHandle) -> u32 {
+        self.timers.get(&handle.0)
+            .map(|timer| timer.fire_count)
+            .unwrap_or(0)
+    }
+
+    pub fn advance(&mut self, delta_time: Duration) {
+        self.timers.retain(|_, timer| !timer.finished);
+        for timer in self.timers.values_mut() {
+            timer.fire_count = 0;
+        }
+        if delta_time.is_zero() {
+            return;
+        }
+        for (id, timer) in self.timers.iter_mut() {
+            if delta_time < timer.remaining {
+                timer.remaining -= delta_time;
+                continue;
+            }
+            let overshoot = delta_time - timer.remaining;
+            match timer.interval {
+                Some(interval) if !interval.is_zero() => {
+                    let missed = (overshoot.as_nanos() / interval.as_nanos()).min(u32::MAX as u128 - 1) as u32;
+                    timer.fire_count = 1 + missed;
+                    timer.remaining = interval - duration_rem(overshoot, interval);
+                }
+                Some(_) => {
+                    timer.fire_count = 1;
+                    timer.remaining = Duration::new(0, 0);
+                }
+                None => {
+                    timer.fire_count = 1;
+                    timer.remaining = Duration::new(0, 0);
+                    timer.finished = true;
+                }
+            }
+            self.fired.push(TimerHandle(*id));
+        }
+    }
+
+    pub fn take_fired(&mut self) -> Vec<TimerHandle> {
+        std::mem::take(&mut self.fired)
+    }
+}
@@ -1,47 +1,380 @@
+mod gesture;
+
+pub use gesture::{Gesture, SwipeDirection};
+pub(crate) use gesture::{GestureRecognizer, GestureSettings};
+
 use crate::error::GameResult;
-use crate::event::TouchPhase;
+use crate::event::{Event, TouchPhase, KeyState, KeyAction};
+use crate::math::Vector;
+use crate::mouse::MouseButton;
 use crate::window::LogicalPosition;
 use std::collections::HashMap;
+use std::time::Duration;
+
+const VELOCITY_SMOOTHING: f32 = 0.5;
+
+pub const MOUSE_TOUCH_ID: u64 = u64::MAX;
+
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum TouchEmulation {
+    None,
+    FromMouse,
+    ToMouse,
+}
+
+struct TouchPoint {
+    state: KeyState,
+    started: bool,
+    start_position: LogicalPosition,
+    start_time: Duration,
+    position: LogicalPosition,
+    frame_position: LogicalPosition,
+    sample_position: LogicalPosition,
+    sample_time: Duration,
+    velocity: Vector,
+}
+
+impl TouchPoint {
+    fn new(position: LogicalPosition, time: Duration) -> Self {
+        Self {
+            state: KeyState::Down,
+            started: true,
+            start_position: position,
+            start_time: time,
+            position,
+            frame_position: position,
+            sample_position: position,
+            sample_time: time,
+            velocity: Vector::zero(),
+        }
+    }
+
+    fn move_to(&mut self, position: LogicalPosition, time: Duration) {
+        self.position = position;
+        if time > self.sample_time {
+            let seconds = (time - self.sample_time).as_secs_f32();
+            let velocity = Vector::new(
+                (position.x - self.sample_position.x) / seconds,
+                (position.y - self.sample_position.y) / seconds,
+            );
+            self.velocity = Vector::new(
+                self.velocity.x + (velocity.x - self.velocity.x) * VELOCITY_SMOOTHING,
+                self.velocity.y + (velocity.y - self.velocity.y) * VELOCITY_SMOOTHING,
+            );
+            self.sample_position = position;
+            self.sample_time = time;
+        }
+    }
+}
 
 pub struct Touch {
-    positions: HashMap<u64, LogicalPosition>,
+    points: HashMap<u64, TouchPoint>,
+    gesture_recognizer: GestureRecognizer,
+    emulation: TouchEmulation,
+    mouse_position: LogicalPosition,
+    mouse_pressed: bool,
+    primary_touch: Option<u64>,
 }
 
 impl Touch {
-    pub(crate) fn new(_: TouchConfig) -> GameResult<Self> {
+    pub(crate) fn new(touch_config: TouchConfig) -> GameResult<Self> {
         Ok(Self {
-            positions: HashMap::new(),
+            points: HashMap::new(),
+            gesture_recognizer: GestureRecognizer::new(touch_config.gesture_settings),
+            emulation: touch_config.emulation,
+            mouse_position: LogicalPosition::zero(),
+            mouse_pressed: false,
+            primary_touch: None,
         })
     }
 
-    pub(crate) fn handle_event(&mut self, id: u64, phase: TouchPhase, position: LogicalPosition) {
+    pub(crate) fn handle_event(&mut self, id: u64, phase: TouchPhase, position: LogicalPosition, time: Duration) {
         match phase {
-            TouchPhase::Start | TouchPhase::Move => self.positions.insert(id, position),
-            TouchPhase::End | TouchPhase::Cancel => self.positions.remove(&id),
-        };
+            TouchPhase::Start => {
+                self.points.insert(id, TouchPoint::new(position, time));
+            }
+            TouchPhase::Move => {
+                if let Some(point) = self.points.get_mut(&id) {
+                    point.move_to(position, time);
+                }
+            }
+            TouchPhase::End | TouchPhase::Cancel => {
+                if let Some(point) = self.points.get_mut(&id) {
+                    point.move_to(position, time);
+                    point.state = KeyState::Up;
+                }
+            }
+        }
+        self.gesture_recognizer.handle_event(id, phase, position, time);
     }
 
-    pub(crate) fn clear_states(&mut self) {}
+    pub(crate) fn emulate_event(&mut self, event: &Event) -> Vec<Event> {
+        match (self.emulation, event) {
+            (TouchEmulation::FromMouse, Event::MouseMove(position)) => {
+                self.mouse_position = *position;
+                if self.mouse_pressed {
+                    return vec![Event::Touch { id: MOUSE_TOUCH_ID, phase: TouchPhase::Move, position: *position }];
+                }
+            }
+            (TouchEmulation::FromMouse, Event::MouseInput { button: MouseButton::Left, action }) => {
+                let pressed = *action == KeyAction::Down;
+                if pressed != self.mouse_pressed {
+                    self.mouse_pressed = pressed;
+                    let phase = if pressed { TouchPhase::Start } else { TouchPhase::End };
+                    return vec![Event::Touch { id: MOUSE_TOUCH_ID, phase, position: self.mouse_position }];
+                }
+            }
+            (TouchEmulation::ToMouse, Event::Touch { id, phase, position }) => {
+                match phase {
+                    TouchPhase::Start if self.primary_touch.is_none() => {
+                        self.primary_touch = Some(*id);
+                        return vec![
+                            Event::MouseMove(*position),
+                            Event::MouseInput { button: MouseButton::Left, action: KeyAction::Down },
+                        ];
+                    }
+                    TouchPhase::Move if self.primary_touch == Some(*id) => {
+                        return vec![Event::MouseMove(*position)];
+                    }
+                    TouchPhase::End | TouchPhase::Cancel if self.primary_touch == Some(*id) => {
+                        self.primary_touch = None;
+                        return vec![
+                            Event::MouseMove(*position),
+                            Event::MouseInput { button: MouseButton::Left, action: KeyAction::Up },
+                        ];
+                    }
+                    _ => (),
+                }
+            }
+            _ => (),
+        }
+        Vec::new()
+    }
 
-    pub fn touches(&self) -> Vec<u64> {
-        let mut touches = Vec::with_capacity(self.positions.len());
-        for (id, _) in &self.positions {
-            touches.push(*id);
+    pub(crate) fn update_gestures(&mut self, time: Duration) {
+        self.gesture_recognizer.update(time);
+    }
+
+    pub(crate) fn take_gestures(&mut self) -> Vec<Gesture> {
+        self.gesture_recognizer.take_gestures()
+    }
+
+    pub(crate) fn reset_states(&mut self) {
+        self.points.clear();
+        self.gesture_recognizer = GestureRecognizer::new(self.gesture_recognizer.settings().clone());
+        self.mouse_position = LogicalPosition::zero();
+        self.mouse_pressed = false;
+        self.primary_touch = None;
+    }
+
+    pub(crate) fn clear_states(&mut self) {
+        self.points.retain(|_, point| match point.state {
+            KeyState::Down | KeyState::Hold => {
+                point.state = KeyState::Hold;
+                point.started = false;
+                point.frame_position = point.position;
+                true
+            }
+            KeyState::Up | KeyState::Idle => false,
+        });
+    }
+
+    fn filter_touches(&self, filter: impl Fn(&TouchPoint) -> bool) -> Vec<u64> {
+        let mut touches = Vec::with_capacity(self.points.len());
+        for (id, point) in &self.points {
+            if filter(point) {
+                touches.push(*id);
+            }
         }
         touches.sort();
         touches
     }
 
+    pub fn touches(&self) -> Vec<u64> {
+        self.filter_touches(|point| point.state != KeyState::Up)
+    }
+
+    pub fn touches_started(&self) -> Vec<u64> {
+        self.filter_touches(|point| point.started)
+    }
+
+    pub fn touches_ended(&self) -> Vec<u64> {
+        self.filter_touches(|point| point.state == KeyState::Up)
+    }
+
+    pub fn is_touch_down(&self, id: u64) -> bool {
+        self.points.get(&id).map(|point| point.started).unwrap_or(false)
+    }
+
+    pub fn is_touch_hold(&self, id: u64) -> bool {
+        self.points.get(&id).map(|point| point.state != KeyState::Up).unwrap_or(false)
+    }
+
+    pub fn is_touch_up(&self, id: u64) -> bool {
+        self.points.get(&id).map(|point| point.state == KeyState::Up).unwrap_or(false)
+    }
+
+    pub fn emulation(&self) -> TouchEmulation {
+        self.emulation
+    }
+
+    pub fn set_emulation(&mut self, emulation: TouchEmulation) {
+        self.emulation = emulation;
+        self.mouse_pressed = false;
+        self.primary_touch = None;
+    }
+
     pub fn position(&self, id: u64) -> Option<LogicalPosition> {
-        self.positions.get(&id).map(|position| *position)
+        self.points.get(&id).map(|point| point.position)
+    }
+
+    pub fn start_position(&self, id: u64) -> Option<LogicalPosition> {
+        self.points.get(&id).map(|point| point.start_position)
+    }
+
+    pub fn previous_position(&self, id: u64) -> Option<LogicalPosition> {
+        self.points.get(&id).map(|point| point.frame_position)
+    }
+
+    pub fn start_time(&self, id: u64) -> Option<Duration> {
+        self.points.get(&id).map(|point| point.start_time)
+    }
+
+    pub fn delta(&self, id: u64) -> Option<Vector> {
+        self.points.get(&id).map(|point| point.position - point.frame_position)
+    }
+
+    pub fn velocity(&self, id: u64) -> Option<Vector> {
+        self.points.get(&id).map(|point| point.velocity)
+    }
+
+    pub fn is_gestures_enabled(&self) -> bool {
+        self.gesture_recognizer.settings().enabled
+    }
+
+    pub fn set_gestures_enabled(&mut self, enabled: bool) {
+        self.gesture_recognizer.settings_mut().enabled = enabled;
     }
 }
 
 #[derive(Debug, Clone)]
-pub struct TouchConfig {}
+pub struct TouchConfig {
+    gesture_settings: GestureSettings,
+    emulation: TouchEmulation,
+}
 
 impl TouchConfig {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            gesture_settings: GestureSettings::new(),
+            emulation: TouchEmulation::None,
+        }
+    }
+
+    pub fn emulation(mut self, emulation: TouchEmulation) -> Self {
+        self.emulation = emulation;
+        self
+    }
+
+    pub fn gestures_enabled(mut self, enabled: bool) -> Self {
+        self.gesture_settings.enabled = enabled;
+        self
+    }
+
+    pub fn tap_max_duration(mut self, duration: Duration) -> Self {
+        self.gesture_settings.tap_max_duration = duration;
+        self
+    }
+
+    pub fn tap_max_distance(mut self, distance: f32) -> Self {
+        self.gesture_settings.tap_max_distance = distance;
+        self
+    }
+
+    pub fn double_tap_interval(mut self, interval: Duration) -> Self {
+        self.gesture_settings.double_tap_interval = interval;
+        self
+    }
+
+    pub fn long_press_duration(mut self, duration: Duration) -> Self {
+        self.gesture_settings.long_press_duration = duration;
+        self
+    }
+
+    pub fn swipe_min_distance(mut self, distance: f32) -> Self {
+        self.gesture_settings.swipe_min_distance = distance;
+        self
+    }
+
+    pub fn swipe_min_velocity(mut self, velocity: f32) -> Self {
+        self.gesture_settings.swipe_min_velocity = velocity;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Touch, TouchConfig, TouchEmulation, MOUSE_TOUCH_ID};
+    use crate::event::{Event, TouchPhase, KeyAction};
+    use crate::math::Vector;
+    use crate::mouse::MouseButton;
+    use crate::window::LogicalPosition;
+    use std::time::Duration;
+
+    fn assert_near(a: Vector, b: Vector) {
+        assert!((a.x - b.x).abs() < 1e-3 && (a.y - b.y).abs() < 1e-3, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn test_touch_points() {
+        let mut touch = Touch::new(TouchConfig::new()).unwrap();
+        touch.handle_event(4, TouchPhase::Start, LogicalPosition::new(10.0, 10.0), Duration::from_millis(0));
+        assert_eq!(touch.touches_started(), vec![4]);
+        assert!(touch.is_touch_down(4) && touch.is_touch_hold(4));
+        touch.handle_event(4, TouchPhase::Move, LogicalPosition::new(20.0, 10.0), Duration::from_millis(100));
+        assert_eq!(touch.delta(4), Some(Vector::new(10.0, 0.0)));
+        assert_near(touch.velocity(4).unwrap(), Vector::new(50.0, 0.0));
+        touch.clear_states();
+        assert!(touch.touches_started().is_empty());
+        assert!(!touch.is_touch_down(4) && touch.is_touch_hold(4));
+        touch.handle_event(4, TouchPhase::Move, LogicalPosition::new(25.0, 5.0), Duration::from_millis(200));
+        assert_eq!(touch.previous_position(4), Some(LogicalPosition::new(20.0, 10.0)));
+        assert_eq!(touch.start_position(4), Some(LogicalPosition::new(10.0, 10.0)));
+        assert_eq!(touch.delta(4), Some(Vector::new(5.0, -5.0)));
+        assert_near(touch.velocity(4).unwrap(), Vector::new(50.0, -25.0));
+        touch.handle_event(4, TouchPhase::End, LogicalPosition::new(25.0, 5.0), Duration::from_millis(300));
+        assert!(touch.touches().is_empty());
+        assert_eq!(touch.touches_ended(), vec![4]);
+        assert!(touch.is_touch_up(4) && !touch.is_touch_hold(4));
+        touch.clear_states();
+        assert!(touch.touches_ended().is_empty());
+        assert_eq!(touch.velocity(4), None);
+
+        touch.handle_event(5, TouchPhase::Start, LogicalPosition::new(0.0, 0.0), Duration::from_millis(400));
+        touch.handle_event(5, TouchPhase::End, LogicalPosition::new(0.0, 0.0), Duration::from_millis(400));
+        assert_eq!(touch.touches_started(), vec![5]);
+        assert_eq!(touch.touches_ended(), vec![5]);
+    }
+
+    #[test]
+    fn test_emulation() {
+        let position = LogicalPosition::new(3.0, 4.0);
+        let mut touch = Touch::new(TouchConfig::new().emulation(TouchEmulation::FromMouse)).unwrap();
+        assert!(touch.emulate_event(&Event::MouseMove(position)).is_empty());
+        let events = touch.emulate_event(&Event::MouseInput { button: MouseButton::Left, action: KeyAction::Down });
+        assert_eq!(events, vec![Event::Touch { id: MOUSE_TOUCH_ID, phase: TouchPhase::Start, position }]);
+        assert_eq!(touch.emulate_event(&Event::MouseMove(position)).len(), 1);
+        assert!(touch.emulate_event(&Event::MouseInput { button: MouseButton::Right, action: KeyAction::Up }).is_empty());
+        let events = touch.emulate_event(&Event::MouseInput { button: MouseButton::Left, action: KeyAction::Up });
+        assert_eq!(events, vec![Event::Touch { id: MOUSE_TOUCH_ID, phase: TouchPhase::End, position }]);
+
+        touch.set_emulation(TouchEmulation::ToMouse);
+        let events = touch.emulate_event(&Event::Touch { id: 1, phase: TouchPhase::Start, position });
+        assert_eq!(events, vec![Event::MouseMove(position), Event::MouseInput { button: MouseButton::Left, action: KeyAction::Down }]);
+        assert!(touch.emulate_event(&Event::Touch { id: 2, phase: TouchPhase::Start, position }).is_empty());
+        assert!(touch.emulate_event(&Event::Touch { id: 2, phase: TouchPhase::End, position }).is_empty());
+        let events = touch.emulate_event(&Event::Touch { id: 1, phase: TouchPhase::Cancel, position });
+        assert_eq!(events[1], Event::MouseInput { button: MouseButton::Left, action: KeyAction::Up });
+        assert!(touch.emulate_event(&Event::MouseMove(position)).is_empty());
     }
 }
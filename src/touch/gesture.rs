@@ -0,0 +1,362 @@
+use crate::event::TouchPhase;
+use crate::math::{Vector, Angle};
+use crate::window::LogicalPosition;
+use std::collections::HashMap;
+use std::time::Duration;
+
+const CHANGE_EPSILON: f32 = 1e-4;
+
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum SwipeDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl SwipeDirection {
+    pub(crate) fn from_vector(vector: Vector) -> Self {
+        if vector.x.abs() >= vector.y.abs() {
+            if vector.x < 0.0 { Self::Left } else { Self::Right }
+        } else if vector.y < 0.0 {
+            Self::Up
+        } else {
+            Self::Down
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Gesture {
+    Tap {
+        position: LogicalPosition,
+    },
+    DoubleTap {
+        position: LogicalPosition,
+    },
+    LongPress {
+        position: LogicalPosition,
+    },
+    Swipe {
+        direction: SwipeDirection,
+        start: LogicalPosition,
+        end: LogicalPosition,
+        velocity: Vector,
+    },
+    TouchpadSwipe {
+        direction: SwipeDirection,
+        delta: Vector,
+        velocity: Vector,
+    },
+    Pinch {
+        center: LogicalPosition,
+        scale: f32,
+        delta: f32,
+    },
+    Rotate {
+        center: LogicalPosition,
+        angle: Angle,
+        delta: Angle,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct GestureSettings {
+    pub enabled: bool,
+    pub tap_max_duration: Duration,
+    pub tap_max_distance: f32,
+    pub double_tap_interval: Duration,
+    pub long_press_duration: Duration,
+    pub swipe_min_distance: f32,
+    pub swipe_min_velocity: f32,
+}
+
+impl GestureSettings {
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            tap_max_duration: Duration::from_millis(250),
+            tap_max_distance: 10.0,
+            double_tap_interval: Duration::from_millis(300),
+            long_press_duration: Duration::from_millis(500),
+            swipe_min_distance: 50.0,
+            swipe_min_velocity: 200.0,
+        }
+    }
+}
+
+struct Contact {
+    start_position: LogicalPosition,
+    start_time: Duration,
+    position: LogicalPosition,
+    max_distance: f32,
+    multi: bool,
+    long_pressed: bool,
+}
+
+struct TwoFinger {
+    ids: (u64, u64),
+    start_distance: f32,
+    start_angle: f32,
+    scale: f32,
+    angle: f32,
+}
+
+pub(crate) struct GestureRecognizer {
+    settings: GestureSettings,
+    contacts: HashMap<u64, Contact>,
+    two_finger: Option<TwoFinger>,
+    last_tap: Option<(Duration, LogicalPosition)>,
+    gestures: Vec<Gesture>,
+}
+
+impl GestureRecognizer {
+    pub fn new(settings: GestureSettings) -> Self {
+        Self {
+            settings,
+            contacts: HashMap::new(),
+            two_finger: None,
+            last_tap: None,
+            gestures: Vec::new(),
+        }
+    }
+
+    pub fn settings(&self) -> &GestureSettings {
+        &self.settings
+    }
+
+    pub fn settings_mut(&mut self) -> &mut GestureSettings {
+        &mut self.settings
+    }
+
+    pub fn handle_event(&mut self, id: u64, phase: TouchPhase, position: LogicalPosition, time: Duration) {
+        match phase {
+            TouchPhase::Start => {
+                self.contacts.insert(id, Contact {
+                    start_position: position,
+                    start_time: time,
+                    position,
+                    max_distance: 0.0,
+                    multi: false,
+                    long_pressed: false,
+                });
+                if self.contacts.len() > 1 {
+                    for contact in self.contacts.values_mut() {
+                        contact.multi = true;
+                    }
+                }
+                self.reset_two_finger();
+            }
+            TouchPhase::Move => {
+                if let Some(contact) = self.contacts.get_mut(&id) {
+                    contact.position = position;
+                    contact.max_distance = contact.max_distance.max(distance(contact.start_position, position));
+                }
+                self.update_two_finger();
+            }
+            TouchPhase::End => {
+                if let Some(mut contact) = self.contacts.remove(&id) {
+                    contact.position = position;
+                    self.finish_contact(contact, time);
+                }
+                self.reset_two_finger();
+            }
+            TouchPhase::Cancel => {
+                self.contacts.remove(&id);
+                self.reset_two_finger();
+            }
+        }
+    }
+
+    pub fn update(&mut self, time: Duration) {
+        if !self.settings.enabled {
+            return;
+        }
+        for contact in self.contacts.values_mut() {
+            if !contact.multi
+                && !contact.long_pressed
+                && contact.max_distance <= self.settings.tap_max_distance
+                && time.saturating_sub(contact.start_time) >= self.settings.long_press_duration
+            {
+                contact.long_pressed = true;
+                self.gestures.push(Gesture::LongPress { position: contact.position });
+            }
+        }
+    }
+
+    pub fn take_gestures(&mut self) -> Vec<Gesture> {
+        std::mem::take(&mut self.gestures)
+    }
+
+    fn finish_contact(&mut self, contact: Contact, time: Duration) {
+        if !self.settings.enabled || contact.multi || contact.long_pressed {
+            return;
+        }
+        let duration = time.saturating_sub(contact.start_time);
+        if duration <= self.settings.tap_max_duration && contact.max_distance <= self.settings.tap_max_distance {
+            self.gestures.push(Gesture::Tap { position: contact.position });
+            match self.last_tap.take() {
+                Some((last_time, last_position))
+                    if time.saturating_sub(last_time) <= self.settings.double_tap_interval
+                        && distance(last_position, contact.position) <= self.settings.tap_max_distance * 2.0 =>
+                {
+                    self.gestures.push(Gesture::DoubleTap { position: contact.position });
+                }
+                _ => self.last_tap = Some((time, contact.position)),
+            }
+            return;
+        }
+        let displacement = contact.position - contact.start_position;
+        let length = distance(contact.start_position, contact.position);
+        let seconds = duration.as_secs_f32().max(f32::EPSILON);
+        if length >= self.settings.swipe_min_distance && length / seconds >= self.settings.swipe_min_velocity {
+            self.gestures.push(Gesture::Swipe {
+                direction: SwipeDirection::from_vector(displacement),
+                start: contact.start_position,
+                end: contact.position,
+                velocity: Vector::new(displacement.x / seconds, displacement.y / seconds),
+            });
+        }
+    }
+
+    fn reset_two_finger(&mut self) {
+        self.two_finger = None;
+        if self.contacts.len() != 2 {
+            return;
+        }
+        let mut ids = self.contacts.keys().copied().collect::<Vec<_>>();
+        ids.sort();
+        let (first, second) = (self.contacts[&ids[0]].position, self.contacts[&ids[1]].position);
+        self.two_finger = Some(TwoFinger {
+            ids: (ids[0], ids[1]),
+            start_distance: distance(first, second).max(f32::EPSILON),
+            start_angle: angle(first, second),
+            scale: 1.0,
+            angle: 0.0,
+        });
+    }
+
+    fn update_two_finger(&mut self) {
+        let two_finger = match &mut self.two_finger {
+            Some(two_finger) => two_finger,
+            None => return,
+        };
+        let (first, second) = match (self.contacts.get(&two_finger.ids.0), self.contacts.get(&two_finger.ids.1)) {
+            (Some(first), Some(second)) => (first.position, second.position),
+            _ => return,
+        };
+        if !self.settings.enabled {
+            return;
+        }
+        let center = LogicalPosition::new((first.x + second.x) / 2.0, (first.y + second.y) / 2.0);
+        let scale = distance(first, second) / two_finger.start_distance;
+        if (scale - two_finger.scale).abs() > CHANGE_EPSILON {
+            let delta = scale / two_finger.scale.max(f32::EPSILON);
+            two_finger.scale = scale;
+            self.gestures.push(Gesture::Pinch { center, scale, delta });
+        }
+        let rotation = normalize_angle(angle(first, second) - two_finger.start_angle);
+        if (rotation - two_finger.angle).abs() > CHANGE_EPSILON {
+            let delta = normalize_angle(rotation - two_finger.angle);
+            two_finger.angle = rotation;
+            self.gestures.push(Gesture::Rotate { center, angle: Angle::radians(rotation), delta: Angle::radians(delta) });
+        }
+    }
+}
+
+fn distance(a: LogicalPosition, b: LogicalPosition) -> f32 {
+    (b.x - a.x).hypot(b.y - a.y)
+}
+
+fn angle(a: LogicalPosition, b: LogicalPosition) -> f32 {
+    (b.y - a.y).atan2(b.x - a.x)
+}
+
+fn normalize_angle(angle: f32) -> f32 {
+    let pi = std::f32::consts::PI;
+    let mut angle = angle % (pi * 2.0);
+    if angle > pi {
+        angle -= pi * 2.0;
+    } else if angle <= -pi {
+        angle += pi * 2.0;
+    }
+    angle
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GestureRecognizer, GestureSettings, Gesture, SwipeDirection};
+    use crate::event::TouchPhase;
+    use crate::window::LogicalPosition;
+    use std::time::Duration;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn test_single_finger() {
+        let mut recognizer = GestureRecognizer::new(GestureSettings::new());
+        let position = LogicalPosition::new(100.0, 100.0);
+        recognizer.handle_event(1, TouchPhase::Start, position, ms(0));
+        recognizer.handle_event(1, TouchPhase::End, position, ms(100));
+        recognizer.handle_event(2, TouchPhase::Start, position, ms(200));
+        recognizer.handle_event(2, TouchPhase::End, LogicalPosition::new(103.0, 98.0), ms(250));
+        assert_eq!(recognizer.take_gestures(), vec![
+            Gesture::Tap { position },
+            Gesture::Tap { position: LogicalPosition::new(103.0, 98.0) },
+            Gesture::DoubleTap { position: LogicalPosition::new(103.0, 98.0) },
+        ]);
+
+        recognizer.handle_event(3, TouchPhase::Start, position, ms(1000));
+        recognizer.update(ms(1400));
+        assert!(recognizer.take_gestures().is_empty());
+        recognizer.update(ms(1500));
+        recognizer.update(ms(1600));
+        assert_eq!(recognizer.take_gestures(), vec![Gesture::LongPress { position }]);
+        recognizer.handle_event(3, TouchPhase::End, position, ms(1700));
+        assert!(recognizer.take_gestures().is_empty());
+
+        recognizer.handle_event(4, TouchPhase::Start, position, ms(2000));
+        recognizer.handle_event(4, TouchPhase::Move, LogicalPosition::new(60.0, 110.0), ms(2050));
+        recognizer.handle_event(4, TouchPhase::End, LogicalPosition::new(20.0, 120.0), ms(2200));
+        match recognizer.take_gestures().as_slice() {
+            [Gesture::Swipe { direction, velocity, .. }] => {
+                assert_eq!(*direction, SwipeDirection::Left);
+                assert!((velocity.x + 400.0).abs() < 1e-2);
+            }
+            gestures => panic!("unexpected gestures {:?}", gestures),
+        }
+
+        recognizer.handle_event(5, TouchPhase::Start, position, ms(3000));
+        recognizer.handle_event(5, TouchPhase::End, LogicalPosition::new(100.0, 200.0), ms(5000));
+        assert!(recognizer.take_gestures().is_empty());
+    }
+
+    #[test]
+    fn test_two_fingers() {
+        let mut recognizer = GestureRecognizer::new(GestureSettings::new());
+        recognizer.handle_event(1, TouchPhase::Start, LogicalPosition::new(0.0, 0.0), ms(0));
+        recognizer.handle_event(2, TouchPhase::Start, LogicalPosition::new(100.0, 0.0), ms(0));
+        recognizer.handle_event(2, TouchPhase::Move, LogicalPosition::new(200.0, 0.0), ms(16));
+        match recognizer.take_gestures().as_slice() {
+            [Gesture::Pinch { center, scale, delta }] => {
+                assert_eq!(*center, LogicalPosition::new(100.0, 0.0));
+                assert!((scale - 2.0).abs() < 1e-4);
+                assert!((delta - 2.0).abs() < 1e-4);
+            }
+            gestures => panic!("unexpected gestures {:?}", gestures),
+        }
+        recognizer.handle_event(2, TouchPhase::Move, LogicalPosition::new(0.0, 200.0), ms(32));
+        match recognizer.take_gestures().as_slice() {
+            [Gesture::Rotate { angle, delta, .. }] => {
+                assert!((angle.radians_value() - std::f32::consts::FRAC_PI_2).abs() < 1e-4);
+                assert!((delta.radians_value() - std::f32::consts::FRAC_PI_2).abs() < 1e-4);
+            }
+            gestures => panic!("unexpected gestures {:?}", gestures),
+        }
+        recognizer.handle_event(2, TouchPhase::End, LogicalPosition::new(0.0, 200.0), ms(48));
+        recognizer.handle_event(1, TouchPhase::End, LogicalPosition::new(0.0, 0.0), ms(64));
+        assert!(recognizer.take_gestures().is_empty());
+    }
+}
@@ -1,23 +1,62 @@
 use crate::error::GameResult;
+use crate::event::TouchPhase;
 use crate::math::Vector;
+use crate::touch::{Gesture, SwipeDirection};
+use std::time::Duration;
 
 pub struct Touchpad {
     scroll_delta: Vector,
     pressure: f32,
     click_stage: i64,
+    swipe_min_distance: f32,
+    scroll_gesture: Option<(Duration, Vector)>,
+    gestures: Vec<Gesture>,
 }
 
 impl Touchpad {
-    pub(crate) fn new(_: TouchpadConfig) -> GameResult<Self> {
+    pub(crate) fn new(touchpad_config: TouchpadConfig) -> GameResult<Self> {
         Ok(Self {
             scroll_delta: Vector::zero(),
             pressure: 0.0,
             click_stage: 0,
+            swipe_min_distance: touchpad_config.swipe_min_distance,
+            scroll_gesture: None,
+            gestures: Vec::new(),
         })
     }
 
-    pub(crate) fn handle_scroll_event(&mut self, delta: Vector) {
+    pub(crate) fn handle_scroll_event(&mut self, delta: Vector, phase: TouchPhase, time: Duration, gestures_enabled: bool) {
         self.scroll_delta += delta;
+        if !gestures_enabled {
+            self.scroll_gesture = None;
+            return;
+        }
+        match phase {
+            TouchPhase::Start => self.scroll_gesture = Some((time, delta)),
+            TouchPhase::Move => {
+                if let Some((_, total)) = &mut self.scroll_gesture {
+                    *total += delta;
+                }
+            }
+            TouchPhase::End => {
+                if let Some((start_time, mut total)) = self.scroll_gesture.take() {
+                    total += delta;
+                    if total.x.hypot(total.y) >= self.swipe_min_distance {
+                        let seconds = time.saturating_sub(start_time).as_secs_f32().max(f32::EPSILON);
+                        self.gestures.push(Gesture::TouchpadSwipe {
+                            direction: SwipeDirection::from_vector(total),
+                            delta: total,
+                            velocity: Vector::new(total.x / seconds, total.y / seconds),
+                        });
+                    }
+                }
+            }
+            TouchPhase::Cancel => self.scroll_gesture = None,
+        }
+    }
+
+    pub(crate) fn take_gestures(&mut self) -> Vec<Gesture> {
+        std::mem::take(&mut self.gestures)
     }
 
     pub(crate) fn handle_press_event(&mut self, pressure: f32, click_stage: i64) {
@@ -25,6 +64,14 @@ impl Touchpad {
         self.click_stage = click_stage;
     }
 
+    pub(crate) fn reset_states(&mut self) {
+        self.scroll_delta = Vector::zero();
+        self.pressure = 0.0;
+        self.click_stage = 0;
+        self.scroll_gesture = None;
+        self.gestures.clear();
+    }
+
     pub(crate) fn clear_states(&mut self) {
         self.scroll_delta = Vector::zero();
     }
@@ -43,10 +90,54 @@ impl Touchpad {
 }
 
 #[derive(Debug, Clone)]
-pub struct TouchpadConfig {}
+pub struct TouchpadConfig {
+    swipe_min_distance: f32,
+}
 
 impl TouchpadConfig {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            swipe_min_distance: 100.0,
+        }
+    }
+
+    pub fn swipe_min_distance(mut self, distance: f32) -> Self {
+        self.swipe_min_distance = distance;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Touchpad, TouchpadConfig};
+    use crate::event::TouchPhase;
+    use crate::math::Vector;
+    use crate::touch::{Gesture, SwipeDirection};
+    use std::time::Duration;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn test_swipe() {
+        let mut touchpad = Touchpad::new(TouchpadConfig::new().swipe_min_distance(50.0)).unwrap();
+        touchpad.handle_scroll_event(Vector::new(-20.0, 0.0), TouchPhase::Start, ms(0), true);
+        touchpad.handle_scroll_event(Vector::new(-30.0, 5.0), TouchPhase::Move, ms(100), true);
+        touchpad.handle_scroll_event(Vector::new(-30.0, 0.0), TouchPhase::End, ms(200), true);
+        assert_eq!(touchpad.take_gestures(), vec![Gesture::TouchpadSwipe {
+            direction: SwipeDirection::Left,
+            delta: Vector::new(-80.0, 5.0),
+            velocity: Vector::new(-400.0, 25.0),
+        }]);
+
+        touchpad.handle_scroll_event(Vector::new(-20.0, 0.0), TouchPhase::Start, ms(1000), false);
+        touchpad.handle_scroll_event(Vector::new(-80.0, 0.0), TouchPhase::End, ms(1100), false);
+        assert!(touchpad.take_gestures().is_empty());
+
+        touchpad.handle_scroll_event(Vector::new(-20.0, 0.0), TouchPhase::Start, ms(2000), true);
+        touchpad.handle_scroll_event(Vector::new(-80.0, 0.0), TouchPhase::End, ms(2100), false);
+        assert!(touchpad.take_gestures().is_empty());
+        assert_eq!(touchpad.scroll_delta(), Vector::new(-280.0, 5.0));
     }
 }
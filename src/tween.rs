@@ -0,0 +1,415 @@
+mod easing;
+mod tweenable;
+
+pub use easing::Easing;
+pub use tweenable::Tweenable;
+
+use crate::timer::Timer;
+use std::rc::Rc;
+use std::cell::Cell;
+use std::time::Duration;
+use std::convert::TryFrom;
+
+pub trait Animation {
+    fn advance(&mut self, delta_time: Duration) -> Duration;
+
+    fn is_finished(&self) -> bool;
+
+    fn reset(&mut self);
+
+    fn update(&mut self, timer: &Timer) -> bool {
+        self.advance(timer.delta_time());
+        self.is_finished()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TweenValue<T: Tweenable>(Rc<Cell<T>>);
+
+impl<T: Tweenable> TweenValue<T> {
+    pub fn get(&self) -> T {
+        self.0.get()
+    }
+}
+
+pub struct Tween<T: Tweenable> {
+    from: T,
+    to: T,
+    duration: Duration,
+    easing: Easing,
+    delay: Duration,
+    delay_elapsed: Duration,
+    elapsed: Duration,
+    cycle: u32,
+    repeat: Option<u32>,
+    yoyo: bool,
+    finished: bool,
+    value: TweenValue<T>,
+    on_complete: Option<Box<dyn FnMut()>>,
+}
+
+impl<T: Tweenable> Tween<T> {
+    pub fn new(from: T, to: T, duration: Duration) -> Self {
+        Self {
+            from,
+            to,
+            duration,
+            easing: Easing::Linear,
+            delay: Duration::new(0, 0),
+            delay_elapsed: Duration::new(0, 0),
+            elapsed: Duration::new(0, 0),
+            cycle: 0,
+            repeat: Some(0),
+            yoyo: false,
+            finished: false,
+            value: TweenValue(Rc::new(Cell::new(from))),
+            on_complete: None,
+        }
+    }
+
+    pub fn easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self.refresh();
+        self
+    }
+
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    pub fn repeat(mut self, count: u32) -> Self {
+        self.repeat = Some(count);
+        self
+    }
+
+    pub fn repeat_forever(mut self) -> Self {
+        self.repeat = None;
+        self
+    }
+
+    pub fn yoyo(mut self, yoyo: bool) -> Self {
+        self.yoyo = yoyo;
+        self
+    }
+
+    pub fn on_complete(mut self, on_complete: impl FnMut() + 'static) -> Self {
+        self.on_complete = Some(Box::new(on_complete));
+        self
+    }
+
+    pub fn from(&self) -> T {
+        self.from
+    }
+
+    pub fn to(&self) -> T {
+        self.to
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    pub fn value(&self) -> T {
+        self.value.get()
+    }
+
+    pub fn value_handle(&self) -> TweenValue<T> {
+        self.value.clone()
+    }
+
+    pub fn cycle(&self) -> u32 {
+        self.cycle
+    }
+
+    pub fn progress(&self) -> f32 {
+        if self.duration.is_zero() {
+            1.0
+        } else {
+            (self.elapsed.as_secs_f32() / self.duration.as_secs_f32()).clamp(0.0, 1.0)
+        }
+    }
+
+    fn refresh(&mut self) {
+        let mut t = self.progress();
+        if self.yoyo && self.cycle % 2 == 1 {
+            t = 1.0 - t;
+        }
+        let value = self.from.interpolate(&self.to, self.easing.apply(t));
+        self.value.0.set(value);
+    }
+
+    fn finish(&mut self) {
+        self.finished = true;
+        self.refresh();
+        if let Some(on_complete) = self.on_complete.as_mut() {
+            on_complete();
+        }
+    }
+}
+
+impl<T: Tweenable> Animation for Tween<T> {
+    fn advance(&mut self, delta_time: Duration) -> Duration {
+        if self.finished {
+            return delta_time;
+        }
+        let mut delta_time = delta_time;
+        if self.delay_elapsed < self.delay {
+            let remaining_delay = self.delay - self.delay_elapsed;
+            if delta_time < remaining_delay {
+                self.delay_elapsed += delta_time;
+                return Duration::new(0, 0);
+            }
+            self.delay_elapsed = self.delay;
+            delta_time -= remaining_delay;
+        }
+        if self.duration.is_zero() {
+            self.elapsed = self.duration;
+            return match self.repeat {
+                Some(repeat) => {
+                    self.cycle = repeat;
+                    self.finish();
+                    delta_time
+                }
+                None => {
+                    self.refresh();
+                    Duration::new(0, 0)
+                }
+            };
+        }
+        self.elapsed += delta_time;
+        let cycles = u32::try_from(self.elapsed.as_nanos() / self.duration.as_nanos()).unwrap_or(u32::MAX);
+        match self.repeat {
+            Some(repeat) if cycles > repeat - self.cycle => {
+                let leftover = self.elapsed - self.duration * (repeat - self.cycle + 1);
+                self.cycle = repeat;
+                self.elapsed = self.duration;
+                self.finish();
+                leftover
+            }
+            _ => {
+                self.cycle = self.cycle.wrapping_add(cycles);
+                self.elapsed = Duration::from_nanos((self.elapsed.as_nanos() % self.duration.as_nanos()) as u64);
+                self.refresh();
+                Duration::new(0, 0)
+            }
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    fn reset(&mut self) {
+        self.delay_elapsed = Duration::new(0, 0);
+        self.elapsed = Duration::new(0, 0);
+        self.cycle = 0;
+        self.finished = false;
+        self.refresh();
+    }
+}
+
+pub struct Sequence {
+    animations: Vec<Box<dyn Animation>>,
+    index: usize,
+    finished: bool,
+    on_complete: Option<Box<dyn FnMut()>>,
+}
+
+impl Sequence {
+    pub fn new() -> Self {
+        Self {
+            animations: Vec::new(),
+            index: 0,
+            finished: false,
+            on_complete: None,
+        }
+    }
+
+    pub fn then(mut self, animation: impl Animation + 'static) -> Self {
+        self.animations.push(Box::new(animation));
+        self
+    }
+
+    pub fn on_complete(mut self, on_complete: impl FnMut() + 'static) -> Self {
+        self.on_complete = Some(Box::new(on_complete));
+        self
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn len(&self) -> usize {
+        self.animations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.animations.is_empty()
+    }
+}
+
+impl Default for Sequence {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Animation for Sequence {
+    fn advance(&mut self, delta_time: Duration) -> Duration {
+        if self.finished {
+            return delta_time;
+        }
+        let mut delta_time = delta_time;
+        while let Some(animation) = self.animations.get_mut(self.index) {
+            delta_time = animation.advance(delta_time);
+            if !animation.is_finished() {
+                return Duration::new(0, 0);
+            }
+            self.index += 1;
+        }
+        self.finished = true;
+        if let Some(on_complete) = self.on_complete.as_mut() {
+            on_complete();
+        }
+        delta_time
+    }
+
+    fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    fn reset(&mut self) {
+        for animation in self.animations.iter_mut() {
+            animation.reset();
+        }
+        self.index = 0;
+        self.finished = false;
+    }
+}
+
+pub struct Parallel {
+    animations: Vec<Box<dyn Animation>>,
+    finished: bool,
+    on_complete: Option<Box<dyn FnMut()>>,
+}
+
+impl Parallel {
+    pub fn new() -> Self {
+        Self {
+            animations: Vec::new(),
+            finished: false,
+            on_complete: None,
+        }
+    }
+
+    pub fn with(mut self, animation: impl Animation + 'static) -> Self {
+        self.animations.push(Box::new(animation));
+        self
+    }
+
+    pub fn on_complete(mut self, on_complete: impl FnMut() + 'static) -> Self {
+        self.on_complete = Some(Box::new(on_complete));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.animations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.animations.is_empty()
+    }
+}
+
+impl Default for Parallel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Animation for Parallel {
+    fn advance(&mut self, delta_time: Duration) -> Duration {
+        if self.finished {
+            return delta_time;
+        }
+        let mut leftover = delta_time;
+        for animation in self.animations.iter_mut() {
+            leftover = leftover.min(animation.advance(delta_time));
+        }
+        if self.animations.iter().all(|animation| animation.is_finished()) {
+            self.finished = true;
+            if let Some(on_complete) = self.on_complete.as_mut() {
+                on_complete();
+            }
+            leftover
+        } else {
+            Duration::new(0, 0)
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    fn reset(&mut self) {
+        for animation in self.animations.iter_mut() {
+            animation.reset();
+        }
+        self.finished = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Animation, Tween, Sequence, Parallel, Easing};
+    use crate::math::Vector;
+    use std::rc::Rc;
+    use std::cell::Cell;
+    use std::time::Duration;
+
+    #[test]
+    fn test_tween() {
+        let completed = Rc::new(Cell::new(false));
+        let mut tween = Tween::new(0.0, 10.0, Duration::from_millis(100))
+            .easing(Easing::QuadIn)
+            .repeat(1)
+            .yoyo(true)
+            .on_complete({
+                let completed = completed.clone();
+                move || completed.set(true)
+            });
+        tween.advance(Duration::from_millis(50));
+        assert!((tween.value() - 2.5).abs() < 1e-4);
+        tween.advance(Duration::from_millis(100));
+        assert_eq!(tween.cycle(), 1);
+        assert!((tween.value() - 2.5).abs() < 1e-4);
+        assert_eq!(tween.advance(Duration::from_millis(80)), Duration::from_millis(30));
+        assert!(tween.is_finished());
+        assert!(completed.get());
+        assert_eq!(tween.value(), 0.0);
+        tween.reset();
+        assert!(!tween.is_finished());
+        assert_eq!(tween.value(), 0.0);
+    }
+
+    #[test]
+    fn test_groups() {
+        let first = Tween::new(Vector::new(0.0, 0.0), Vector::new(10.0, 0.0), Duration::from_millis(100));
+        let second = Tween::new(0.0, 1.0, Duration::from_millis(100)).delay(Duration::from_millis(50));
+        let third = Tween::new(0.0, 1.0, Duration::from_millis(200));
+        let first_value = first.value_handle();
+        let second_value = second.value_handle();
+        let third_value = third.value_handle();
+        let mut animation = Parallel::new()
+            .with(Sequence::new().then(first).then(second))
+            .with(third);
+        animation.advance(Duration::from_millis(200));
+        assert_eq!(first_value.get(), Vector::new(10.0, 0.0));
+        assert!((second_value.get() - 0.5).abs() < 1e-4);
+        assert!((third_value.get() - 1.0).abs() < 1e-4);
+        assert!(!animation.is_finished());
+        assert_eq!(animation.advance(Duration::from_millis(70)), Duration::from_millis(20));
+        assert!(animation.is_finished());
+    }
+}
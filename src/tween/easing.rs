@@ -0,0 +1,183 @@
+use std::f32::consts::PI;
+
+const BACK_C1: f32 = 1.70158;
+const BACK_C2: f32 = BACK_C1 * 1.525;
+const BACK_C3: f32 = BACK_C1 + 1.0;
+const ELASTIC_C4: f32 = (2.0 * PI) / 3.0;
+const ELASTIC_C5: f32 = (2.0 * PI) / 4.5;
+
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Default)]
+pub enum Easing {
+    #[default]
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    QuartIn,
+    QuartOut,
+    QuartInOut,
+    QuintIn,
+    QuintOut,
+    QuintInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+    ExpoIn,
+    ExpoOut,
+    ExpoInOut,
+    CircIn,
+    CircOut,
+    CircInOut,
+    BackIn,
+    BackOut,
+    BackInOut,
+    ElasticIn,
+    ElasticOut,
+    ElasticInOut,
+    BounceIn,
+    BounceOut,
+    BounceInOut,
+}
+
+impl Easing {
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => t,
+            Self::QuadIn => t * t,
+            Self::QuadOut => 1.0 - (1.0 - t).powi(2),
+            Self::QuadInOut => if t < 0.5 {
+                2.0 * t * t
+            } else {
+                1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+            },
+            Self::CubicIn => t.powi(3),
+            Self::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Self::CubicInOut => if t < 0.5 {
+                4.0 * t.powi(3)
+            } else {
+                1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+            },
+            Self::QuartIn => t.powi(4),
+            Self::QuartOut => 1.0 - (1.0 - t).powi(4),
+            Self::QuartInOut => if t < 0.5 {
+                8.0 * t.powi(4)
+            } else {
+                1.0 - (-2.0 * t + 2.0).powi(4) / 2.0
+            },
+            Self::QuintIn => t.powi(5),
+            Self::QuintOut => 1.0 - (1.0 - t).powi(5),
+            Self::QuintInOut => if t < 0.5 {
+                16.0 * t.powi(5)
+            } else {
+                1.0 - (-2.0 * t + 2.0).powi(5) / 2.0
+            },
+            Self::SineIn => 1.0 - (t * PI / 2.0).cos(),
+            Self::SineOut => (t * PI / 2.0).sin(),
+            Self::SineInOut => -((PI * t).cos() - 1.0) / 2.0,
+            Self::ExpoIn => if t == 0.0 {
+                0.0
+            } else {
+                2.0f32.powf(10.0 * t - 10.0)
+            },
+            Self::ExpoOut => if t == 1.0 {
+                1.0
+            } else {
+                1.0 - 2.0f32.powf(-10.0 * t)
+            },
+            Self::ExpoInOut => if t == 0.0 || t == 1.0 {
+                t
+            } else if t < 0.5 {
+                2.0f32.powf(20.0 * t - 10.0) / 2.0
+            } else {
+                (2.0 - 2.0f32.powf(-20.0 * t + 10.0)) / 2.0
+            },
+            Self::CircIn => 1.0 - (1.0 - t * t).sqrt(),
+            Self::CircOut => (1.0 - (t - 1.0).powi(2)).sqrt(),
+            Self::CircInOut => if t < 0.5 {
+                (1.0 - (1.0 - (2.0 * t).powi(2)).sqrt()) / 2.0
+            } else {
+                ((1.0 - (-2.0 * t + 2.0).powi(2)).sqrt() + 1.0) / 2.0
+            },
+            Self::BackIn => BACK_C3 * t.powi(3) - BACK_C1 * t * t,
+            Self::BackOut => 1.0 + BACK_C3 * (t - 1.0).powi(3) + BACK_C1 * (t - 1.0).powi(2),
+            Self::BackInOut => if t < 0.5 {
+                ((2.0 * t).powi(2) * ((BACK_C2 + 1.0) * 2.0 * t - BACK_C2)) / 2.0
+            } else {
+                ((2.0 * t - 2.0).powi(2) * ((BACK_C2 + 1.0) * (t * 2.0 - 2.0) + BACK_C2) + 2.0) / 2.0
+            },
+            Self::ElasticIn => if t == 0.0 || t == 1.0 {
+                t
+            } else {
+                -(2.0f32.powf(10.0 * t - 10.0)) * ((t * 10.0 - 10.75) * ELASTIC_C4).sin()
+            },
+            Self::ElasticOut => if t == 0.0 || t == 1.0 {
+                t
+            } else {
+                2.0f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * ELASTIC_C4).sin() + 1.0
+            },
+            Self::ElasticInOut => if t == 0.0 || t == 1.0 {
+                t
+            } else if t < 0.5 {
+                -(2.0f32.powf(20.0 * t - 10.0) * ((20.0 * t - 11.125) * ELASTIC_C5).sin()) / 2.0
+            } else {
+                (2.0f32.powf(-20.0 * t + 10.0) * ((20.0 * t - 11.125) * ELASTIC_C5).sin()) / 2.0 + 1.0
+            },
+            Self::BounceIn => 1.0 - bounce_out(1.0 - t),
+            Self::BounceOut => bounce_out(t),
+            Self::BounceInOut => if t < 0.5 {
+                (1.0 - bounce_out(1.0 - 2.0 * t)) / 2.0
+            } else {
+                (1.0 + bounce_out(2.0 * t - 1.0)) / 2.0
+            },
+        }
+    }
+}
+
+fn bounce_out(t: f32) -> f32 {
+    const N1: f32 = 7.5625;
+    const D1: f32 = 2.75;
+    if t < 1.0 / D1 {
+        N1 * t * t
+    } else if t < 2.0 / D1 {
+        let t = t - 1.5 / D1;
+        N1 * t * t + 0.75
+    } else if t < 2.5 / D1 {
+        let t = t - 2.25 / D1;
+        N1 * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D1;
+        N1 * t * t + 0.984375
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Easing;
+
+    #[test]
+    fn test_endpoints() {
+        let easings = [
+            Easing::Linear,
+            Easing::QuadIn, Easing::QuadOut, Easing::QuadInOut,
+            Easing::CubicIn, Easing::CubicOut, Easing::CubicInOut,
+            Easing::QuartIn, Easing::QuartOut, Easing::QuartInOut,
+            Easing::QuintIn, Easing::QuintOut, Easing::QuintInOut,
+            Easing::SineIn, Easing::SineOut, Easing::SineInOut,
+            Easing::ExpoIn, Easing::ExpoOut, Easing::ExpoInOut,
+            Easing::CircIn, Easing::CircOut, Easing::CircInOut,
+            Easing::BackIn, Easing::BackOut, Easing::BackInOut,
+            Easing::ElasticIn, Easing::ElasticOut, Easing::ElasticInOut,
+            Easing::BounceIn, Easing::BounceOut, Easing::BounceInOut,
+        ];
+        for easing in easings.iter() {
+            assert!(easing.apply(0.0).abs() < 1e-4, "{:?}", easing);
+            assert!((easing.apply(1.0) - 1.0).abs() < 1e-4, "{:?}", easing);
+        }
+        assert!((Easing::QuadInOut.apply(0.5) - 0.5).abs() < 1e-4);
+        assert!(Easing::BackIn.apply(0.2) < 0.0);
+    }
+}
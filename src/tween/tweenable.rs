@@ -0,0 +1,48 @@
+use crate::math::{Vector, Angle, Transform};
+use crate::graphics::Color;
+
+fn lerp(from: f32, to: f32, t: f32) -> f32 {
+    from + (to - from) * t
+}
+
+pub trait Tweenable: Copy + 'static {
+    fn interpolate(&self, target: &Self, t: f32) -> Self;
+}
+
+impl Tweenable for f32 {
+    fn interpolate(&self, target: &Self, t: f32) -> Self {
+        lerp(*self, *target, t)
+    }
+}
+
+impl Tweenable for Vector {
+    fn interpolate(&self, target: &Self, t: f32) -> Self {
+        Vector::new(lerp(self.x, target.x, t), lerp(self.y, target.y, t))
+    }
+}
+
+impl Tweenable for Color {
+    fn interpolate(&self, target: &Self, t: f32) -> Self {
+        Color::new(
+            lerp(self.red, target.red, t),
+            lerp(self.green, target.green, t),
+            lerp(self.blue, target.blue, t),
+            lerp(self.alpha, target.alpha, t),
+        )
+    }
+}
+
+impl Tweenable for Angle {
+    fn interpolate(&self, target: &Self, t: f32) -> Self {
+        match self {
+            Self::Radians(value) => Self::radians(lerp(*value, target.radians_value(), t)),
+            Self::Degrees(value) => Self::degrees(lerp(*value, target.degrees_value(), t)),
+        }
+    }
+}
+
+impl Tweenable for Transform {
+    fn interpolate(&self, target: &Self, t: f32) -> Self {
+        Transform(self.0 * (1.0 - t) + target.0 * t)
+    }
+}
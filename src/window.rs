@@ -1,21 +1,24 @@
 mod icon;
 mod dpi;
 mod fullscreen;
+mod context;
 
 pub use icon::Icon;
 pub use dpi::{LogicalPosition, PhysicalPosition, LogicalSize, PhysicalSize};
 pub use fullscreen::FullscreenMode;
+pub(crate) use context::WindowContext;
 
+use context::HeadlessContext;
 use crate::error::{GameError, GameResult};
 use crate::filesystem::Filesystem;
 use winit::event_loop::EventLoop;
-use winit::window::WindowBuilder;
-use glutin::{ContextBuilder, ContextWrapper, PossiblyCurrent};
+use winit::window::{WindowBuilder, WindowId};
+use glutin::ContextBuilder;
 use glow::Context;
 use std::rc::Rc;
 
 pub struct Window {
-    context_wrapper: Rc<ContextWrapper<PossiblyCurrent, winit::window::Window>>,
+    context: Rc<WindowContext>,
     gl: Rc<Context>,
     title: String,
     resizable: bool,
@@ -25,13 +28,15 @@ pub struct Window {
     always_on_top: bool,
     visible: bool,
     focused: bool,
+    ime_allowed: bool,
+    headless_resize: Option<winit::dpi::PhysicalSize<u32>>,
 }
 
 impl Window {
     pub(crate) fn new(window_config: WindowConfig, event_loop: &EventLoop<()>, filesystem: &Filesystem) -> GameResult<Self> {
         let mut window_builder = WindowBuilder::new()
             .with_title(&window_config.title)
-            .with_window_icon(match window_config.icon {
+            .with_window_icon(match &window_config.icon {
                 Some(path) => {
                     let bytes = filesystem.read(path)?;
                     let icon = Icon::from_bytes(&bytes)?;
@@ -70,11 +75,27 @@ impl Window {
             windowed_context.make_current()
                 .map_err(|(_, error)| GameError::InitError(error.into()))?
         };
+        Ok(Self::from_context(WindowContext::Windowed(context_wrapper), window_config))
+    }
+
+    pub(crate) fn new_headless(window_config: WindowConfig) -> GameResult<Self> {
+        let physical_size = match window_config.inner_size {
+            Some(size) => winit::dpi::PhysicalSize::new(size.width.round() as u32, size.height.round() as u32),
+            None => winit::dpi::PhysicalSize::new(800, 600),
+        };
+        let context = HeadlessContext::new(ContextBuilder::new(), physical_size)?;
+        Ok(Self::from_context(WindowContext::Headless(context), window_config))
+    }
+
+    fn from_context(context: WindowContext, window_config: WindowConfig) -> Self {
+        if let Some(window) = context.window() {
+            window.set_ime_allowed(window_config.ime_allowed);
+        }
         let gl = unsafe {
-            Context::from_loader_function(|symbol| context_wrapper.get_proc_address(symbol).cast())
+            Context::from_loader_function(|symbol| context.get_proc_address(symbol).cast())
         };
-        Ok(Self {
-            context_wrapper: Rc::new(context_wrapper),
+        Self {
+            context: Rc::new(context),
             gl: Rc::new(gl),
             title: window_config.title,
             resizable: window_config.resizable,
@@ -84,19 +105,35 @@ impl Window {
             always_on_top: window_config.always_on_top,
             visible: window_config.visible,
             focused: false,
-        })
+            ime_allowed: window_config.ime_allowed,
+            headless_resize: None,
+        }
     }
 
-    pub(crate) fn context_wrapper(&self) -> Rc<ContextWrapper<PossiblyCurrent, winit::window::Window>> {
-        self.context_wrapper.clone()
+    pub(crate) fn context(&self) -> Rc<WindowContext> {
+        self.context.clone()
     }
 
     pub(crate) fn gl(&self) -> Rc<Context> {
         self.gl.clone()
     }
 
-    pub(crate) fn window(&self) -> &winit::window::Window {
-        self.context_wrapper.window()
+    pub(crate) fn window(&self) -> Option<&winit::window::Window> {
+        self.context.window()
+    }
+
+    pub(crate) fn id(&self) -> Option<WindowId> {
+        self.window().map(|window| window.id())
+    }
+
+    pub(crate) fn request_redraw(&self) {
+        if let Some(window) = self.window() {
+            window.request_redraw();
+        }
+    }
+
+    pub fn is_headless(&self) -> bool {
+        self.context.is_headless()
     }
 
     pub(crate) fn handle_focus_change_event(&mut self, focused: bool) {
@@ -109,94 +146,138 @@ impl Window {
 
     pub fn set_title(&mut self, title: impl Into<String>) {
         self.title = title.into();
-        self.window().set_title(&self.title);
+        if let Some(window) = self.window() {
+            window.set_title(&self.title);
+        }
     }
 
     pub fn set_icon(&mut self, icon: Option<Icon>) {
-        self.window().set_window_icon(icon.map(|icon| icon.into()));
+        if let Some(window) = self.window() {
+            window.set_window_icon(icon.map(|icon| icon.into()));
+        }
     }
 
     pub fn inner_size(&self) -> LogicalSize {
-        let physical_size = self.window().inner_size();
-        let scale_factor = self.window().scale_factor();
+        let physical_size = self.context.inner_size();
+        let scale_factor = self.context.scale_factor();
         let logical_size = physical_size.to_logical(scale_factor);
         LogicalSize::new(logical_size.width, logical_size.height)
     }
 
     pub fn set_inner_size(&mut self, size: impl Into<LogicalSize>) {
         let size = size.into();
-        self.window().set_inner_size(winit::dpi::LogicalSize::new(size.width, size.height));
+        match self.window() {
+            Some(window) => window.set_inner_size(winit::dpi::LogicalSize::new(size.width, size.height)),
+            None => self.headless_resize = Some(winit::dpi::PhysicalSize::new(size.width.round().max(1.0) as u32, size.height.round().max(1.0) as u32)),
+        }
+    }
+
+    pub(crate) fn take_headless_resize(&mut self) -> Option<winit::dpi::PhysicalSize<u32>> {
+        self.headless_resize.take()
     }
 
     pub fn outer_size(&self) -> LogicalSize {
-        let physical_size = self.window().outer_size();
-        let scale_factor = self.window().scale_factor();
+        let physical_size = match self.window() {
+            Some(window) => window.outer_size(),
+            None => self.context.inner_size(),
+        };
+        let scale_factor = self.context.scale_factor();
         let logical_size = physical_size.to_logical(scale_factor);
         LogicalSize::new(logical_size.width, logical_size.height)
     }
 
     pub fn set_min_inner_size(&mut self, size: Option<impl Into<LogicalSize>>) {
-        self.window().set_min_inner_size(size.map(|size| {
-            let size = size.into();
-            winit::dpi::LogicalSize::new(size.width, size.height)
-        }));
+        if let Some(window) = self.window() {
+            window.set_min_inner_size(size.map(|size| {
+                let size = size.into();
+                winit::dpi::LogicalSize::new(size.width, size.height)
+            }));
+        }
     }
 
     pub fn set_max_inner_size(&mut self, size: Option<impl Into<LogicalSize>>) {
-        self.window().set_max_inner_size(size.map(|size| {
-            let size = size.into();
-            winit::dpi::LogicalSize::new(size.width, size.height)
-        }));
+        if let Some(window) = self.window() {
+            window.set_max_inner_size(size.map(|size| {
+                let size = size.into();
+                winit::dpi::LogicalSize::new(size.width, size.height)
+            }));
+        }
     }
 
     pub fn inner_position(&self) -> GameResult<LogicalPosition> {
-        let physical_position = self.window().inner_position()
+        let window = self.window()
+            .ok_or_else(|| GameError::NotSupportedError("headless window has no position".into()))?;
+        let physical_position = window.inner_position()
             .map_err(|error| GameError::NotSupportedError(error.into()))?;
-        let scale_factor = self.window().scale_factor();
+        let scale_factor = window.scale_factor();
         let logical_position = physical_position.to_logical(scale_factor);
         Ok(LogicalPosition::new(logical_position.x, logical_position.y))
     }
 
     pub fn outer_position(&self) -> GameResult<LogicalPosition> {
-        let physical_position = self.window().outer_position()
+        let window = self.window()
+            .ok_or_else(|| GameError::NotSupportedError("headless window has no position".into()))?;
+        let physical_position = window.outer_position()
             .map_err(|error| GameError::NotSupportedError(error.into()))?;
-        let scale_factor = self.window().scale_factor();
+        let scale_factor = window.scale_factor();
         let logical_position = physical_position.to_logical(scale_factor);
         Ok(LogicalPosition::new(logical_position.x, logical_position.y))
     }
 
     pub fn set_outer_position(&mut self, position: impl Into<LogicalPosition>) {
         let position = position.into();
-        self.window().set_outer_position(winit::dpi::LogicalPosition::new(position.x, position.y));
+        if let Some(window) = self.window() {
+            window.set_outer_position(winit::dpi::LogicalPosition::new(position.x, position.y));
+        }
     }
 
     pub fn set_ime_position(&mut self, position: impl Into<LogicalPosition>) {
         let position = position.into();
-        self.window().set_ime_position(winit::dpi::LogicalPosition::new(position.x, position.y));
+        if let Some(window) = self.window() {
+            window.set_ime_position(winit::dpi::LogicalPosition::new(position.x, position.y));
+        }
+    }
+
+    pub fn is_ime_allowed(&self) -> bool {
+        self.ime_allowed
+    }
+
+    pub fn set_ime_allowed(&mut self, ime_allowed: bool) {
+        self.ime_allowed = ime_allowed;
+        if let Some(window) = self.window() {
+            window.set_ime_allowed(ime_allowed);
+        }
     }
 
     pub fn scale_factor(&self) -> f32 {
-        self.window().scale_factor() as f32
+        self.context.scale_factor() as f32
     }
 
     pub fn fullscreen(&self) -> Option<FullscreenMode> {
-        self.window().fullscreen()
+        self.window()
+            .and_then(|window| window.fullscreen())
             .map(|fullscreen| FullscreenMode::from_raw(fullscreen))
     }
 
     pub fn is_fullscreen(&self) -> bool {
-        self.window().fullscreen().is_some()
+        self.window()
+            .map(|window| window.fullscreen().is_some())
+            .unwrap_or(false)
     }
 
     pub fn set_fullscreen(&mut self, fullscreen: Option<FullscreenMode>) -> GameResult {
+        let window = match self.window() {
+            Some(window) => window,
+            None => return Ok(()),
+        };
         let fullscreen = match fullscreen {
             Some(fullscreen_mode) => {
-                let monitor = self.window().current_monitor();
+                let monitor = window.current_monitor();
                 Some(fullscreen_mode.into_raw(monitor)?)
             }
             None => None,
         };
-        self.window().set_fullscreen(fullscreen);
+        window.set_fullscreen(fullscreen);
         Ok(())
     }
 
@@ -206,7 +287,9 @@ impl Window {
 
     pub fn set_resizable(&mut self, resizable: bool) {
         self.resizable = resizable;
-        self.window().set_resizable(self.resizable);
+        if let Some(window) = self.window() {
+            window.set_resizable(self.resizable);
+        }
     }
 
     pub fn is_maximized(&self) -> bool {
@@ -215,7 +298,9 @@ impl Window {
 
     pub fn set_maximized(&mut self, maximized: bool) {
         self.maximized = maximized;
-        self.window().set_maximized(self.maximized);
+        if let Some(window) = self.window() {
+            window.set_maximized(self.maximized);
+        }
     }
 
     pub fn is_transparent(&self) -> bool {
@@ -228,7 +313,9 @@ impl Window {
 
     pub fn set_decorations(&mut self, decorations: bool) {
         self.decorations = decorations;
-        self.window().set_decorations(self.decorations);
+        if let Some(window) = self.window() {
+            window.set_decorations(self.decorations);
+        }
     }
 
     pub fn is_always_on_top(&self) -> bool {
@@ -237,7 +324,9 @@ impl Window {
 
     pub fn set_always_on_top(&mut self, always_on_top: bool) {
         self.always_on_top = always_on_top;
-        self.window().set_always_on_top(self.always_on_top);
+        if let Some(window) = self.window() {
+            window.set_always_on_top(self.always_on_top);
+        }
     }
 
     pub fn is_visible(&self) -> bool {
@@ -246,7 +335,9 @@ impl Window {
 
     pub fn set_visible(&mut self, visible: bool) {
         self.visible = visible;
-        self.window().set_visible(self.visible);
+        if let Some(window) = self.window() {
+            window.set_visible(self.visible);
+        }
     }
 
     pub fn is_focused(&self) -> bool {
@@ -269,6 +360,7 @@ pub struct WindowConfig {
     always_on_top: bool,
     visible: bool,
     vsync: bool,
+    ime_allowed: bool,
 }
 
 impl WindowConfig {
@@ -287,6 +379,7 @@ impl WindowConfig {
             always_on_top: false,
             visible: true,
             vsync: false,
+            ime_allowed: false,
         }
     }
 
@@ -354,4 +447,9 @@ impl WindowConfig {
         self.vsync = vsync;
         self
     }
+
+    pub fn ime_allowed(mut self, ime_allowed: bool) -> Self {
+        self.ime_allowed = ime_allowed;
+        self
+    }
 }
@@ -0,0 +1,141 @@
+#[cfg(any(target_os = "linux", target_os = "dragonfly", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd"))]
+mod egl;
+
+use crate::error::{GameError, GameResult};
+use winit::dpi::PhysicalSize;
+use glutin::{ContextBuilder, ContextWrapper, PossiblyCurrent, NotCurrent, ContextError};
+use std::cell::Cell;
+
+enum HeadlessBackend {
+    #[cfg(any(target_os = "linux", target_os = "dragonfly", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd"))]
+    Egl(Box<egl::EglContext>),
+    Glutin(glutin::Context<PossiblyCurrent>),
+}
+
+pub(crate) struct HeadlessContext {
+    backend: HeadlessBackend,
+    physical_size: Cell<PhysicalSize<u32>>,
+}
+
+impl HeadlessContext {
+    pub fn new(context_builder: ContextBuilder<NotCurrent>, physical_size: PhysicalSize<u32>) -> GameResult<Self> {
+        #[cfg(any(target_os = "linux", target_os = "dragonfly", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd"))]
+        let backend = match egl::EglContext::new(physical_size) {
+            Ok(context) => HeadlessBackend::Egl(Box::new(context)),
+            Err(egl_error) => {
+                use glutin::platform::unix::HeadlessContextExt;
+                let context = context_builder.build_osmesa(physical_size)
+                    .map_err(|error| GameError::InitError(format!("{}; osmesa: {}", egl_error, error).into()))?;
+                HeadlessBackend::Glutin(make_current(context)?)
+            }
+        };
+        #[cfg(not(any(target_os = "linux", target_os = "dragonfly", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd")))]
+        let backend = {
+            let context = with_headless_event_loop(|event_loop| context_builder.build_headless(event_loop, physical_size))?
+                .map_err(|error| GameError::InitError(error.into()))?;
+            HeadlessBackend::Glutin(make_current(context)?)
+        };
+        Ok(Self {
+            backend,
+            physical_size: Cell::new(physical_size),
+        })
+    }
+
+    fn get_proc_address(&self, symbol: &str) -> *const core::ffi::c_void {
+        match &self.backend {
+            #[cfg(any(target_os = "linux", target_os = "dragonfly", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd"))]
+            HeadlessBackend::Egl(context) => context.get_proc_address(symbol),
+            HeadlessBackend::Glutin(context) => context.get_proc_address(symbol),
+        }
+    }
+
+    fn resize(&self, physical_size: PhysicalSize<u32>) {
+        #[cfg(any(target_os = "linux", target_os = "dragonfly", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd"))]
+        if let HeadlessBackend::Egl(context) = &self.backend {
+            context.resize(physical_size);
+        }
+        self.physical_size.set(physical_size);
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "dragonfly", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd")))]
+thread_local! {
+    static HEADLESS_EVENT_LOOP: std::cell::RefCell<Option<winit::event_loop::EventLoop<()>>> = std::cell::RefCell::new(None);
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "dragonfly", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd")))]
+fn with_headless_event_loop<R>(f: impl FnOnce(&winit::event_loop::EventLoop<()>) -> R) -> GameResult<R> {
+    HEADLESS_EVENT_LOOP.with(|event_loop| {
+        let mut event_loop = event_loop.borrow_mut();
+        if event_loop.is_none() {
+            let created = std::panic::catch_unwind(|| {
+                let mut builder = winit::event_loop::EventLoopBuilder::new();
+                #[cfg(target_os = "windows")]
+                winit::platform::windows::EventLoopBuilderExtWindows::with_any_thread(&mut builder, true);
+                builder.build()
+            }).map_err(|_| GameError::InitError("headless context needs an event loop, which can only be created once per process and on the main thread on this platform".into()))?;
+            *event_loop = Some(created);
+        }
+        Ok(f(event_loop.as_ref().unwrap()))
+    })
+}
+
+fn make_current(context: glutin::Context<NotCurrent>) -> GameResult<glutin::Context<PossiblyCurrent>> {
+    unsafe {
+        context.make_current()
+            .map_err(|(_, error)| GameError::InitError(error.into()))
+    }
+}
+
+pub(crate) enum WindowContext {
+    Windowed(ContextWrapper<PossiblyCurrent, winit::window::Window>),
+    Headless(HeadlessContext),
+}
+
+impl WindowContext {
+    pub fn window(&self) -> Option<&winit::window::Window> {
+        match self {
+            Self::Windowed(context_wrapper) => Some(context_wrapper.window()),
+            Self::Headless(_) => None,
+        }
+    }
+
+    pub fn is_headless(&self) -> bool {
+        matches!(self, Self::Headless(_))
+    }
+
+    pub fn get_proc_address(&self, symbol: &str) -> *const core::ffi::c_void {
+        match self {
+            Self::Windowed(context_wrapper) => context_wrapper.get_proc_address(symbol),
+            Self::Headless(headless) => headless.get_proc_address(symbol),
+        }
+    }
+
+    pub fn inner_size(&self) -> PhysicalSize<u32> {
+        match self {
+            Self::Windowed(context_wrapper) => context_wrapper.window().inner_size(),
+            Self::Headless(headless) => headless.physical_size.get(),
+        }
+    }
+
+    pub fn scale_factor(&self) -> f64 {
+        match self {
+            Self::Windowed(context_wrapper) => context_wrapper.window().scale_factor(),
+            Self::Headless(_) => 1.0,
+        }
+    }
+
+    pub fn resize(&self, physical_size: PhysicalSize<u32>) {
+        match self {
+            Self::Windowed(context_wrapper) => context_wrapper.resize(physical_size),
+            Self::Headless(headless) => headless.resize(physical_size),
+        }
+    }
+
+    pub fn swap_buffers(&self) -> Result<(), ContextError> {
+        match self {
+            Self::Windowed(context_wrapper) => context_wrapper.swap_buffers(),
+            Self::Headless(_) => Ok(()),
+        }
+    }
+}
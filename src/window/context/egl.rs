@@ -0,0 +1,182 @@
+use crate::error::{GameError, GameResult};
+use glutin_egl_sys::egl;
+use glutin_egl_sys::egl::types::{EGLConfig, EGLContext, EGLDisplay, EGLSurface, EGLenum, EGLint};
+use libloading::Library;
+use winit::dpi::PhysicalSize;
+use std::cell::Cell;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_void};
+
+const PLATFORM_SURFACELESS_MESA: EGLenum = 0x31DD;
+
+pub(crate) struct EglContext {
+    egl: egl::Egl,
+    display: EGLDisplay,
+    config: EGLConfig,
+    context: EGLContext,
+    surface: Cell<EGLSurface>,
+    _library: Library,
+}
+
+impl EglContext {
+    pub fn new(physical_size: PhysicalSize<u32>) -> GameResult<Self> {
+        let library = unsafe { Library::new("libEGL.so.1").or_else(|_| Library::new("libEGL.so")) }
+            .map_err(|error| GameError::InitError(error.into()))?;
+        let get_proc_address = unsafe {
+            library.get::<extern "system" fn(*const c_char) -> *const c_void>(b"eglGetProcAddress\0")
+                .map(|symbol| *symbol)
+                .map_err(|error| GameError::InitError(error.into()))?
+        };
+        let egl = egl::Egl::load_with(|name| {
+            let name = CString::new(name).unwrap();
+            unsafe {
+                match library.get::<*const c_void>(name.as_bytes_with_nul()) {
+                    Ok(symbol) => *symbol,
+                    Err(_) => get_proc_address(name.as_ptr()),
+                }
+            }
+        });
+        let mut context = Self {
+            egl,
+            display: egl::NO_DISPLAY,
+            config: std::ptr::null(),
+            context: egl::NO_CONTEXT,
+            surface: Cell::new(egl::NO_SURFACE),
+            _library: library,
+        };
+        unsafe { context.init(physical_size)? };
+        Ok(context)
+    }
+
+    unsafe fn init(&mut self, physical_size: PhysicalSize<u32>) -> GameResult {
+        let client_extensions = query_extensions(&self.egl, egl::NO_DISPLAY);
+        self.display = if client_extensions.iter().any(|extension| extension == "EGL_MESA_platform_surfaceless")
+            && self.egl.GetPlatformDisplayEXT.is_loaded() {
+            self.egl.GetPlatformDisplayEXT(PLATFORM_SURFACELESS_MESA, egl::DEFAULT_DISPLAY as *mut _, std::ptr::null())
+        } else {
+            self.egl.GetDisplay(egl::DEFAULT_DISPLAY)
+        };
+        if self.display == egl::NO_DISPLAY {
+            return Err(self.error("no egl display"));
+        }
+        let (mut major, mut minor) = (0, 0);
+        if self.egl.Initialize(self.display, &mut major, &mut minor) == egl::FALSE {
+            self.display = egl::NO_DISPLAY;
+            return Err(self.error("egl initialize failed"));
+        }
+        if self.egl.BindAPI(egl::OPENGL_API) == egl::FALSE {
+            return Err(self.error("egl does not support opengl"));
+        }
+        let surface_types = [egl::PBUFFER_BIT as EGLint, 0];
+        let mut pbuffer = false;
+        for surface_type in surface_types.iter() {
+            let config_attributes = [
+                egl::SURFACE_TYPE as EGLint, *surface_type,
+                egl::RENDERABLE_TYPE as EGLint, egl::OPENGL_BIT as EGLint,
+                egl::RED_SIZE as EGLint, 8,
+                egl::GREEN_SIZE as EGLint, 8,
+                egl::BLUE_SIZE as EGLint, 8,
+                egl::ALPHA_SIZE as EGLint, 8,
+                egl::NONE as EGLint,
+            ];
+            let mut config_count = 0;
+            if self.egl.ChooseConfig(self.display, config_attributes.as_ptr(), &mut self.config, 1, &mut config_count) != egl::FALSE
+                && config_count > 0 {
+                pbuffer = *surface_type != 0;
+                break;
+            }
+        }
+        if self.config.is_null() {
+            return Err(self.error("no suitable egl config"));
+        }
+        let context_attributes = [
+            egl::CONTEXT_MAJOR_VERSION as EGLint, 3,
+            egl::CONTEXT_MINOR_VERSION as EGLint, 3,
+            egl::CONTEXT_OPENGL_PROFILE_MASK as EGLint, egl::CONTEXT_OPENGL_CORE_PROFILE_BIT as EGLint,
+            egl::NONE as EGLint,
+        ];
+        self.context = self.egl.CreateContext(self.display, self.config, egl::NO_CONTEXT, context_attributes.as_ptr());
+        if self.context == egl::NO_CONTEXT {
+            return Err(self.error("egl create context failed"));
+        }
+        if pbuffer {
+            self.surface.set(self.create_pbuffer(physical_size)?);
+        } else if !query_extensions(&self.egl, self.display).iter().any(|extension| extension == "EGL_KHR_surfaceless_context") {
+            return Err(self.error("egl supports neither pbuffer nor surfaceless contexts"));
+        }
+        self.make_current(self.surface.get())
+    }
+
+    unsafe fn create_pbuffer(&self, physical_size: PhysicalSize<u32>) -> GameResult<EGLSurface> {
+        let surface_attributes = [
+            egl::WIDTH as EGLint, physical_size.width.max(1) as EGLint,
+            egl::HEIGHT as EGLint, physical_size.height.max(1) as EGLint,
+            egl::NONE as EGLint,
+        ];
+        let surface = self.egl.CreatePbufferSurface(self.display, self.config, surface_attributes.as_ptr());
+        if surface == egl::NO_SURFACE {
+            return Err(self.error("egl create pbuffer failed"));
+        }
+        Ok(surface)
+    }
+
+    unsafe fn make_current(&self, surface: EGLSurface) -> GameResult {
+        if self.egl.MakeCurrent(self.display, surface, surface, self.context) == egl::FALSE {
+            return Err(self.error("egl make current failed"));
+        }
+        Ok(())
+    }
+
+    fn error(&self, message: &str) -> GameError {
+        let code = unsafe { self.egl.GetError() };
+        GameError::InitError(format!("{} (0x{:x})", message, code).into())
+    }
+
+    pub fn get_proc_address(&self, symbol: &str) -> *const c_void {
+        let symbol = CString::new(symbol).unwrap();
+        unsafe { self.egl.GetProcAddress(symbol.as_ptr()) as *const c_void }
+    }
+
+    pub fn resize(&self, physical_size: PhysicalSize<u32>) {
+        let old_surface = self.surface.get();
+        if old_surface == egl::NO_SURFACE {
+            return;
+        }
+        unsafe {
+            if let Ok(surface) = self.create_pbuffer(physical_size) {
+                if self.make_current(surface).is_ok() {
+                    self.egl.DestroySurface(self.display, old_surface);
+                    self.surface.set(surface);
+                } else {
+                    self.egl.DestroySurface(self.display, surface);
+                }
+            }
+        }
+    }
+}
+
+impl Drop for EglContext {
+    fn drop(&mut self) {
+        if self.display == egl::NO_DISPLAY {
+            return;
+        }
+        unsafe {
+            self.egl.MakeCurrent(self.display, egl::NO_SURFACE, egl::NO_SURFACE, egl::NO_CONTEXT);
+            if self.surface.get() != egl::NO_SURFACE {
+                self.egl.DestroySurface(self.display, self.surface.get());
+            }
+            if self.context != egl::NO_CONTEXT {
+                self.egl.DestroyContext(self.display, self.context);
+            }
+            self.egl.Terminate(self.display);
+        }
+    }
+}
+
+unsafe fn query_extensions(egl: &egl::Egl, display: EGLDisplay) -> Vec<String> {
+    let extensions = egl.QueryString(display, egl::EXTENSIONS as EGLint);
+    if extensions.is_null() {
+        return Vec::new();
+    }
+    CStr::from_ptr(extensions).to_string_lossy().split_whitespace().map(str::to_owned).collect()
+}